@@ -0,0 +1,38 @@
+CREATE TABLE tours (
+    tour_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tour_name TEXT NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    user_id UUID REFERENCES users (user_id) NOT NULL,
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    updated_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+
+    CHECK (start_date <= end_date)
+);
+
+CREATE TABLE tour_dates (
+    tour_date_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tour_id UUID REFERENCES tours (tour_id) ON DELETE CASCADE NOT NULL,
+    show_date DATE NOT NULL,
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    updated_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+-- Contacts are attached to the date row rather than the calendar date so
+-- moving a date keeps everything attached to it
+CREATE TABLE tour_dates_contacts (
+    PRIMARY KEY (tour_date_id, contact_id),
+    tour_date_id UUID REFERENCES tour_dates (tour_date_id) ON DELETE CASCADE NOT NULL,
+    contact_id INT REFERENCES contacts (contact_id) ON DELETE CASCADE NOT NULL,
+    status TEXT NOT NULL DEFAULT 'contacted'
+        CHECK (status IN ('contacted', 'pending', 'confirmed', 'n/a')),
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    updated_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+CREATE INDEX idx_tours_user_id ON tours (user_id);
+CREATE INDEX idx_tour_dates_tour_id ON tour_dates (tour_id);
+CREATE INDEX idx_tour_dates_contacts_contact_id ON tour_dates_contacts (contact_id);
@@ -0,0 +1,18 @@
+CREATE TABLE geocoding_jobs (
+    job_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contact_id INT REFERENCES contacts (contact_id) ON DELETE CASCADE NOT NULL,
+    reason TEXT NOT NULL
+        CHECK (reason IN ('approval', 'address_change', 'backfill')),
+    status TEXT NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'done', 'failed')),
+    attempts INT NOT NULL DEFAULT 0,
+    run_after TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    last_error TEXT,
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    updated_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+-- A contact only ever has one job waiting, re-queueing bumps the existing one
+CREATE UNIQUE INDEX idx_geocoding_jobs_queued_contact ON geocoding_jobs (contact_id) WHERE status = 'queued';
+CREATE INDEX idx_geocoding_jobs_run_after ON geocoding_jobs (run_after) WHERE status = 'queued';
@@ -0,0 +1,10 @@
+-- Normalised address from the last successful geocode. The address columns
+-- keep whatever was typed in.
+ALTER TABLE contacts
+    ADD COLUMN geocoded_address TEXT,
+    ADD COLUMN geocoded_locality TEXT,
+    ADD COLUMN geocoded_region TEXT,
+    ADD COLUMN geocoded_postal_code TEXT,
+    ADD COLUMN geocoded_country TEXT,
+    ADD COLUMN address_mismatches TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN geocoded_at TIMESTAMP(3);
@@ -0,0 +1,11 @@
+-- Lets calendar apps subscribe to a tour without logging in. Deleting the
+-- row revokes the subscription URL.
+CREATE TABLE calendar_tokens (
+    calendar_token TEXT NOT NULL,
+    tour_id UUID NOT NULL REFERENCES tours (tour_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (user_id),
+    PRIMARY KEY (calendar_token),
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+CREATE INDEX idx_calendar_tokens_tour_id ON calendar_tokens (tour_id);
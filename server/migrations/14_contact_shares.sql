@@ -0,0 +1,14 @@
+-- Grants another user access to a private contact
+CREATE TABLE contact_shares (
+    PRIMARY KEY (contact_id, user_id),
+    contact_id INT REFERENCES contacts (contact_id) ON DELETE CASCADE NOT NULL,
+    user_id UUID REFERENCES users (user_id) ON DELETE CASCADE NOT NULL,
+    shared_by UUID REFERENCES users (user_id) ON DELETE CASCADE NOT NULL,
+    access TEXT NOT NULL DEFAULT 'read'
+        CHECK (access IN ('read', 'edit')),
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    updated_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+CREATE INDEX idx_contact_shares_user_id ON contact_shares (user_id);
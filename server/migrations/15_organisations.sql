@@ -0,0 +1,44 @@
+-- Bands and other groups that share a contact book and tours
+CREATE TABLE organisations (
+    org_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    org_name TEXT NOT NULL,
+    created_by UUID REFERENCES users (user_id) NOT NULL,
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    updated_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+CREATE TABLE organisation_members (
+    PRIMARY KEY (org_id, user_id),
+    org_id UUID REFERENCES organisations (org_id) ON DELETE CASCADE NOT NULL,
+    user_id UUID REFERENCES users (user_id) ON DELETE CASCADE NOT NULL,
+    role TEXT NOT NULL DEFAULT 'member'
+        CHECK (role IN ('owner', 'booker', 'member')),
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    updated_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+-- Invites are sent by email and accepted by whoever logs in with that email
+CREATE TABLE organisation_invites (
+    invite_token TEXT PRIMARY KEY,
+    org_id UUID REFERENCES organisations (org_id) ON DELETE CASCADE NOT NULL,
+    email TEXT NOT NULL,
+    role TEXT NOT NULL DEFAULT 'member'
+        CHECK (role IN ('owner', 'booker', 'member')),
+    invited_by UUID REFERENCES users (user_id) ON DELETE CASCADE NOT NULL,
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+-- `user_id` stays as whoever created the row; `org_id` is set when it was
+-- created on behalf of an organisation
+ALTER TABLE contacts ADD COLUMN org_id UUID REFERENCES organisations (org_id) ON DELETE SET NULL;
+ALTER TABLE tours ADD COLUMN org_id UUID REFERENCES organisations (org_id) ON DELETE SET NULL;
+ALTER TABLE reviews ADD COLUMN org_id UUID REFERENCES organisations (org_id) ON DELETE SET NULL;
+
+CREATE INDEX idx_organisation_members_user_id ON organisation_members (user_id);
+CREATE INDEX idx_organisation_invites_org_id ON organisation_invites (org_id);
+CREATE INDEX idx_contacts_org_id ON contacts (org_id);
+CREATE INDEX idx_tours_org_id ON tours (org_id);
+CREATE INDEX idx_reviews_org_id ON reviews (org_id);
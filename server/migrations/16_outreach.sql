@@ -0,0 +1,19 @@
+-- Every booking inquiry a user sends to a contact, so replies and
+-- follow-ups can be tracked per venue.
+CREATE TABLE outreach (
+    outreach_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    contact_id INT NOT NULL REFERENCES contacts (contact_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (user_id),
+    outreach_date DATE NOT NULL,
+    channel TEXT NOT NULL CHECK (channel IN ('email', 'form', 'phone', 'social')),
+    requested_from DATE,
+    requested_to DATE,
+    outcome TEXT NOT NULL DEFAULT 'no_reply' CHECK (outcome IN ('no_reply', 'declined', 'hold', 'offer')),
+    notes TEXT,
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    updated_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+CREATE INDEX idx_outreach_user_contact ON outreach (user_id, contact_id);
+CREATE INDEX idx_outreach_date ON outreach (outreach_date);
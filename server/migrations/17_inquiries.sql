@@ -0,0 +1,31 @@
+-- Reusable booking inquiry emails. Placeholders like {{venue_name}} are
+-- filled in per contact when sent.
+CREATE TABLE inquiry_templates (
+    template_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users (user_id),
+    template_name TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    updated_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+CREATE INDEX idx_inquiry_templates_user_id ON inquiry_templates (user_id);
+
+-- Every inquiry sent through the platform, as rendered. Also used to
+-- enforce the daily send limit.
+CREATE TABLE sent_inquiries (
+    inquiry_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users (user_id),
+    contact_id INT NOT NULL REFERENCES contacts (contact_id) ON DELETE CASCADE,
+    template_id UUID REFERENCES inquiry_templates (template_id) ON DELETE SET NULL,
+    outreach_id UUID REFERENCES outreach (outreach_id) ON DELETE SET NULL,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+
+    sent_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+CREATE INDEX idx_sent_inquiries_user_sent ON sent_inquiries (user_id, sent_at);
@@ -0,0 +1,18 @@
+CREATE TABLE email_outbox (
+    email_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    recipient TEXT NOT NULL,
+    reply_to TEXT,
+    subject TEXT NOT NULL,
+    html_body TEXT NOT NULL,
+    text_body TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'sent', 'failed')),
+    attempts INT NOT NULL DEFAULT 0,
+    run_after TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    last_error TEXT,
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL,
+    updated_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+CREATE INDEX idx_email_outbox_run_after ON email_outbox (run_after) WHERE status = 'queued';
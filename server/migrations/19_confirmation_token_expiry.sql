@@ -0,0 +1,4 @@
+-- Tokens issued before this are treated as new, so they get a full day
+ALTER TABLE confirmation_tokens ADD COLUMN created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL;
+
+CREATE INDEX idx_confirmation_tokens_user_id ON confirmation_tokens (user_id);
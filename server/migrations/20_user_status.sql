@@ -0,0 +1,5 @@
+UPDATE users SET status = 'pending' WHERE status IS NULL;
+
+ALTER TABLE users ALTER COLUMN status SET NOT NULL;
+ALTER TABLE users ADD CONSTRAINT users_status_check
+    CHECK (status IN ('pending', 'confirmed', 'suspended', 'banned'));
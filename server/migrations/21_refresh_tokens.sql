@@ -0,0 +1,19 @@
+-- Refresh tokens are stored hashed. Each refresh replaces the token with a
+-- new one in the same family, and the old one is kept so reusing it can be
+-- spotted.
+CREATE TABLE refresh_tokens (
+    token_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    family_id UUID NOT NULL,
+    user_id UUID REFERENCES users (user_id) ON DELETE CASCADE NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    -- Organisation the user was acting for, carried over to new access tokens
+    org_id UUID REFERENCES organisations (org_id) ON DELETE SET NULL,
+    expires_at TIMESTAMP(3) NOT NULL,
+    used_at TIMESTAMP(3),
+    revoked_at TIMESTAMP(3),
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+CREATE INDEX idx_refresh_tokens_family_id ON refresh_tokens (family_id);
+CREATE INDEX idx_refresh_tokens_user_id ON refresh_tokens (user_id);
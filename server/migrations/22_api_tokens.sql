@@ -0,0 +1,20 @@
+-- Long-lived tokens users make for scripts. Stored hashed, like refresh
+-- tokens, and shown once when created.
+CREATE TABLE api_tokens (
+    token_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES users (user_id) ON DELETE CASCADE NOT NULL,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL CHECK (
+        cardinality(scopes) > 0
+        AND scopes <@ ARRAY['read', 'contacts:write', 'tours:write']
+    ),
+    -- Organisation the token acts for, as with a session
+    org_id UUID REFERENCES organisations (org_id) ON DELETE SET NULL,
+    last_used_at TIMESTAMP(3),
+    revoked_at TIMESTAMP(3),
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+CREATE INDEX idx_api_tokens_user_id ON api_tokens (user_id);
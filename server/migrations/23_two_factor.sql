@@ -0,0 +1,30 @@
+-- TOTP two-factor authentication. A secret is saved when the user starts
+-- enrolling and only takes effect once they've proved they can generate
+-- codes from it.
+CREATE TABLE user_totp (
+    user_id UUID PRIMARY KEY REFERENCES users (user_id) ON DELETE CASCADE,
+    secret TEXT NOT NULL,
+    enabled_at TIMESTAMP(3),
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+-- Single-use codes for when the authenticator is lost. Stored hashed.
+CREATE TABLE recovery_codes (
+    code_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES users (user_id) ON DELETE CASCADE NOT NULL,
+    code_hash TEXT NOT NULL,
+    used_at TIMESTAMP(3),
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
+
+CREATE INDEX idx_recovery_codes_user_id ON recovery_codes (user_id);
+
+-- Roles whose members can't use their role's powers without two-factor.
+-- Only admins for now.
+CREATE TABLE two_factor_requirements (
+    role TEXT PRIMARY KEY CHECK (role = 'admin'),
+
+    created_at TIMESTAMP(3) DEFAULT current_timestamp NOT NULL
+);
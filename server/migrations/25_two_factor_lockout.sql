@@ -0,0 +1,6 @@
+-- Wrong second-factor codes, counted per user rather than per login so
+-- starting a new login doesn't reset them. Too many locks two-factor until
+-- `locked_until`.
+ALTER TABLE user_totp
+ADD COLUMN failed_attempts INT DEFAULT 0 NOT NULL,
+ADD COLUMN locked_until TIMESTAMP(3);
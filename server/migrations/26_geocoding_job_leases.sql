@@ -0,0 +1,4 @@
+-- Set while a worker has a job claimed, so the geocoder can be called
+-- without holding a transaction open. A job whose worker died is picked up
+-- again once this has passed.
+ALTER TABLE geocoding_jobs ADD COLUMN locked_until TIMESTAMP(3);
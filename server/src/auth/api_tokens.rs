@@ -0,0 +1,344 @@
+use actix_web::{
+    dev::Payload,
+    error::{ErrorForbidden, ErrorInternalServerError, ErrorUnauthorized},
+    web,
+    Error as ActixWebError,
+    FromRequest,
+    HttpMessage,
+    HttpRequest,
+};
+use futures_util::future::LocalBoxFuture;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgExecutor, PgPool};
+use std::marker::PhantomData;
+use uuid::Uuid;
+
+use crate::auth::{bearer_token, ErrorResponse, JwtMiddleware};
+use crate::domain::{query_membership, ActiveOrganisation, UserStatus};
+use crate::error::LoginError;
+use crate::utils::generate_token;
+
+/// Starts every API token, so they can be told apart from JWTs in the
+/// `Authorization` header
+pub const API_TOKEN_PREFIX: &str = "byot_pat_";
+
+/// What an API token is allowed to do. Every token can read.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ApiTokenScope {
+    #[serde(rename = "read")]
+    Read,
+    #[serde(rename = "contacts:write")]
+    ContactsWrite,
+    #[serde(rename = "tours:write")]
+    ToursWrite,
+}
+
+impl ApiTokenScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiTokenScope::Read => "read",
+            ApiTokenScope::ContactsWrite => "contacts:write",
+            ApiTokenScope::ToursWrite => "tours:write",
+        }
+    }
+}
+
+impl TryFrom<String> for ApiTokenScope {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "read" => Ok(Self::Read),
+            "contacts:write" => Ok(Self::ContactsWrite),
+            "tours:write" => Ok(Self::ToursWrite),
+            other => Err(format!("{} is not a supported scope", other))
+        }
+    }
+}
+
+/// The scope a route needs, given as the type parameter of `ApiAuth`
+pub trait RequiredScope {
+    const SCOPE: ApiTokenScope;
+}
+
+pub mod scope {
+    use super::{ApiTokenScope, RequiredScope};
+
+    pub struct Read;
+    pub struct ContactsWrite;
+    pub struct ToursWrite;
+
+    impl RequiredScope for Read {
+        const SCOPE: ApiTokenScope = ApiTokenScope::Read;
+    }
+
+    impl RequiredScope for ContactsWrite {
+        const SCOPE: ApiTokenScope = ApiTokenScope::ContactsWrite;
+    }
+
+    impl RequiredScope for ToursWrite {
+        const SCOPE: ApiTokenScope = ApiTokenScope::ToursWrite;
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewApiTokenData {
+    pub name:   String,
+    pub scopes: Vec<ApiTokenScope>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokenParams {
+    pub token_id: Uuid,
+}
+
+/// Returned once, when the token is made. Only its hash is kept after that.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedApiToken {
+    pub token_id: Uuid,
+    pub token:    String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokenSummary {
+    pub token_id:     Uuid,
+    pub name:         String,
+    pub scopes:       Vec<String>,
+    pub org_id:       Option<Uuid>,
+    pub last_used_at: Option<chrono::NaiveDateTime>,
+    pub created_at:   chrono::NaiveDateTime,
+}
+
+/// Who a presented API token belongs to and what it may do
+#[derive(Debug)]
+struct ApiTokenOwner {
+    user_id: Uuid,
+    role:    String,
+    status:  String,
+    org_id:  Option<Uuid>,
+    scopes:  Vec<String>,
+}
+
+pub fn hash_api_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[tracing::instrument(
+    name = "Storing API token",
+    skip(executor)
+)]
+pub async fn insert_api_token(
+    user_id:  &Uuid,
+    name:     &str,
+    scopes:   &[ApiTokenScope],
+    org_id:   Option<Uuid>,
+    executor: impl PgExecutor<'_>
+) -> Result<CreatedApiToken, sqlx::Error> {
+    let token  = format!("{}{}", API_TOKEN_PREFIX, generate_token());
+    let scopes = scopes.iter().map(|s| s.as_str().to_string()).collect::<Vec<_>>();
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO api_tokens (user_id, name, token_hash, scopes, org_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING token_id
+        "#,
+        user_id,
+        name,
+        hash_api_token(&token),
+        &scopes,
+        org_id
+    ).fetch_one(executor)
+    .await?;
+
+    Ok(CreatedApiToken { token_id: result.token_id, token })
+}
+
+#[tracing::instrument(
+    name = "Querying API tokens",
+    skip(pool)
+)]
+pub async fn query_api_tokens(
+    user_id: &Uuid,
+    pool:    &PgPool
+) -> Result<Vec<ApiTokenSummary>, sqlx::Error> {
+    sqlx::query_as!(
+        ApiTokenSummary,
+        r#"
+        SELECT token_id, name, scopes, org_id, last_used_at, created_at
+        FROM api_tokens
+        WHERE user_id = $1 AND revoked_at IS NULL
+        ORDER BY created_at DESC
+        "#,
+        user_id
+    ).fetch_all(pool)
+    .await
+}
+
+/// Returns false if the user has no active token with that id
+#[tracing::instrument(
+    name = "Revoking API token",
+    skip(pool)
+)]
+pub async fn revoke_api_token(
+    token_id: &Uuid,
+    user_id:  &Uuid,
+    pool:     &PgPool
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE api_tokens
+        SET revoked_at = current_timestamp
+        WHERE token_id = $1 AND user_id = $2 AND revoked_at IS NULL
+        "#,
+        token_id,
+        user_id
+    ).execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revokes every token the user has, for when their sessions are all ended
+#[tracing::instrument(
+    name = "Revoking all API tokens",
+    skip(executor)
+)]
+pub async fn revoke_api_tokens(
+    user_id:  &Uuid,
+    executor: impl PgExecutor<'_>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE api_tokens
+        SET revoked_at = current_timestamp
+        WHERE user_id = $1 AND revoked_at IS NULL
+        "#,
+        user_id
+    ).execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Looks up an active token and marks it used in one go
+#[tracing::instrument(
+    name = "Using API token",
+    skip(token, pool)
+)]
+async fn use_api_token(
+    token: &str,
+    pool:  &PgPool
+) -> Result<Option<ApiTokenOwner>, sqlx::Error> {
+    sqlx::query_as!(
+        ApiTokenOwner,
+        r#"
+        UPDATE api_tokens t
+        SET last_used_at = current_timestamp
+        FROM users u
+        WHERE t.user_id = u.user_id
+        AND t.token_hash = $1
+        AND t.revoked_at IS NULL
+        RETURNING t.user_id, u.role, u.status, t.org_id, t.scopes
+        "#,
+        hash_api_token(token)
+    ).fetch_optional(pool)
+    .await
+}
+
+/// Accepts either a session, the same way `JwtMiddleware` does, or an API
+/// token sent as `Authorization: Bearer`. API tokens also need the scope
+/// `S`; sessions can do anything.
+pub struct ApiAuth<S: RequiredScope = scope::Read> {
+    pub user_id:      Uuid,
+    pub role:         String,
+    pub organisation: Option<ActiveOrganisation>,
+    /// Set when the request was made with an API token
+    pub token_scopes: Option<Vec<String>>,
+    scope:            PhantomData<S>,
+}
+
+fn fail(message: impl Into<String>) -> ErrorResponse {
+    ErrorResponse {
+        status:  "fail".to_string(),
+        message: message.into()
+    }
+}
+
+impl<S: RequiredScope + 'static> FromRequest for ApiAuth<S> {
+    type Error  = ActixWebError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let token = bearer_token(req).filter(|token| token.starts_with(API_TOKEN_PREFIX));
+
+        let Some(token) = token else {
+            let session = JwtMiddleware::from_request(req, payload);
+
+            return Box::pin(async move {
+                let jwt = session.await?;
+
+                Ok(ApiAuth {
+                    user_id:      jwt.user_id,
+                    role:         jwt.role,
+                    organisation: jwt.organisation,
+                    token_scopes: None,
+                    scope:        PhantomData,
+                })
+            });
+        };
+
+        let req = req.clone();
+
+        Box::pin(async move {
+            let pool  = req.app_data::<web::Data<PgPool>>().unwrap();
+            let owner = use_api_token(&token, pool)
+                .await
+                .map_err(ErrorInternalServerError)?
+                .ok_or_else(|| ErrorUnauthorized(fail("Invalid token")))?;
+
+            let status = UserStatus::try_from(owner.status).map_err(ErrorInternalServerError)?;
+
+            if let Some(e) = LoginError::for_status(status) {
+                return Err(ErrorForbidden(fail(e.to_string())));
+            }
+
+            let required = S::SCOPE;
+
+            if required != ApiTokenScope::Read && !owner.scopes.iter().any(|s| s == required.as_str()) {
+                return Err(ErrorForbidden(fail(format!(
+                    "This API token doesn't have the {} scope", required.as_str()
+                ))));
+            }
+
+            let organisation = match owner.org_id {
+                Some(org_id) => {
+                    query_membership(&org_id, &owner.user_id, pool)
+                        .await
+                        .map_err(ErrorInternalServerError)?
+                        .map(|role| ActiveOrganisation { org_id, role })
+                }
+                None => None,
+            };
+
+            req.extensions_mut().insert::<Uuid>(owner.user_id);
+            req.extensions_mut().insert::<String>(owner.role.clone());
+
+            if let Some(organisation) = organisation {
+                req.extensions_mut().insert::<ActiveOrganisation>(organisation);
+            }
+
+            Ok(ApiAuth {
+                user_id:      owner.user_id,
+                role:         owner.role,
+                organisation,
+                token_scopes: Some(owner.scopes),
+                scope:        PhantomData,
+            })
+        })
+    }
+}
@@ -1,6 +1,7 @@
 use actix_web::{
+    cookie::{time::Duration as ActixWebDuration, Cookie, SameSite},
     dev::Payload,
-    error::ErrorUnauthorized,
+    error::{ErrorForbidden, ErrorInternalServerError, ErrorUnauthorized},
     http,
     web,
     Error as ActixWebError, 
@@ -8,31 +9,92 @@ use actix_web::{
     HttpMessage, 
     HttpRequest,
 };
+use chrono::Utc;
 use core::fmt;
-use jsonwebtoken::{decode, DecodingKey, Validation};
-use secrecy::ExposeSecret;
+use futures_util::future::LocalBoxFuture;
 use serde::{Deserialize, Serialize};
-use std::future::{ready, Ready};
+use sqlx::PgPool;
 
+use crate::auth::{is_token_revoked, JwtKeys, TwoFactorMissing};
 use crate::configuration::JWTSettings;
+use crate::domain::{query_membership, query_user_access, ActiveOrganisation};
+use crate::error::LoginError;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TokenClaims {
-    pub sub:  String,
-    pub iat:  usize,
-    pub exp:  usize,
-    pub role: String,
+    pub sub:   String,
+    pub iat:   usize,
+    pub exp:   usize,
+    /// Identifies the token so it can be revoked on its own
+    pub jti:   String,
+    /// The user's session epoch when the token was issued
+    pub epoch: String,
+    pub role:  String,
+    /// Organisation the user is acting for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org:   Option<uuid::Uuid>,
+}
+
+impl TokenClaims {
+    pub fn new(
+        user_id:      &uuid::Uuid,
+        role:         &str,
+        org:          Option<uuid::Uuid>,
+        epoch:        &str,
+        jwt_settings: &JWTSettings
+    ) -> Self {
+        let now = Utc::now();
+        let iat = now.timestamp() as usize;
+        let exp = (now + jwt_settings.expires_in).timestamp() as usize;
+
+        Self {
+            sub:   user_id.to_string(),
+            iat,
+            exp,
+            jti:   uuid::Uuid::new_v4().to_string(),
+            epoch: epoch.to_string(),
+            role:  role.to_string(),
+            org,
+        }
+    }
+
+    pub fn encode(&self, jwt_keys: &JwtKeys) -> String {
+        jwt_keys.encode(self).unwrap()
+    }
+}
+
+pub fn token_cookie(token: &str, jwt_settings: &JWTSettings) -> Cookie<'static> {
+    Cookie::build("token", token.to_owned())
+        .path("/")
+        .max_age(ActixWebDuration::minutes(jwt_settings.max_age as i64))
+        .http_only(true)
+        .same_site(SameSite::None) // TODO: is this safe?
+        .finish()
+}
+
+/// The token from an `Authorization: Bearer <token>` header. Anything else
+/// in the header is ignored.
+pub(crate) fn bearer_token(req: &HttpRequest) -> Option<String> {
+    let value           = req.headers().get(http::header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.trim().split_once(' ')?;
+    let token           = token.trim();
+
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then(|| token.to_string())
 }
 
 pub struct JwtMiddleware {
-    pub user_id: uuid::Uuid,
-    pub role:    String,
+    pub user_id:      uuid::Uuid,
+    pub role:         String,
+    /// Only set while the user is still a member of the organisation in their token
+    pub organisation: Option<ActiveOrganisation>,
+    /// The token itself, for revoking or replacing it
+    pub claims:       TokenClaims,
 }
 
 #[derive(Debug, Serialize)]
-struct ErrorResponse {
-    status:  String,
-    message: String,
+pub(crate) struct ErrorResponse {
+    pub(crate) status:  String,
+    pub(crate) message: String,
 }
 impl fmt::Display for ErrorResponse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -42,56 +104,128 @@ impl fmt::Display for ErrorResponse {
 
 impl FromRequest for JwtMiddleware { 
     type Error  = ActixWebError;
-    type Future = Ready<Result<Self, Self::Error>>;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
 
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-        let jwt_settings = req.app_data::<web::Data<JWTSettings>>().unwrap();
-        let jwt_secret   = jwt_settings.secret.expose_secret();
+        let jwt_keys = req.app_data::<web::Data<JwtKeys>>().unwrap();
         
+        // TODO: app isn't getting authorization header from frontend...
         let token = req.cookie("token")
             .map(|c| c.value().to_string())
-            .or_else(|| {
-                // TODO: app isn't getting authorization header from frontend...
-                req.headers()
-                    .get(http::header::AUTHORIZATION)
-                    .map(|h| h.to_str().unwrap().split_at(7).1.to_string())
-            });
+            .or_else(|| bearer_token(req));
         
         if token.is_none() {
             let json_error = ErrorResponse {
                 status:  "fail".to_string(),
                 message: "You are not logged in".to_string()
             };
-            return ready(
-                Err(ErrorUnauthorized(json_error))
-            );
+            return Box::pin(async { Err(ErrorUnauthorized(json_error)) });
         }
 
-        let claims = match decode::<TokenClaims>(
-            &token.unwrap(),
-            &DecodingKey::from_secret(jwt_secret.as_ref()),
-            &Validation::default()
-        ) {
-            Ok(c) => c.claims,
+        let claims = match jwt_keys.decode::<TokenClaims>(&token.unwrap()) {
+            Ok(c) => c,
             Err(_) => {
                 let json_error = ErrorResponse {
                     status:  "fail".to_string(),
                     message: "Invalid token".to_string()
                 };
-                return ready(
-                    Err(ErrorUnauthorized(json_error))
-                );
+                return Box::pin(async { Err(ErrorUnauthorized(json_error)) });
             }
         };
 
         let user_id = uuid::Uuid::parse_str(claims.sub.as_str()).unwrap();
         let role    = claims.role.to_string();
+        let req     = req.clone();
+
+        Box::pin(async move {
+            let pool  = req.app_data::<web::Data<PgPool>>().unwrap();
+            let redis = req.app_data::<web::Data<redis::Client>>().unwrap();
+
+            let mut conn = redis.get_tokio_connection()
+                .await
+                .map_err(ErrorInternalServerError)?;
+            let revoked  = is_token_revoked(&mut conn, &user_id, &claims)
+                .await
+                .map_err(ErrorInternalServerError)?;
+
+            if revoked {
+                return Err(ErrorUnauthorized(ErrorResponse {
+                    status:  "fail".to_string(),
+                    message: "Token has been revoked".to_string()
+                }));
+            }
+
+            // Like membership below, the account's status is checked on every
+            // request so suspending someone logs them out straight away
+            let access = query_user_access(&user_id, pool)
+                .await
+                .map_err(ErrorInternalServerError)?;
 
-        req.extensions_mut().insert::<uuid::Uuid>(user_id.to_owned());
-        req.extensions_mut().insert::<String>(role.clone());
+            let Some(access) = access else {
+                return Err(ErrorUnauthorized(ErrorResponse {
+                    status:  "fail".to_string(),
+                    message: "Invalid token".to_string()
+                }));
+            };
 
-        ready(
-            Ok(JwtMiddleware { user_id, role })
-        )
+            if let Some(e) = LoginError::for_status(access.status) {
+                return Err(ErrorForbidden(ErrorResponse {
+                    status:  "fail".to_string(),
+                    message: e.to_string()
+                }));
+            }
+
+            // Membership is checked on every request so removing someone
+            // takes effect before their token expires
+            let organisation = match claims.org {
+                Some(org_id) => {
+                    query_membership(&org_id, &user_id, pool)
+                        .await
+                        .map_err(ErrorInternalServerError)?
+                        .map(|role| ActiveOrganisation { org_id, role })
+                }
+                None => None,
+            };
+
+            req.extensions_mut().insert::<uuid::Uuid>(user_id.to_owned());
+            req.extensions_mut().insert::<String>(role.clone());
+
+            if let Some(organisation) = organisation {
+                req.extensions_mut().insert::<ActiveOrganisation>(organisation);
+            }
+
+            if access.two_factor_missing {
+                req.extensions_mut().insert(TwoFactorMissing);
+            }
+
+            Ok(JwtMiddleware { user_id, role, organisation, claims })
+        })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::header::AUTHORIZATION, test::TestRequest};
+
+    use crate::auth::bearer_token;
+
+    fn token_from(header: &str) -> Option<String> {
+        bearer_token(&TestRequest::default().insert_header((AUTHORIZATION, header)).to_http_request())
+    }
+
+    #[test]
+    fn bearer_tokens_are_read_from_the_header() {
+        assert_eq!(Some("abc.def".to_string()), token_from("Bearer abc.def"));
+        assert_eq!(Some("abc.def".to_string()), token_from("bearer  abc.def "));
+    }
+
+    #[test]
+    fn malformed_headers_have_no_token() {
+        assert_eq!(None, token_from(""));
+        assert_eq!(None, token_from("Bearer"));
+        assert_eq!(None, token_from("Bearer "));
+        assert_eq!(None, token_from("Basic dXNlcjpwYXNz"));
+        assert_eq!(None, token_from("abc.def"));
+        assert_eq!(None, bearer_token(&TestRequest::default().to_http_request()));
+    }
+}
@@ -1,5 +1,17 @@
+mod api_tokens;
+mod keys;
 mod middleware;
 mod password;
+mod refresh;
+mod revocation;
+mod totp;
+mod two_factor;
 
+pub use api_tokens::*;
+pub use keys::*;
 pub use middleware::*;
-pub use password::*;
\ No newline at end of file
+pub use password::*;
+pub use refresh::*;
+pub use revocation::*;
+pub use totp::*;
+pub use two_factor::*;
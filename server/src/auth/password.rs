@@ -5,7 +5,7 @@ use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVe
 use secrecy::{Secret, ExposeSecret};
 use sqlx::PgPool;
 
-use crate::domain::{CleanUser, User};
+use crate::domain::{CleanUser, User, UserStatus};
 use crate::telemetry::spawn_blocking_with_tracing;
 
 pub struct Credentials {
@@ -60,7 +60,8 @@ pub async fn validate_credentials(
         user = Some(CleanUser {
             user_id: stored_user.user_id,
             email:   stored_user.email,
-            role:    stored_user.role
+            role:    stored_user.role,
+            status:  stored_user.status
         });
     }
 
@@ -101,18 +102,22 @@ async fn get_stored_credentials(
     pool:  &PgPool
 ) -> Result<Option<User>, anyhow::Error> {
     let user = sqlx::query!(
-        r#"SELECT user_id, password_hash, role FROM users WHERE email = $1"#,
+        r#"SELECT user_id, password_hash, role, status FROM users WHERE email = $1"#,
         email,
     )
     .fetch_optional(pool)
     .await
     .context("Failed to perform a query to retrieve stored credentials.")?
-    .map(|row| User {
-        user_id: row.user_id,
-        email: email.to_string(),
-        password_hash: row.password_hash.into(),
-        role: row.role
-    });
+    .map(|row| -> Result<User, anyhow::Error> {
+        Ok(User {
+            user_id: row.user_id,
+            email: email.to_string(),
+            password_hash: row.password_hash.into(),
+            role: row.role,
+            status: UserStatus::try_from(row.status).map_err(anyhow::Error::msg)?
+        })
+    })
+    .transpose()?;
 
     Ok(user)
 }
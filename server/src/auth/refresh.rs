@@ -0,0 +1,226 @@
+use actix_web::cookie::{time::Duration as ActixWebDuration, Cookie, SameSite};
+use actix_web::HttpResponse;
+use chrono::Utc;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::{PgExecutor, Postgres, Transaction, types::chrono::NaiveDateTime};
+use uuid::Uuid;
+
+use crate::auth::token_cookie;
+use crate::configuration::JWTSettings;
+use crate::utils::generate_token;
+
+pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
+
+/// A refresh token as it's stored. Only the hash of the token itself is kept.
+#[derive(Debug)]
+pub struct StoredRefreshToken {
+    pub token_id:   Uuid,
+    pub family_id:  Uuid,
+    pub user_id:    Uuid,
+    pub org_id:     Option<Uuid>,
+    pub expires_at: NaiveDateTime,
+    /// Set once it's been swapped for a new token. Seeing it again means
+    /// it was stolen.
+    pub used_at:    Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+pub fn hash_refresh_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+pub fn refresh_token_cookie(token: &str, jwt_settings: &JWTSettings) -> Cookie<'static> {
+    Cookie::build(REFRESH_TOKEN_COOKIE, token.to_owned())
+        .path("/")
+        .max_age(ActixWebDuration::seconds(jwt_settings.refresh_expires_in.num_seconds()))
+        .http_only(true)
+        .same_site(SameSite::None)
+        .finish()
+}
+
+/// Sends both tokens as cookies, and the access token in the body too, for
+/// anything that starts or continues a session
+pub fn session_response(
+    access_token:  &str,
+    refresh_token: &str,
+    jwt_settings:  &JWTSettings
+) -> HttpResponse {
+    HttpResponse::Ok()
+        .cookie(token_cookie(access_token, jwt_settings))
+        .cookie(refresh_token_cookie(refresh_token, jwt_settings))
+        .json(json!({
+            "status": "success",
+            "token": access_token
+        }))
+}
+
+/// Stores a new refresh token and returns it. Pass the family of the token
+/// it replaces, or `None` to start a new session.
+#[tracing::instrument(
+    name = "Issuing refresh token",
+    skip(jwt_settings, executor)
+)]
+pub async fn issue_refresh_token(
+    user_id:      &Uuid,
+    family_id:    Option<Uuid>,
+    org_id:       Option<Uuid>,
+    jwt_settings: &JWTSettings,
+    executor:     impl PgExecutor<'_>
+) -> Result<String, sqlx::Error> {
+    let token      = generate_token();
+    let family_id  = family_id.unwrap_or_else(Uuid::new_v4);
+    let expires_at = (Utc::now() + jwt_settings.refresh_expires_in).naive_utc();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO refresh_tokens (family_id, user_id, token_hash, org_id, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        family_id,
+        user_id,
+        hash_refresh_token(&token),
+        org_id,
+        expires_at
+    ).execute(executor)
+    .await?;
+
+    Ok(token)
+}
+
+/// Locks the token until the transaction ends, so two refreshes with the
+/// same token can't both go through
+#[tracing::instrument(
+    name = "Getting refresh token",
+    skip(token, transaction)
+)]
+pub async fn get_refresh_token_for_update(
+    token:       &str,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<Option<StoredRefreshToken>, sqlx::Error> {
+    sqlx::query_as!(
+        StoredRefreshToken,
+        r#"
+        SELECT token_id, family_id, user_id, org_id, expires_at, used_at, revoked_at
+        FROM refresh_tokens
+        WHERE token_hash = $1
+        FOR UPDATE
+        "#,
+        hash_refresh_token(token)
+    ).fetch_optional(transaction)
+    .await
+}
+
+#[tracing::instrument(
+    name = "Marking refresh token used",
+    skip(transaction)
+)]
+pub async fn mark_refresh_token_used(
+    token_id:    &Uuid,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"UPDATE refresh_tokens SET used_at = current_timestamp WHERE token_id = $1"#,
+        token_id
+    ).execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+/// Ends the session a refresh token belongs to
+#[tracing::instrument(
+    name = "Revoking refresh token family",
+    skip(executor)
+)]
+pub async fn revoke_refresh_token_family(
+    family_id: &Uuid,
+    executor:  impl PgExecutor<'_>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = current_timestamp
+        WHERE family_id = $1 AND revoked_at IS NULL
+        "#,
+        family_id
+    ).execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Ends the session of a refresh token someone presented, e.g. when they log
+/// out. Unknown tokens are ignored.
+#[tracing::instrument(
+    name = "Revoking refresh token",
+    skip(token, executor)
+)]
+pub async fn revoke_refresh_token(
+    token:    &str,
+    executor: impl PgExecutor<'_>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = current_timestamp
+        WHERE revoked_at IS NULL AND family_id = (
+            SELECT family_id FROM refresh_tokens WHERE token_hash = $1
+        )
+        "#,
+        hash_refresh_token(token)
+    ).execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Ends every session the user has
+#[tracing::instrument(
+    name = "Revoking all refresh tokens",
+    skip(executor)
+)]
+pub async fn revoke_refresh_tokens(
+    user_id:  &Uuid,
+    executor: impl PgExecutor<'_>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = current_timestamp
+        WHERE user_id = $1 AND revoked_at IS NULL
+        "#,
+        user_id
+    ).execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Remembers which organisation the user switched to, so refreshed access
+/// tokens keep acting for it
+#[tracing::instrument(
+    name = "Setting refresh token organisation",
+    skip(token, executor)
+)]
+pub async fn set_refresh_token_org(
+    token:    &str,
+    user_id:  &Uuid,
+    org_id:   Option<Uuid>,
+    executor: impl PgExecutor<'_>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET org_id = $1
+        WHERE token_hash = $2 AND user_id = $3
+        AND used_at IS NULL AND revoked_at IS NULL
+        "#,
+        org_id,
+        hash_refresh_token(token),
+        user_id
+    ).execute(executor)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use chrono::Utc;
+use redis::{aio::Connection, RedisError};
+
+use crate::auth::TokenClaims;
+
+fn revoked_token_key(jti: &str) -> String {
+    format!("revoked_token:{}", jti)
+}
+
+fn session_epoch_key(user_id: &uuid::Uuid) -> String {
+    format!("session_epoch:{}", user_id)
+}
+
+/// Every token carries the user's epoch from when it was issued. Logging out
+/// everywhere starts a new one, which revokes all of the older tokens at
+/// once. Users who never have are on the empty epoch.
+#[tracing::instrument(
+    name = "Get session epoch",
+    skip(conn)
+)]
+pub async fn current_session_epoch(
+    conn:    &mut Connection,
+    user_id: &uuid::Uuid
+) -> Result<String, RedisError> {
+    let epoch: Option<String> = redis::cmd("GET")
+        .arg(session_epoch_key(user_id))
+        .query_async(conn)
+        .await?;
+
+    Ok(epoch.unwrap_or_default())
+}
+
+/// Revokes a single token, e.g. when logging out
+#[tracing::instrument(
+    name = "Revoke token",
+    skip(conn, claims),
+    fields(user_id = %claims.sub)
+)]
+pub async fn revoke_token(
+    conn:   &mut Connection,
+    claims: &TokenClaims
+) -> Result<(), RedisError> {
+    // Only needs remembering until the token would have expired anyway
+    let ttl = (claims.exp as i64 - Utc::now().timestamp()).max(1);
+
+    redis::cmd("SET")
+        .arg(revoked_token_key(&claims.jti))
+        .arg(1)
+        .arg("EX")
+        .arg(ttl)
+        .query_async(conn)
+        .await
+}
+
+/// Revokes every token issued to the user so far and returns the new epoch
+/// for any token that replaces them
+#[tracing::instrument(
+    name = "Revoke all sessions",
+    skip(conn)
+)]
+pub async fn revoke_all_sessions(
+    conn:    &mut Connection,
+    user_id: &uuid::Uuid
+) -> Result<String, RedisError> {
+    let epoch = uuid::Uuid::new_v4().to_string();
+
+    redis::cmd("SET")
+        .arg(session_epoch_key(user_id))
+        .arg(&epoch)
+        .query_async(conn)
+        .await?;
+
+    Ok(epoch)
+}
+
+#[tracing::instrument(
+    name = "Check if token is revoked",
+    skip(conn, claims),
+    fields(user_id = %claims.sub)
+)]
+pub async fn is_token_revoked(
+    conn:    &mut Connection,
+    user_id: &uuid::Uuid,
+    claims:  &TokenClaims
+) -> Result<bool, RedisError> {
+    let revoked: Option<String> = redis::cmd("GET")
+        .arg(revoked_token_key(&claims.jti))
+        .query_async(conn)
+        .await?;
+
+    if revoked.is_some() {
+        return Ok(true)
+    }
+
+    Ok(current_session_epoch(conn, user_id).await? != claims.epoch)
+}
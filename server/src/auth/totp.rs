@@ -0,0 +1,237 @@
+use hmac::{Hmac, Mac};
+use rand::{distributions::Alphanumeric, thread_rng, Rng, RngCore};
+use redis::{aio::Connection, RedisError};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/// Shown as the account's issuer in authenticator apps
+pub const TOTP_ISSUER: &str = "BYOT";
+/// Seconds each code is valid for
+pub const TOTP_STEP:   u64 = 30;
+const TOTP_DIGITS:     u32 = 6;
+/// Steps either side of now that are accepted, for clock drift
+const TOTP_WINDOW:     u64 = 1;
+const SECRET_BYTES:    usize = 20;
+
+pub const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_LENGTH:    usize = 10;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// RFC 4648 base32 without padding, which is what authenticator apps expect
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity((bytes.len() * 8 + 4) / 5);
+    let mut buffer = 0u32;
+    let mut bits   = 0;
+
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits  += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+
+    output
+}
+
+/// Ignores case, spaces and padding. None if anything else is in there.
+pub fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits   = 0;
+
+    for c in text.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = BASE32_ALPHABET.iter().position(|a| *a as char == c.to_ascii_uppercase())?;
+
+        buffer = (buffer << 5) | value as u32;
+        bits  += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(output)
+}
+
+pub fn generate_totp_secret() -> String {
+    let mut secret = [0u8; SECRET_BYTES];
+    thread_rng().fill_bytes(&mut secret);
+
+    base32_encode(&secret)
+}
+
+/// The `otpauth://` URI authenticator apps read from a QR code
+pub fn provisioning_uri(secret: &str, account: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+        issuer  = urlencoding::encode(TOTP_ISSUER),
+        account = urlencoding::encode(account),
+        secret  = secret,
+        digits  = TOTP_DIGITS,
+        period  = TOTP_STEP
+    )
+}
+
+/// The code for one time step, as in RFC 6238
+pub fn totp_code(secret: &[u8], step: u64) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC takes keys of any length");
+    mac.update(&step.to_be_bytes());
+
+    let hash   = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0xf) as usize;
+    let binary = u32::from_be_bytes([hash[offset], hash[offset + 1], hash[offset + 2], hash[offset + 3]]) & 0x7fff_ffff;
+
+    format!("{:0width$}", binary % 10u32.pow(TOTP_DIGITS), width = TOTP_DIGITS as usize)
+}
+
+/// The time step a code belongs to, if it's valid for any step in the window
+/// around `now`
+pub fn matching_step(secret: &str, code: &str, now: u64) -> Option<u64> {
+    let secret = base32_decode(secret)?;
+    let code   = code.trim().replace(' ', "");
+    let now    = now / TOTP_STEP;
+
+    (now.saturating_sub(TOTP_WINDOW)..=now + TOTP_WINDOW)
+        .find(|step| totp_code(&secret, *step) == code)
+}
+
+fn used_step_key(user_id: &uuid::Uuid, step: u64) -> String {
+    format!("totp:{}:{}", user_id, step)
+}
+
+/// Checks a code and marks its time step used, so the same code can't be
+/// used again. `SET NX` only succeeds for whoever gets there first, so two
+/// requests racing with the same code can't both get in.
+#[tracing::instrument(
+    name = "Verify TOTP code",
+    skip(conn, secret, code)
+)]
+pub async fn verify_totp(
+    conn:    &mut Connection,
+    user_id: &uuid::Uuid,
+    secret:  &str,
+    code:    &str
+) -> Result<bool, RedisError> {
+    let now = chrono::Utc::now().timestamp() as u64;
+    let Some(step) = matching_step(secret, code, now) else {
+        return Ok(false)
+    };
+
+    // Only needs remembering until the step has left the window
+    let first_use: Option<String> = redis::cmd("SET")
+        .arg(used_step_key(user_id, step))
+        .arg(1)
+        .arg("NX")
+        .arg("EX")
+        .arg((2 * TOTP_WINDOW + 1) * TOTP_STEP)
+        .query_async(conn)
+        .await?;
+
+    Ok(first_use.is_some())
+}
+
+/// Grouped as `xxxxx-xxxxx` to make them easier to copy down
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = thread_rng();
+
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let code: String = std::iter::repeat_with(|| rng.sample(Alphanumeric))
+                .map(|c| char::from(c).to_ascii_lowercase())
+                .take(RECOVERY_CODE_LENGTH)
+                .collect();
+
+            format!("{}-{}", &code[..RECOVERY_CODE_LENGTH / 2], &code[RECOVERY_CODE_LENGTH / 2..])
+        })
+        .collect()
+}
+
+/// Hashes a recovery code however it was typed in
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalised: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    format!("{:x}", Sha256::digest(normalised.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::{
+        base32_decode,
+        base32_encode,
+        generate_recovery_codes,
+        generate_totp_secret,
+        hash_recovery_code,
+        matching_step,
+        provisioning_uri,
+        totp_code,
+        TOTP_STEP
+    };
+
+    /// The SHA1 secret from RFC 6238's test vectors
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn codes_match_the_rfc_test_vectors() {
+        // The RFC gives 8 digits; these are the last 6
+        assert_eq!("287082", totp_code(RFC_SECRET, 59 / TOTP_STEP));
+        assert_eq!("081804", totp_code(RFC_SECRET, 1111111109 / TOTP_STEP));
+        assert_eq!("050471", totp_code(RFC_SECRET, 1111111111 / TOTP_STEP));
+        assert_eq!("005924", totp_code(RFC_SECRET, 1234567890 / TOTP_STEP));
+        assert_eq!("279037", totp_code(RFC_SECRET, 2000000000 / TOTP_STEP));
+    }
+
+    #[test]
+    fn base32_round_trips() {
+        assert_eq!("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", base32_encode(RFC_SECRET));
+        assert_eq!(Some(RFC_SECRET.to_vec()), base32_decode("gezd gnbv gy3t qojq gezd gnbv gy3t qojq"));
+        assert_eq!(None, base32_decode("not base32!"));
+
+        let secret = generate_totp_secret();
+        assert_eq!(20, base32_decode(&secret).unwrap().len());
+    }
+
+    #[test]
+    fn codes_are_accepted_within_the_window() {
+        let secret = base32_encode(RFC_SECRET);
+        let now    = 1234567890;
+        let step   = now / TOTP_STEP;
+
+        assert_eq!(Some(step), matching_step(&secret, &totp_code(RFC_SECRET, step), now));
+        assert_eq!(Some(step - 1), matching_step(&secret, &totp_code(RFC_SECRET, step - 1), now));
+        assert_eq!(Some(step + 1), matching_step(&secret, &totp_code(RFC_SECRET, step + 1), now));
+        assert_eq!(None, matching_step(&secret, &totp_code(RFC_SECRET, step - 2), now));
+        assert_eq!(None, matching_step(&secret, "abcdef", now));
+    }
+
+    #[test]
+    fn provisioning_uris_escape_the_account() {
+        assert_eq!(
+            "otpauth://totp/BYOT:a%2Bb%40test.test?secret=ABC&issuer=BYOT&algorithm=SHA1&digits=6&period=30",
+            provisioning_uri("ABC", "a+b@test.test")
+        );
+    }
+
+    #[test]
+    fn recovery_codes_hash_the_same_however_they_are_typed() {
+        let codes = generate_recovery_codes();
+        assert_eq!(10, codes.len());
+        assert_eq!(11, codes[0].len());
+
+        let code = &codes[0];
+        assert_eq!(hash_recovery_code(code), hash_recovery_code(&code.to_uppercase().replace('-', " ")));
+        assert_ne!(hash_recovery_code(&codes[0]), hash_recovery_code(&codes[1]));
+    }
+}
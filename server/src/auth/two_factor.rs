@@ -0,0 +1,449 @@
+use anyhow::Context;
+use redis::aio::Connection;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction, types::chrono::NaiveDateTime};
+use uuid::Uuid;
+
+use crate::auth::{hash_recovery_code, verify_totp};
+use crate::domain::CleanUser;
+use crate::utils::generate_token;
+
+/// How long someone has to enter their code after their password
+const CHALLENGE_EXPIRY_SECONDS: u64 = 300;
+/// Wrong codes allowed per login before the password has to be entered again
+const CHALLENGE_MAX_ATTEMPTS:   u32 = 5;
+/// Wrong codes allowed per user, across logins, before two-factor is locked
+const USER_MAX_FAILED_ATTEMPTS: i32 = 10;
+const LOCKOUT_MINUTES:          i32 = 15;
+
+/// Put in the request's extensions when the user's role requires two-factor
+/// and they haven't set it up. `is_admin` won't let them through.
+#[derive(Clone, Copy, Debug)]
+pub struct TwoFactorMissing;
+
+#[derive(Debug)]
+pub struct StoredTotp {
+    pub secret:     String,
+    /// Unset while the user is still enrolling
+    pub enabled_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorCodeData {
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorLoginData {
+    pub challenge_token: String,
+    pub code:            String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorRequirementData {
+    pub required: bool,
+}
+
+/// A login that got past the password and is waiting on the second factor
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TwoFactorChallenge {
+    pub user:       CleanUser,
+    pub attempts:   u32,
+    /// Unix time the challenge was first due to expire. Wrong codes don't
+    /// push it back.
+    pub expires_at: i64,
+}
+
+fn challenge_key(token: &str) -> String {
+    format!("two_factor_challenge:{}", token)
+}
+
+#[tracing::instrument(
+    name = "Querying TOTP secret",
+    skip(executor)
+)]
+pub async fn query_totp(
+    user_id:  &Uuid,
+    executor: impl PgExecutor<'_>
+) -> Result<Option<StoredTotp>, sqlx::Error> {
+    sqlx::query_as!(
+        StoredTotp,
+        r#"SELECT secret, enabled_at FROM user_totp WHERE user_id = $1"#,
+        user_id
+    ).fetch_optional(executor)
+    .await
+}
+
+/// Saves a new secret for the user to enrol with, replacing any earlier one
+/// they didn't finish with. Returns false if two-factor is already on.
+#[tracing::instrument(
+    name = "Storing pending TOTP secret",
+    skip(secret, pool)
+)]
+pub async fn store_pending_totp(
+    user_id: &Uuid,
+    secret:  &str,
+    pool:    &PgPool
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO user_totp (user_id, secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+        SET secret = EXCLUDED.secret, created_at = current_timestamp
+        WHERE user_totp.enabled_at IS NULL
+        "#,
+        user_id,
+        secret
+    ).execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[tracing::instrument(
+    name = "Enabling TOTP",
+    skip(transaction)
+)]
+pub async fn enable_totp(
+    user_id:     &Uuid,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"UPDATE user_totp SET enabled_at = current_timestamp WHERE user_id = $1"#,
+        user_id
+    ).execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+/// Turns two-factor off, along with any recovery codes
+#[tracing::instrument(
+    name = "Deleting TOTP",
+    skip(transaction)
+)]
+pub async fn delete_totp(
+    user_id:     &Uuid,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"DELETE FROM user_totp WHERE user_id = $1"#,
+        user_id
+    ).execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"DELETE FROM recovery_codes WHERE user_id = $1"#,
+        user_id
+    ).execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+/// Swaps the user's recovery codes for new ones
+#[tracing::instrument(
+    name = "Replacing recovery codes",
+    skip(codes, transaction)
+)]
+pub async fn replace_recovery_codes(
+    user_id:     &Uuid,
+    codes:       &[String],
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<(), sqlx::Error> {
+    let hashes = codes.iter().map(|code| hash_recovery_code(code)).collect::<Vec<_>>();
+
+    sqlx::query!(
+        r#"DELETE FROM recovery_codes WHERE user_id = $1"#,
+        user_id
+    ).execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO recovery_codes (user_id, code_hash)
+        SELECT $1, code_hash FROM UNNEST($2::TEXT[]) AS code_hash
+        "#,
+        user_id,
+        &hashes
+    ).execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+/// Returns false if the code isn't one of the user's unused ones
+#[tracing::instrument(
+    name = "Using recovery code",
+    skip(code, pool)
+)]
+pub async fn use_recovery_code(
+    user_id: &Uuid,
+    code:    &str,
+    pool:    &PgPool
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE recovery_codes
+        SET used_at = current_timestamp
+        WHERE code_id = (
+            SELECT code_id FROM recovery_codes
+            WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+            LIMIT 1
+        )
+        "#,
+        user_id,
+        hash_recovery_code(code)
+    ).execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Checks a code from the user's authenticator, or failing that one of their
+/// recovery codes. False if they don't have two-factor on.
+#[tracing::instrument(
+    name = "Verify second factor",
+    skip(code, conn, pool)
+)]
+pub async fn verify_second_factor(
+    user_id: &Uuid,
+    code:    &str,
+    conn:    &mut Connection,
+    pool:    &PgPool
+) -> Result<bool, anyhow::Error> {
+    let totp = query_totp(user_id, pool)
+        .await
+        .context("Failed to query TOTP secret")?;
+
+    let Some(StoredTotp { secret, enabled_at: Some(_) }) = totp else {
+        return Ok(false)
+    };
+
+    if two_factor_locked(user_id, pool).await.context("Failed to check two-factor lockout")? {
+        return Ok(false)
+    }
+
+    let verified = verify_totp(conn, user_id, &secret, code).await.context("Failed to check TOTP replay window")?
+        || use_recovery_code(user_id, code, pool).await.context("Failed to check recovery code")?;
+
+    if verified {
+        clear_two_factor_failures(user_id, pool)
+            .await
+            .context("Failed to clear two-factor failures")?;
+    } else {
+        record_two_factor_failure(user_id, pool)
+            .await
+            .context("Failed to record two-factor failure")?;
+    }
+
+    Ok(verified)
+}
+
+/// True while the user is locked out for entering too many wrong codes
+#[tracing::instrument(
+    name = "Checking two-factor lockout",
+    skip(executor)
+)]
+pub async fn two_factor_locked(
+    user_id:  &Uuid,
+    executor: impl PgExecutor<'_>
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM user_totp WHERE user_id = $1 AND locked_until > current_timestamp
+        ) AS "locked!"
+        "#,
+        user_id
+    ).fetch_one(executor)
+    .await?;
+
+    Ok(result.locked)
+}
+
+/// Counts a wrong code against the user, locking them out for a while and
+/// starting the count again once there have been too many
+async fn record_two_factor_failure(
+    user_id:  &Uuid,
+    executor: impl PgExecutor<'_>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE user_totp
+        SET failed_attempts = CASE WHEN failed_attempts + 1 >= $2 THEN 0 ELSE failed_attempts + 1 END,
+            locked_until = CASE
+                WHEN failed_attempts + 1 >= $2 THEN current_timestamp + make_interval(mins => $3)
+                ELSE locked_until
+            END
+        WHERE user_id = $1
+        "#,
+        user_id,
+        USER_MAX_FAILED_ATTEMPTS,
+        LOCKOUT_MINUTES
+    ).execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+async fn clear_two_factor_failures(
+    user_id:  &Uuid,
+    executor: impl PgExecutor<'_>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE user_totp
+        SET failed_attempts = 0, locked_until = NULL
+        WHERE user_id = $1 AND (failed_attempts > 0 OR locked_until IS NOT NULL)
+        "#,
+        user_id
+    ).execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Querying two-factor requirement",
+    skip(pool)
+)]
+pub async fn query_two_factor_required(
+    role: &str,
+    pool: &PgPool
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"SELECT EXISTS (SELECT 1 FROM two_factor_requirements WHERE role = $1) AS "required!""#,
+        role
+    ).fetch_one(pool)
+    .await?;
+
+    Ok(result.required)
+}
+
+#[tracing::instrument(
+    name = "Setting two-factor requirement",
+    skip(pool)
+)]
+pub async fn set_two_factor_required(
+    role:     &str,
+    required: bool,
+    pool:     &PgPool
+) -> Result<(), sqlx::Error> {
+    if required {
+        sqlx::query!(
+            r#"INSERT INTO two_factor_requirements (role) VALUES ($1) ON CONFLICT DO NOTHING"#,
+            role
+        ).execute(pool)
+        .await?;
+    } else {
+        sqlx::query!(
+            r#"DELETE FROM two_factor_requirements WHERE role = $1"#,
+            role
+        ).execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Remembers a user who got their password right, and returns the token
+/// they finish logging in with
+#[tracing::instrument(
+    name = "Start two-factor challenge",
+    skip(conn, user),
+    fields(user_id = %user.user_id)
+)]
+pub async fn start_two_factor_challenge(
+    conn: &mut Connection,
+    user: CleanUser
+) -> Result<String, anyhow::Error> {
+    let token      = generate_token();
+    let expires_at = chrono::Utc::now().timestamp() + CHALLENGE_EXPIRY_SECONDS as i64;
+
+    save_two_factor_challenge(conn, &token, &TwoFactorChallenge { user, attempts: 0, expires_at }).await?;
+
+    Ok(token)
+}
+
+#[tracing::instrument(
+    name = "Get two-factor challenge",
+    skip(conn, token)
+)]
+pub async fn get_two_factor_challenge(
+    conn:  &mut Connection,
+    token: &str
+) -> Result<Option<TwoFactorChallenge>, anyhow::Error> {
+    let challenge: Option<String> = redis::cmd("GET")
+        .arg(challenge_key(token))
+        .query_async(conn)
+        .await?;
+
+    // Ended challenges are left empty until they expire
+    match challenge.filter(|c| !c.is_empty()) {
+        Some(challenge) => Ok(Some(serde_json::from_str(&challenge)?)),
+        None => Ok(None),
+    }
+}
+
+/// Counts a wrong code against the challenge, ending it once there have
+/// been too many
+#[tracing::instrument(
+    name = "Fail two-factor challenge",
+    skip(conn, token, challenge)
+)]
+pub async fn fail_two_factor_challenge(
+    conn:      &mut Connection,
+    token:     &str,
+    challenge: TwoFactorChallenge
+) -> Result<(), anyhow::Error> {
+    let challenge = TwoFactorChallenge { attempts: challenge.attempts + 1, ..challenge };
+
+    if challenge.attempts >= CHALLENGE_MAX_ATTEMPTS {
+        return end_two_factor_challenge(conn, token).await
+    }
+
+    save_two_factor_challenge(conn, token, &challenge).await
+}
+
+#[tracing::instrument(
+    name = "End two-factor challenge",
+    skip(conn, token)
+)]
+pub async fn end_two_factor_challenge(
+    conn:  &mut Connection,
+    token: &str
+) -> Result<(), anyhow::Error> {
+    redis::cmd("SET")
+        .arg(challenge_key(token))
+        .arg("")
+        .arg("EX")
+        .arg(CHALLENGE_EXPIRY_SECONDS)
+        .query_async(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Saves the challenge until its original expiry
+async fn save_two_factor_challenge(
+    conn:      &mut Connection,
+    token:     &str,
+    challenge: &TwoFactorChallenge
+) -> Result<(), anyhow::Error> {
+    let remaining = challenge.expires_at - chrono::Utc::now().timestamp();
+
+    redis::cmd("SET")
+        .arg(challenge_key(token))
+        .arg(serde_json::to_string(challenge)?)
+        .arg("EX")
+        .arg(remaining.max(1))
+        .query_async(conn)
+        .await?;
+
+    Ok(())
+}
@@ -2,18 +2,21 @@ use secrecy::{ExposeSecret, Secret};
 use serde_aux::field_attributes::deserialize_number_from_string;
 use sqlx::postgres::{PgConnectOptions, PgSslMode};
 use sqlx::ConnectOptions;
+use std::collections::BTreeMap;
 use std::convert::{TryFrom, TryInto};
 
 use crate::domain::UserEmail;
-use crate::email_client::EmailClient;
-use crate::gmaps_api_client::GoogleMapsAPIClient;
+use crate::email_client::{EmailClient, MailerSettings};
+use crate::geocoder::GeocoderSettings;
+use crate::routing::RoutingSettings;
 
 #[derive(serde::Deserialize, Clone)]
 pub struct Settings {
     pub database:     DatabaseSettings,
     pub application:  ApplicationSettings,
     pub email_client: EmailClientSettings,
-    pub gmaps_client: GoogleMapsAPIClient,
+    pub geocoder:     GeocoderSettings,
+    pub routing:      RoutingSettings,
     pub redis_uri:    Secret<String>,
     pub jwt_settings: JWTSettings,
     pub frontend_url: String,
@@ -63,11 +66,13 @@ impl DatabaseSettings {
 
 #[derive(serde::Deserialize, Clone)]
 pub struct EmailClientSettings {
-    pub base_url:       String,
-    pub sender_email:   String,
-    pub auth_token:     Secret<String>,
+    pub sender_email:        String,
+    /// Inquiry emails a user can send in 24 hours, so one account can't get
+    /// the sending domain flagged as spam
     #[serde(deserialize_with = "deserialize_number_from_string")]
-    pub timeout_millis: u64
+    pub daily_inquiry_limit: i64,
+    #[serde(flatten)]
+    pub mailer:              MailerSettings,
 }
 
 impl EmailClientSettings {
@@ -75,28 +80,66 @@ impl EmailClientSettings {
         UserEmail::parse(self.sender_email.clone())
     }
 
-    pub fn timeout(&self) -> std::time::Duration {
-        std::time::Duration::from_millis(self.timeout_millis)
-    }
-
-    pub fn client(self) -> EmailClient {
-        let sender_email = self.sender().expect("Invalid sender email address");
-        let timeout      = self.timeout();
+    pub fn client(self) -> Result<EmailClient, anyhow::Error> {
+        let sender_email = self.sender().map_err(|e| anyhow::anyhow!(e))?;
+        let mailer       = self.mailer.build()?;
 
-        EmailClient::new(
-            self.base_url,
-            sender_email,
-            self.auth_token,
-            timeout
-        )
+        Ok(EmailClient::new(mailer, sender_email))
     }
 }
 
 #[derive(serde::Deserialize, Clone)]
 pub struct JWTSettings {
-    pub secret:     Secret<String>,
-    pub expires_in: String,
-    pub max_age:    u64
+    /// `kid` of the key new tokens are signed with
+    pub signing_key:        String,
+    /// Private half of `signing_key` as PKCS#8 PEM, e.g. from
+    /// `openssl genpkey -algorithm ed25519`. Set in `local.yaml` or
+    /// `APP_JWT_SETTINGS__PRIVATE_KEY`, never in a committed file.
+    pub private_key:        Option<Secret<String>>,
+    /// Ed25519 public keys by `kid`. Keys kept after a rotation let older
+    /// tokens still verify.
+    #[serde(default)]
+    pub keys:               BTreeMap<String, JwtKeySettings>,
+    /// How long access tokens last, e.g. `15m`
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub expires_in:         chrono::Duration,
+    /// Minutes the access token cookie is kept for
+    pub max_age:            u64,
+    /// How long a session lasts without being refreshed, e.g. `30d`
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub refresh_expires_in: chrono::Duration,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct JwtKeySettings {
+    pub public_key: String,
+}
+
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<chrono::Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: String = serde::Deserialize::deserialize(deserializer)?;
+
+    parse_duration(&s).map_err(serde::de::Error::custom)
+}
+
+/// Reads durations like `30s`, `15m`, `12h` or `30d`
+pub fn parse_duration(s: &str) -> Result<chrono::Duration, String> {
+    let s       = s.trim();
+    let invalid = || format!("{} is not a valid duration. Use e.g. `15m` or `30d`", s);
+    let unit    = s.chars().last().ok_or_else(invalid)?;
+    let amount  = s[..s.len() - unit.len_utf8()]
+        .parse::<i64>()
+        .map_err(|_| invalid())?;
+
+    match unit {
+        's' => Ok(chrono::Duration::seconds(amount)),
+        'm' => Ok(chrono::Duration::minutes(amount)),
+        'h' => Ok(chrono::Duration::hours(amount)),
+        'd' => Ok(chrono::Duration::days(amount)),
+        _ => Err(invalid()),
+    }
 }
 
 pub enum Environment {
@@ -146,4 +189,26 @@ pub fn get_configuration() -> Result<Settings, config::ConfigError> {
         .build()?;
 
     settings.try_deserialize::<Settings>()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_err, assert_ok_eq};
+
+    use crate::configuration::parse_duration;
+
+    #[test]
+    fn durations_are_parsed_with_their_unit() {
+        assert_ok_eq!(parse_duration("45s"), chrono::Duration::seconds(45));
+        assert_ok_eq!(parse_duration("15m"), chrono::Duration::minutes(15));
+        assert_ok_eq!(parse_duration("12h"), chrono::Duration::hours(12));
+        assert_ok_eq!(parse_duration("30d"), chrono::Duration::days(30));
+    }
+
+    #[test]
+    fn invalid_durations_are_rejected() {
+        for s in ["", "15", "m", "15w", "1.5h", "15分"] {
+            assert_err!(parse_duration(s));
+        }
+    }
+}
@@ -8,6 +8,18 @@ use crate::domain::{Genre, OptionalStringInput, StringInput};
 // TODO: use generics to clean up
 // https://stackoverflow.com/questions/32552593/is-it-possible-for-one-struct-to-extend-an-existing-struct-keeping-all-the-fiel
 
+/// Who a contact is being looked up for, which decides whether a private
+/// contact is visible
+#[derive(Clone, Copy, Debug)]
+pub enum ContactViewer<'a> {
+    /// Admins, and lookups of contacts already known to be visible
+    Unrestricted,
+    Guest,
+    /// Sees public contacts, their own, ones shared with them and those of
+    /// the organisation they're acting for
+    User(&'a Uuid, Option<Uuid>),
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Contact {
     pub contact_id:   i32,
@@ -48,6 +60,7 @@ pub struct ContactResponse {
     pub contact_form:   Option<String>,
     pub age_range:      String,
     pub user_id:        Uuid,
+    pub org_id:         Option<Uuid>,
     pub is_private:     bool,
     pub contact_type:   Option<String>,
     pub average_rating: Option<f32>,
@@ -70,6 +83,7 @@ pub struct ContactRow {
     pub contact_form:   Option<String>,
     pub age_range:      String,
     pub user_id:        Uuid,
+    pub org_id:         Option<Uuid>,
     pub is_private:     bool,
     pub contact_type:   Option<String>,
     pub average_rating: Option<f32>,
@@ -282,15 +296,16 @@ pub async fn insert_contact(
     contact:     &NewContact,
     transaction: &mut Transaction<'_, Postgres>,
     user_id:     &Uuid,
+    org_id:      Option<Uuid>,
 ) -> Result<i32, sqlx::Error> {
     let result = sqlx::query!(
         r#"
         INSERT INTO contacts (
             display_name, address, city, state, zip_code, capacity, email, 
             contact_form, age_range, is_private, contact_type,
-            user_id
+            user_id, org_id
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
         RETURNING contact_id
         "#,
         contact.display_name,
@@ -304,7 +319,8 @@ pub async fn insert_contact(
         contact.age_range,
         contact.is_private,
         contact.contact_type,
-        user_id
+        user_id,
+        org_id
     ).fetch_one(transaction)
     .await?;
 
@@ -320,13 +336,20 @@ pub async fn insert_contact(
 pub async fn query_contact_by_id(
     pool:       &PgPool,
     contact_id: &i32,
+    viewer:     ContactViewer<'_>,
 ) -> Result<Option<ContactResponse>, sqlx::Error> {
+    let (unrestricted, user_id, org_id) = match viewer {
+        ContactViewer::Unrestricted => (true, None, None),
+        ContactViewer::Guest => (false, None, None),
+        ContactViewer::User(user_id, org_id) => (false, Some(*user_id), org_id),
+    };
+
     let contacts = sqlx::query_as!(
         ContactRow,
         r#"
         SELECT c.contact_id, c.display_name, c.address, c.city, c.state, c.zip_code, 
                c.capacity, c.latitude, c.longitude, c.email, c.contact_form, 
-               c.age_range, c.country, c.is_private, c.user_id, c.contact_type,
+               c.age_range, c.country, c.is_private, c.user_id, c.org_id, c.contact_type,
                ROUND(AVG(r.rating), 2)::real AS average_rating,
                g.genre_name, g.genre_id
         FROM contacts c
@@ -334,9 +357,22 @@ pub async fn query_contact_by_id(
         LEFT JOIN contacts_genres ON c.contact_id = contacts_genres.contact_id
         LEFT JOIN genres g on g.genre_id = contacts_genres.genre_id
         WHERE c.contact_id = $1
+        AND (
+            $2
+            OR c.is_private = false
+            OR (c.org_id IS NULL AND c.user_id = $3)
+            OR c.org_id = $4
+            OR EXISTS (
+                SELECT 1 FROM contact_shares s
+                WHERE s.contact_id = c.contact_id AND s.user_id = $3
+            )
+        )
         GROUP BY c.contact_id, g.genre_name, g.genre_id
         "#,
-        contact_id
+        contact_id,
+        unrestricted,
+        user_id,
+        org_id
     ).fetch_all(pool)
     .await?;
 
@@ -346,6 +382,40 @@ pub async fn query_contact_by_id(
     Ok(contact.cloned())
 }
 
+#[tracing::instrument(
+    name = "Querying contacts by id",
+    skip(pool, contact_ids)
+)]
+pub async fn query_contacts_by_ids(
+    pool:        &PgPool,
+    contact_ids: &[i32],
+) -> Result<Vec<ContactResponse>, sqlx::Error> {
+    let rows = sqlx::query_as!(
+        ContactRow,
+        r#"
+        SELECT
+            c.contact_id, c.display_name, c.address, c.city, c.state, c.zip_code, c.country,
+            c.capacity, c.latitude, c.longitude, c.email, c.contact_form, c.age_range,
+            c.user_id, c.org_id, c.is_private, c.contact_type,
+            ROUND(AVG(r.rating), 2)::real AS average_rating,
+            g.genre_name, g.genre_id
+        FROM contacts c
+        LEFT JOIN reviews r ON c.contact_id = r.contact_id
+        LEFT JOIN contacts_genres ON c.contact_id = contacts_genres.contact_id
+        LEFT JOIN genres g ON g.genre_id = contacts_genres.genre_id
+        WHERE c.contact_id = ANY($1)
+        GROUP BY
+            c.contact_id, g.genre_name, g.genre_id
+        ORDER BY c.contact_id
+        "#,
+        contact_ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(format_contact_response(rows))
+}
+
 #[tracing::instrument(
     name = "Querying genres for contact",
     skip(contact_id, transaction)
@@ -498,6 +568,7 @@ pub fn format_contact_response(rows: Vec<ContactRow>) -> Vec<ContactResponse> {
                 age_range:      row.age_range,
                 is_private:     row.is_private,
                 user_id:        row.user_id,
+                org_id:         row.org_id,
                 average_rating: row.average_rating,
                 contact_type:   row.contact_type,
                 genres:         [genre].to_vec()
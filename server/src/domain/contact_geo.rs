@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::domain::{format_contact_response, ContactResponse, ContactRow};
+use crate::gmaps_api_client::Location;
+
+const EARTH_RADIUS_KM:  f64 = 6371.0;
+const KM_PER_DEGREE:    f64 = 111.32;
+const MAX_RADIUS_KM:    f64 = 2000.0;
+
+/// Centre is either a `lat`/`lng` pair or a `city` that gets geocoded.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NearbyParams {
+    pub lat:       Option<f32>,
+    pub lng:       Option<f32>,
+    pub city:      Option<String>,
+    pub radius_km: f64,
+}
+
+impl NearbyParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.radius_km > 0.0 && self.radius_km <= MAX_RADIUS_KM) {
+            return Err(format!("radiusKm must be between 0 and {}", MAX_RADIUS_KM))
+        }
+
+        match (self.lat, self.lng, &self.city) {
+            (Some(lat), Some(lng), None) => validate_location(&Location { lat, lng }),
+            (None, None, Some(city)) if !city.trim().is_empty() => Ok(()),
+            _ => Err("Either lat and lng or city is required".to_string())
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub struct BoundingBox {
+    pub north: f32,
+    pub south: f32,
+    pub east:  f32,
+    pub west:  f32,
+}
+
+impl BoundingBox {
+    /// Smallest box containing every point within `radius_km` of `centre`.
+    pub fn around(centre: &Location, radius_km: f64) -> Self {
+        let lat_delta = radius_km / KM_PER_DEGREE;
+        let north     = (centre.lat as f64 + lat_delta).min(90.0);
+        let south     = (centre.lat as f64 - lat_delta).max(-90.0);
+
+        // Near the poles every longitude is within reach
+        let lng_delta = lat_delta / (centre.lat as f64).to_radians().cos();
+        if north >= 90.0 || south <= -90.0 || lng_delta >= 180.0 {
+            return Self { north: north as f32, south: south as f32, east: 180.0, west: -180.0 }
+        }
+
+        Self {
+            north: north as f32,
+            south: south as f32,
+            east:  wrap_longitude(centre.lng as f64 + lng_delta) as f32,
+            west:  wrap_longitude(centre.lng as f64 - lng_delta) as f32,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.south > self.north {
+            return Err("south cannot be larger than north".to_string())
+        }
+
+        for location in [
+            Location { lat: self.north, lng: self.east },
+            Location { lat: self.south, lng: self.west }
+        ] {
+            validate_location(&location)?;
+        }
+
+        Ok(())
+    }
+
+    /// Boxes crossing the antimeridian have `west > east`.
+    pub fn centre(&self) -> Location {
+        let east = if self.west > self.east { self.east + 360.0 } else { self.east };
+
+        Location {
+            lat: (self.north + self.south) / 2.0,
+            lng: wrap_longitude(((self.west + east) / 2.0) as f64) as f32,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactDistance {
+    #[serde(flatten)]
+    pub contact:     ContactResponse,
+    pub distance_km: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NearbyContacts {
+    pub centre:   Location,
+    pub contacts: Vec<ContactDistance>,
+}
+
+/// Great-circle distance between two points.
+pub fn haversine_km(a: &Location, b: &Location) -> f64 {
+    let (lat_a, lat_b) = ((a.lat as f64).to_radians(), (b.lat as f64).to_radians());
+    let d_lat          = lat_b - lat_a;
+    let d_lng          = (b.lng as f64 - a.lng as f64).to_radians();
+
+    let h = (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lng / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+#[tracing::instrument(
+    name = "Querying public contacts within a radius",
+    skip(pool)
+)]
+pub async fn query_contacts_near(
+    pool:      &PgPool,
+    centre:    &Location,
+    radius_km: f64
+) -> Result<Vec<ContactDistance>, sqlx::Error> {
+    let bounds   = BoundingBox::around(centre, radius_km);
+    let contacts = query_contacts_in_bounds(pool, &bounds, centre)
+        .await?
+        .into_iter()
+        .filter(|c| c.distance_km <= radius_km)
+        .collect();
+
+    Ok(contacts)
+}
+
+/// Contacts inside `bounds`, closest to `centre` first.
+#[tracing::instrument(
+    name = "Querying public contacts in bounding box",
+    skip(pool)
+)]
+pub async fn query_contacts_in_bounds(
+    pool:   &PgPool,
+    bounds: &BoundingBox,
+    centre: &Location
+) -> Result<Vec<ContactDistance>, sqlx::Error> {
+    let rows = sqlx::query_as!(
+        ContactRow,
+        r#"
+        SELECT
+            c.contact_id, c.display_name, c.address, c.city, c.state, c.zip_code, c.country,
+            c.capacity, c.latitude, c.longitude, c.email, c.contact_form, c.age_range,
+            c.user_id, c.org_id, c.is_private, c.contact_type,
+            ROUND(AVG(r.rating), 2)::real AS average_rating,
+            g.genre_name, g.genre_id
+        FROM contacts c
+        LEFT JOIN reviews r ON c.contact_id = r.contact_id
+        LEFT JOIN contacts_genres ON c.contact_id = contacts_genres.contact_id
+        LEFT JOIN genres g ON g.genre_id = contacts_genres.genre_id
+        WHERE c.is_private = false
+        AND c.latitude BETWEEN $1::real AND $2::real
+        AND (
+            ($3::real <= $4::real AND c.longitude BETWEEN $3 AND $4)
+            OR ($3::real > $4::real AND (c.longitude >= $3 OR c.longitude <= $4))
+        )
+        GROUP BY
+            c.contact_id, g.genre_name, g.genre_id
+        "#,
+        bounds.south,
+        bounds.north,
+        bounds.west,
+        bounds.east
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut contacts: Vec<ContactDistance> = format_contact_response(rows)
+        .into_iter()
+        .filter_map(|contact| {
+            let location = Location { lat: contact.latitude?, lng: contact.longitude? };
+
+            Some(ContactDistance { distance_km: haversine_km(centre, &location), contact })
+        })
+        .collect();
+
+    contacts.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km));
+
+    Ok(contacts)
+}
+
+fn validate_location(location: &Location) -> Result<(), String> {
+    if !(-90.0..=90.0).contains(&location.lat) {
+        return Err(format!("{} is not a valid latitude", location.lat))
+    }
+    if !(-180.0..=180.0).contains(&location.lng) {
+        return Err(format!("{} is not a valid longitude", location.lng))
+    }
+
+    Ok(())
+}
+
+fn wrap_longitude(lng: f64) -> f64 {
+    if lng > 180.0 {
+        lng - 360.0
+    } else if lng < -180.0 {
+        lng + 360.0
+    } else {
+        lng
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_err, assert_ok};
+
+    use crate::domain::{haversine_km, BoundingBox, NearbyParams};
+    use crate::gmaps_api_client::Location;
+
+    const ASHEVILLE: Location = Location { lat: 35.5951, lng: -82.5515 };
+    const RICHMOND:  Location = Location { lat: 37.5407, lng: -77.4360 };
+
+    #[test]
+    fn haversine_matches_known_distance() {
+        let distance = haversine_km(&ASHEVILLE, &RICHMOND);
+
+        // ~505 km as the crow flies
+        assert!((distance - 505.0).abs() < 5.0, "{}", distance);
+        assert_eq!(0.0, haversine_km(&RICHMOND, &RICHMOND));
+    }
+
+    #[test]
+    fn box_around_point_contains_radius() {
+        let bounds = BoundingBox::around(&RICHMOND, 150.0);
+
+        assert!(bounds.north > RICHMOND.lat + 1.3 && bounds.south < RICHMOND.lat - 1.3);
+        assert!(bounds.west < RICHMOND.lng - 1.6 && bounds.east > RICHMOND.lng + 1.6);
+    }
+
+    #[test]
+    fn box_near_antimeridian_wraps() {
+        let bounds = BoundingBox::around(&Location { lat: 0.0, lng: 179.5 }, 200.0);
+
+        assert!(bounds.west > bounds.east);
+        assert!((bounds.centre().lng - 179.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn box_near_pole_covers_every_longitude() {
+        let bounds = BoundingBox::around(&Location { lat: 89.5, lng: 10.0 }, 100.0);
+
+        assert_eq!((-180.0, 180.0), (bounds.west, bounds.east));
+    }
+
+    #[test]
+    fn nearby_params_need_one_kind_of_centre() {
+        let params = |lat, lng, city: Option<&str>, radius_km| NearbyParams {
+            lat,
+            lng,
+            city: city.map(str::to_string),
+            radius_km
+        };
+
+        assert_ok!(params(Some(37.5), Some(-77.4), None, 150.0).validate());
+        assert_ok!(params(None, None, Some("richmond"), 150.0).validate());
+        assert_err!(params(None, None, None, 150.0).validate());
+        assert_err!(params(Some(37.5), None, None, 150.0).validate());
+        assert_err!(params(Some(37.5), Some(-77.4), Some("richmond"), 150.0).validate());
+        assert_err!(params(Some(95.0), Some(-77.4), None, 150.0).validate());
+        assert_err!(params(None, None, Some("richmond"), 0.0).validate());
+    }
+}
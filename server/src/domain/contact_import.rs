@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{Genre, NewContact, NewContactData, UserEmail};
+
+const GENRE_SEPARATOR: char = ';';
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ImportMode {
+    #[default]
+    #[serde(rename = "dry-run")]
+    DryRun,
+    #[serde(rename = "commit")]
+    Commit,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ImportParams {
+    #[serde(default)]
+    pub mode: ImportMode,
+}
+
+/// A row of the import spreadsheet. Everything is read as text so a bad
+/// value can be reported against its row instead of failing the whole file.
+#[derive(Debug, Deserialize)]
+pub struct ContactImportRow {
+    pub display_name: Option<String>,
+    pub address:      Option<String>,
+    pub city:         Option<String>,
+    pub state:        Option<String>,
+    pub zip_code:     Option<String>,
+    pub capacity:     Option<String>,
+    pub email:        Option<String>,
+    pub contact_form: Option<String>,
+    pub age_range:    Option<String>,
+    pub contact_type: Option<String>,
+    pub is_private:   Option<String>,
+    pub genres:       Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowError {
+    pub row:    u64,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub mode:       ImportMode,
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub imported:   usize,
+    pub errors:     Vec<RowError>,
+}
+
+/// Parses an uploaded CSV into contacts ready for `insert_contact`. Rows are
+/// numbered by their line in the file so the header is row 1.
+pub fn parse_contact_csv(
+    data:   &[u8],
+    genres: &[Genre]
+) -> Result<Vec<Result<NewContact, RowError>>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(data);
+
+    let headers = reader.headers()
+        .map_err(|e| format!("Could not read CSV header: {}", e))?
+        .clone();
+
+    for required in ["display_name", "city"] {
+        if !headers.iter().any(|h| h == required) {
+            return Err(format!("CSV is missing the `{}` column", required))
+        }
+    }
+
+    let mut rows = Vec::new();
+
+    for record in reader.records() {
+        let row = match record {
+            Ok(record) => {
+                let line = record.position().map(|p| p.line()).unwrap_or_default();
+
+                record.deserialize::<ContactImportRow>(Some(&headers))
+                    .map_err(|e| vec![format!("Could not parse row: {}", e)])
+                    .and_then(|r| validate_row(r, genres))
+                    .map_err(|errors| RowError { row: line, errors })
+            }
+            Err(e) => {
+                let line = e.position().map(|p| p.line()).unwrap_or_default();
+
+                Err(RowError { row: line, errors: vec![format!("Could not parse row: {}", e)] })
+            }
+        };
+
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+fn validate_row(
+    row:    ContactImportRow,
+    genres: &[Genre]
+) -> Result<NewContact, Vec<String>> {
+    let mut errors = Vec::new();
+
+    let display_name = required(row.display_name, "display_name", &mut errors);
+    let city         = required(row.city, "city", &mut errors);
+    let age_range    = non_empty(row.age_range).unwrap_or_else(|| "all".to_string());
+    let contact_type = non_empty(row.contact_type).unwrap_or_else(|| "venue".to_string());
+
+    let email = non_empty(row.email);
+    if let Some(email) = &email {
+        if let Err(e) = UserEmail::parse(email.clone()) {
+            errors.push(e);
+        }
+    }
+
+    let capacity = match non_empty(row.capacity) {
+        Some(c) => match c.parse::<i32>() {
+            Ok(c) if c >= 0 => Some(c),
+            _ => {
+                errors.push(format!("{} is not a valid capacity", c));
+                None
+            }
+        },
+        None => None
+    };
+
+    let is_private = match non_empty(row.is_private).map(|p| p.to_lowercase()) {
+        None => false,
+        Some(p) if ["true", "yes", "1"].contains(&p.as_str()) => true,
+        Some(p) if ["false", "no", "0"].contains(&p.as_str()) => false,
+        Some(p) => {
+            errors.push(format!("{} is not a valid value for is_private", p));
+            false
+        }
+    };
+
+    let mut genre_ids      = Vec::new();
+    let mut unknown_genres = false;
+    for name in non_empty(row.genres).unwrap_or_default().split(GENRE_SEPARATOR) {
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+
+        match genres.iter().find(|g| g.genre_name.eq_ignore_ascii_case(name)) {
+            Some(genre) if !genre_ids.contains(&genre.genre_id) => genre_ids.push(genre.genre_id),
+            Some(_) => {}
+            None => {
+                unknown_genres = true;
+                errors.push(format!("{} is not a known genre", name));
+            }
+        }
+    }
+
+    if genre_ids.is_empty() && !unknown_genres {
+        errors.push("At least one genre is required".to_string());
+    }
+
+    if !errors.is_empty() {
+        return Err(errors)
+    }
+
+    let data = NewContactData {
+        display_name,
+        address:      row.address,
+        city,
+        state:        row.state,
+        zip_code:     row.zip_code,
+        capacity,
+        email,
+        contact_form: row.contact_form,
+        age_range,
+        is_private,
+        contact_type,
+        genres:       genre_ids,
+    };
+
+    NewContact::try_from(data).map_err(|e| vec![e])
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.trim().is_empty())
+}
+
+fn required(value: Option<String>, column: &str, errors: &mut Vec<String>) -> String {
+    match non_empty(value) {
+        Some(v) => v,
+        None => {
+            errors.push(format!("{} is required", column));
+            String::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_err, assert_ok};
+
+    use crate::domain::{parse_contact_csv, Genre};
+
+    const HEADER: &str = "display_name,address,city,state,zip_code,capacity,email,contact_form,age_range,contact_type,is_private,genres\n";
+
+    fn genres() -> Vec<Genre> {
+        vec![
+            Genre { genre_id: 1, genre_name: "alternative/rock".to_string() },
+            Genre { genre_id: 3, genre_name: "punk/hardcore".to_string() },
+        ]
+    }
+
+    fn parse(rows: &str) -> Vec<Result<crate::domain::NewContact, crate::domain::RowError>> {
+        let csv = format!("{}{}", HEADER, rows);
+        parse_contact_csv(csv.as_bytes(), &genres()).unwrap()
+    }
+
+    #[test]
+    fn valid_row_is_parsed() {
+        let rows = parse("Static Age,16 Lexington,Asheville,NC,28806,99,info@static.com,,all,venue,false,Punk/Hardcore;alternative/rock\n");
+        let contact = rows[0].as_ref().unwrap();
+
+        assert_eq!(contact.display_name, "Static Age");
+        assert_eq!(contact.capacity, Some(99));
+        assert_eq!(contact.genres, vec![3, 1]);
+    }
+
+    #[test]
+    fn row_is_sanitised_like_other_contacts() {
+        let rows = parse("\"<Static Age>\",,Asheville,,,,,,all,venue,,punk/hardcore\n");
+
+        assert_eq!(rows[0].as_ref().unwrap().display_name, "Static Age");
+    }
+
+    #[test]
+    fn missing_city_is_reported() {
+        let rows  = parse("Static Age,,,,,,,,all,venue,,punk/hardcore\n");
+        let error = rows[0].as_ref().unwrap_err();
+
+        assert_eq!(error.row, 2);
+        assert_eq!(error.errors, vec!["city is required".to_string()]);
+    }
+
+    #[test]
+    fn every_problem_with_a_row_is_reported() {
+        let rows  = parse("ok,,Asheville,,,,info@static.com,,all,venue,,punk/hardcore\nbad,,Asheville,,,lots,not-an-email,,all,venue,,polka\n");
+        let error = rows[1].as_ref().unwrap_err();
+
+        assert_ok!(&rows[0]);
+        assert_eq!(error.row, 3);
+        assert_eq!(error.errors.len(), 3);
+        assert!(error.errors.contains(&"polka is not a known genre".to_string()));
+    }
+
+    #[test]
+    fn row_without_genres_is_rejected() {
+        let rows = parse("Static Age,,Asheville,,,,,,all,venue,,\n");
+
+        assert_err!(&rows[0]);
+    }
+
+    #[test]
+    fn file_without_required_columns_is_rejected() {
+        assert_err!(parse_contact_csv(b"name,town\nStatic Age,Asheville\n", &genres()));
+    }
+}
@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::domain::{query_contacts_by_ids, ContactResponse, StringInput};
+
+const MAX_PAGE_SIZE: i64 = 1000;
+
+/// Query string accepted by `/contacts`. List filters are comma separated,
+/// e.g. `?ageRanges=all,18%2B&genres=1,3`. Omitting `limit` returns every match.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactSearchParams {
+    pub q:                   Option<String>,
+    pub min_capacity:        Option<i32>,
+    pub max_capacity:        Option<i32>,
+    pub allow_null_capacity: Option<bool>,
+    pub age_ranges:          Option<String>,
+    pub contact_types:       Option<String>,
+    pub genres:              Option<String>,
+    pub cursor:              Option<i32>,
+    pub limit:               Option<i64>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ContactFilters {
+    pub search:              Option<String>,
+    pub min_capacity:        Option<i32>,
+    pub max_capacity:        Option<i32>,
+    pub allow_null_capacity: bool,
+    pub age_ranges:          Option<Vec<String>>,
+    pub contact_types:       Option<Vec<String>>,
+    pub genres:              Option<Vec<i32>>,
+    pub cursor:              Option<i32>,
+    pub limit:               Option<i64>,
+}
+
+impl TryFrom<ContactSearchParams> for ContactFilters {
+    type Error = String;
+
+    fn try_from(value: ContactSearchParams) -> Result<Self, Self::Error> {
+        let search = value.q
+            .map(StringInput::parse)
+            .map(|q| q.trim().to_string())
+            .filter(|q| !q.is_empty())
+            .map(|q| format!("%{}%", escape_like(&q)));
+
+        let genres = match value.genres {
+            Some(g) => Some(
+                split_list(&g)
+                    .into_iter()
+                    .map(|id| id.parse::<i32>().map_err(|_| format!("{} is not a valid genre id", id)))
+                    .collect::<Result<Vec<_>, _>>()?
+            ),
+            None => None
+        };
+
+        if let (Some(min), Some(max)) = (value.min_capacity, value.max_capacity) {
+            if min > max {
+                return Err("minCapacity cannot be larger than maxCapacity".to_string())
+            }
+        }
+
+        let limit = match value.limit {
+            Some(l) if !(1..=MAX_PAGE_SIZE).contains(&l) => {
+                return Err(format!("limit must be between 1 and {}", MAX_PAGE_SIZE))
+            }
+            limit => limit
+        };
+
+        Ok(Self {
+            search,
+            min_capacity:        value.min_capacity,
+            max_capacity:        value.max_capacity,
+            allow_null_capacity: value.allow_null_capacity.unwrap_or(true),
+            age_ranges:          value.age_ranges.map(|a| split_list(&a)),
+            contact_types:       value.contact_types.map(|t| split_list(&t)),
+            genres,
+            cursor:              value.cursor,
+            limit
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactPage {
+    pub contacts:    Vec<ContactResponse>,
+    pub total_count: i64,
+    pub next_cursor: Option<i32>,
+}
+
+#[tracing::instrument(
+    name = "Searching public contacts in DB",
+    skip(pool, filters)
+)]
+pub async fn search_public_contacts(
+    pool:    &PgPool,
+    filters: &ContactFilters,
+) -> Result<ContactPage, sqlx::Error> {
+    // One extra id is fetched to know whether there's another page
+    let page = sqlx::query!(
+        r#"
+        WITH matches AS (
+            SELECT c.contact_id
+            FROM contacts c
+            WHERE c.is_private = false
+            AND (
+                (c.capacity IS NULL AND $3)
+                OR (
+                    c.capacity IS NOT NULL
+                    AND ($1::int IS NULL OR c.capacity >= $1)
+                    AND ($2::int IS NULL OR c.capacity <= $2)
+                )
+            )
+            AND ($4::text[] IS NULL OR c.age_range = ANY($4))
+            AND ($5::text[] IS NULL OR c.contact_type = ANY($5))
+            AND ($6::int[] IS NULL OR EXISTS (
+                SELECT 1 FROM contacts_genres cg
+                WHERE cg.contact_id = c.contact_id AND cg.genre_id = ANY($6)
+            ))
+            AND ($7::text IS NULL
+                OR c.display_name ILIKE $7
+                OR c.city ILIKE $7
+                OR c.state ILIKE $7
+                OR c.address ILIKE $7
+            )
+        ),
+        page AS (
+            SELECT contact_id FROM matches
+            WHERE $8::int IS NULL OR contact_id > $8
+            ORDER BY contact_id
+            LIMIT $9::bigint + 1
+        )
+        SELECT
+            (SELECT COUNT(*) FROM matches) AS "total_count!",
+            ARRAY(SELECT contact_id FROM page ORDER BY contact_id) AS "contact_ids!"
+        "#,
+        filters.min_capacity,
+        filters.max_capacity,
+        filters.allow_null_capacity,
+        filters.age_ranges.as_deref(),
+        filters.contact_types.as_deref(),
+        filters.genres.as_deref(),
+        filters.search,
+        filters.cursor,
+        filters.limit,
+    ).fetch_one(pool)
+    .await?;
+
+    let mut contact_ids = page.contact_ids;
+    let next_cursor     = match filters.limit {
+        Some(limit) if contact_ids.len() as i64 > limit => {
+            contact_ids.truncate(limit as usize);
+            contact_ids.last().copied()
+        }
+        _ => None
+    };
+
+    let contacts = query_contacts_by_ids(pool, &contact_ids).await?;
+
+    Ok(ContactPage {
+        contacts,
+        total_count: page.total_count,
+        next_cursor
+    })
+}
+
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn split_list(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::assert_err;
+
+    use crate::domain::{ContactFilters, ContactSearchParams};
+
+    #[test]
+    fn empty_params_match_everything() {
+        let filters = ContactFilters::try_from(ContactSearchParams::default()).unwrap();
+
+        assert_eq!(filters.search, None);
+        assert_eq!(filters.genres, None);
+        assert_eq!(filters.limit, None);
+        assert!(filters.allow_null_capacity);
+    }
+
+    #[test]
+    fn lists_are_split_on_commas() {
+        let params = ContactSearchParams {
+            age_ranges: Some("all, 21+,".to_string()),
+            genres:     Some("1,3".to_string()),
+            ..Default::default()
+        };
+        let filters = ContactFilters::try_from(params).unwrap();
+
+        assert_eq!(filters.age_ranges, Some(vec!["all".to_string(), "21+".to_string()]));
+        assert_eq!(filters.genres, Some(vec![1, 3]));
+    }
+
+    #[test]
+    fn search_text_is_escaped_for_like() {
+        let params = ContactSearchParams {
+            q: Some("100%_club".to_string()),
+            ..Default::default()
+        };
+        let filters = ContactFilters::try_from(params).unwrap();
+
+        assert_eq!(filters.search, Some("%100\\%\\_club%".to_string()));
+    }
+
+    #[test]
+    fn invalid_genre_ids_are_rejected() {
+        let params = ContactSearchParams {
+            genres: Some("1,punk".to_string()),
+            ..Default::default()
+        };
+
+        assert_err!(ContactFilters::try_from(params));
+    }
+
+    #[test]
+    fn oversized_limit_is_rejected() {
+        let params = ContactSearchParams {
+            limit: Some(100_000),
+            ..Default::default()
+        };
+
+        assert_err!(ContactFilters::try_from(params));
+    }
+}
@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::domain::{decode_enum, query_contacts_by_ids, ActiveOrganisation, ContactResponse, UserEmail};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContactAccess {
+    Read,
+    Edit,
+}
+
+impl ContactAccess {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContactAccess::Read => "read",
+            ContactAccess::Edit => "edit",
+        }
+    }
+}
+
+impl TryFrom<String> for ContactAccess {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "read" => Ok(Self::Read),
+            "edit" => Ok(Self::Edit),
+            other => Err(format!("{} is not a supported access level", other))
+        }
+    }
+}
+
+/// What a user is allowed to do with a contact
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContactPermission {
+    Owner,
+    Shared(ContactAccess),
+}
+
+impl ContactPermission {
+    pub fn can_edit(&self) -> bool {
+        matches!(self, Self::Owner | Self::Shared(ContactAccess::Edit))
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareContactData {
+    pub contact_id: i32,
+    pub email:      String,
+    pub access:     Option<ContactAccess>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnshareContactData {
+    pub contact_id: i32,
+    pub email:      String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactShareParams {
+    pub contact_id: i32,
+}
+
+/// A grant as the contact's owner sees it
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactShare {
+    pub email:  String,
+    pub access: ContactAccess,
+}
+
+/// A contact as the user it was shared with sees it
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedContact {
+    #[serde(flatten)]
+    pub contact:   ContactResponse,
+    /// Email of whoever shared it
+    pub shared_by: String,
+    pub access:    ContactAccess,
+}
+
+#[tracing::instrument(
+    name = "Removing contact share",
+    skip(pool)
+)]
+pub async fn delete_contact_share(
+    contact_id: &i32,
+    user_id:    &Uuid,
+    pool:       &PgPool
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM contact_shares WHERE contact_id = $1 AND user_id = $2
+        "#,
+        contact_id,
+        user_id
+    ).execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Querying user's permission for contact",
+    skip(pool)
+)]
+pub async fn query_contact_permission(
+    contact_id: &i32,
+    user_id:    &Uuid,
+    org:        Option<&ActiveOrganisation>,
+    pool:       &PgPool
+) -> Result<Option<ContactPermission>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT c.user_id, c.org_id, s.access AS "access?"
+        FROM contacts c
+        LEFT JOIN contact_shares s ON s.contact_id = c.contact_id AND s.user_id = $2
+        WHERE c.contact_id = $1
+        "#,
+        contact_id,
+        user_id
+    ).fetch_optional(pool)
+    .await?;
+
+    // An organisation's contacts belong to whoever can edit for it
+    let permission = match row {
+        Some(row) if row.org_id.is_some() && row.org_id == org.map(|o| o.org_id) => {
+            match org.map(|o| o.role.can_edit()) {
+                Some(true) => Some(ContactPermission::Owner),
+                _ => Some(ContactPermission::Shared(ContactAccess::Read)),
+            }
+        }
+        Some(row) if row.org_id.is_none() && &row.user_id == user_id => Some(ContactPermission::Owner),
+        Some(row) => match row.access {
+            Some(access) => Some(ContactPermission::Shared(decode_enum(access)?)),
+            None => None,
+        },
+        None => None,
+    };
+
+    Ok(permission)
+}
+
+#[tracing::instrument(
+    name = "Querying shares for contact",
+    skip(pool)
+)]
+pub async fn query_contact_shares(
+    contact_id: &i32,
+    pool:       &PgPool
+) -> Result<Vec<ContactShare>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT u.email, s.access
+        FROM contact_shares s
+        JOIN users u ON u.user_id = s.user_id
+        WHERE s.contact_id = $1
+        ORDER BY u.email
+        "#,
+        contact_id
+    ).fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| Ok(ContactShare {
+            email:  row.email,
+            access: decode_enum(row.access)?,
+        }))
+        .collect()
+}
+
+#[tracing::instrument(
+    name = "Querying contacts shared with user",
+    skip(pool)
+)]
+pub async fn query_shared_contacts(
+    user_id: &Uuid,
+    pool:    &PgPool
+) -> Result<Vec<SharedContact>, sqlx::Error> {
+    let shares = sqlx::query!(
+        r#"
+        SELECT s.contact_id, s.access, u.email AS shared_by
+        FROM contact_shares s
+        JOIN users u ON u.user_id = s.shared_by
+        WHERE s.user_id = $1
+        "#,
+        user_id
+    ).fetch_all(pool)
+    .await?;
+
+    let contact_ids: Vec<i32> = shares.iter().map(|s| s.contact_id).collect();
+    let contacts              = query_contacts_by_ids(pool, &contact_ids).await?;
+
+    contacts.into_iter()
+        .filter_map(|contact| {
+            let share = shares.iter().find(|s| s.contact_id == contact.contact_id)?;
+
+            Some(decode_enum::<ContactAccess>(share.access.clone())
+                .map(|access| SharedContact {
+                    contact,
+                    shared_by: share.shared_by.clone(),
+                    access,
+                }))
+        })
+        .collect()
+}
+
+#[tracing::instrument(
+    name = "Querying ids of contacts shared with user",
+    skip(pool)
+)]
+pub async fn query_shared_contact_ids(
+    user_id: &Uuid,
+    pool:    &PgPool
+) -> Result<Vec<i32>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT contact_id FROM contact_shares WHERE user_id = $1
+        "#,
+        user_id
+    ).fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.contact_id).collect())
+}
+
+#[tracing::instrument(
+    name = "Looking up user id from email",
+    skip(email, pool)
+)]
+pub async fn query_user_id_by_email(
+    email: &UserEmail,
+    pool:  &PgPool
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"SELECT user_id FROM users WHERE email = $1"#,
+        email.as_ref()
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(result.map(|r| r.user_id))
+}
+
+#[tracing::instrument(
+    name = "Sharing contact with user",
+    skip(pool)
+)]
+pub async fn upsert_contact_share(
+    contact_id: &i32,
+    user_id:    &Uuid,
+    shared_by:  &Uuid,
+    access:     ContactAccess,
+    pool:       &PgPool
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO contact_shares (contact_id, user_id, shared_by, access)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (contact_id, user_id)
+        DO UPDATE SET access = EXCLUDED.access, updated_at = current_timestamp
+        "#,
+        contact_id,
+        user_id,
+        shared_by,
+        access.as_str()
+    ).execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::assert_err;
+
+    use crate::domain::{ContactAccess, ContactPermission};
+
+    #[test]
+    fn access_round_trips_through_strings() {
+        for access in [ContactAccess::Read, ContactAccess::Edit] {
+            assert_eq!(ContactAccess::try_from(access.as_str().to_string()), Ok(access));
+        }
+        assert_err!(ContactAccess::try_from("delete".to_string()));
+    }
+
+    #[test]
+    fn only_owners_and_editors_can_edit() {
+        assert!(ContactPermission::Owner.can_edit());
+        assert!(ContactPermission::Shared(ContactAccess::Edit).can_edit());
+        assert!(!ContactPermission::Shared(ContactAccess::Read).can_edit());
+    }
+}
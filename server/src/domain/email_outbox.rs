@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::domain::UserEmail;
+use crate::email_templates::RenderedEmail;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailStatus {
+    Queued,
+    Sent,
+    /// Gave up after too many attempts. The default, since that's what
+    /// admins come looking for.
+    #[default]
+    Failed,
+}
+
+impl EmailStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Sent => "sent",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct OutboxParams {
+    #[serde(default)]
+    pub status: EmailStatus,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResendEmailData {
+    pub email_id: Uuid,
+}
+
+/// An email waiting to be handed to the mailer.
+#[derive(Debug)]
+pub struct NewOutboxEmail<'a> {
+    pub recipient: &'a UserEmail,
+    pub reply_to:  Option<&'a UserEmail>,
+    pub subject:   &'a str,
+    pub html_body: &'a str,
+    pub text_body: &'a str,
+}
+
+impl<'a> NewOutboxEmail<'a> {
+    pub fn from_template(recipient: &'a UserEmail, email: &'a RenderedEmail) -> Self {
+        Self {
+            recipient,
+            reply_to:  None,
+            subject:   &email.subject,
+            html_body: &email.html_body,
+            text_body: &email.text_body,
+        }
+    }
+}
+
+/// What admins see of an email. Bodies are left out since they can hold
+/// confirmation and reset tokens.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxEmail {
+    pub email_id:   Uuid,
+    pub recipient:  String,
+    pub subject:    String,
+    pub status:     String,
+    pub attempts:   i32,
+    pub run_after:  chrono::NaiveDateTime,
+    pub last_error: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Queues an email for the email worker. Pass the transaction that creates
+/// whatever the email is about, so either both are saved or neither is.
+#[tracing::instrument(
+    name = "Queueing email",
+    skip(email, executor),
+    fields(subject = %email.subject)
+)]
+pub async fn enqueue_email(
+    email:    &NewOutboxEmail<'_>,
+    executor: impl PgExecutor<'_>
+) -> Result<Uuid, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO email_outbox (recipient, reply_to, subject, html_body, text_body)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING email_id
+        "#,
+        email.recipient.as_ref(),
+        email.reply_to.map(|reply_to| reply_to.as_ref()),
+        email.subject,
+        email.html_body,
+        email.text_body
+    ).fetch_one(executor)
+    .await?;
+
+    Ok(result.email_id)
+}
+
+#[tracing::instrument(
+    name = "Querying outbox emails",
+    skip(pool)
+)]
+pub async fn query_outbox_emails(
+    status: EmailStatus,
+    pool:   &PgPool
+) -> Result<Vec<OutboxEmail>, sqlx::Error> {
+    let emails = sqlx::query_as!(
+        OutboxEmail,
+        r#"
+        SELECT
+            email_id, recipient, subject, status, attempts, run_after,
+            last_error, created_at
+        FROM email_outbox
+        WHERE status = $1
+        ORDER BY updated_at DESC
+        "#,
+        status.as_str()
+    ).fetch_all(pool)
+    .await?;
+
+    Ok(emails)
+}
+
+/// Puts a failed email back in the queue with a fresh set of attempts.
+/// Returns false if there's no failed email with that id.
+#[tracing::instrument(
+    name = "Requeueing failed email",
+    skip(pool)
+)]
+pub async fn requeue_failed_email(
+    email_id: &Uuid,
+    pool:     &PgPool
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE email_outbox
+        SET
+            status = 'queued',
+            attempts = 0,
+            run_after = current_timestamp,
+            last_error = NULL,
+            updated_at = current_timestamp
+        WHERE email_id = $1
+        AND status = 'failed'
+        "#,
+        email_id
+    ).execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
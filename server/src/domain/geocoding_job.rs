@@ -0,0 +1,418 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::exporter::join_address;
+use crate::geocoder::GeocodeResult;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GeocodingReason {
+    Approval,
+    AddressChange,
+    Backfill,
+}
+
+impl GeocodingReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Approval => "approval",
+            Self::AddressChange => "address_change",
+            Self::Backfill => "backfill",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GeocodingStatus {
+    #[default]
+    Queued,
+    Done,
+    Failed,
+}
+
+impl GeocodingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Done => "done",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct GeocodingJobParams {
+    #[serde(default)]
+    pub status: GeocodingStatus,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeocodingJob {
+    pub job_id:       Uuid,
+    pub contact_id:   i32,
+    pub display_name: String,
+    pub reason:       String,
+    pub status:       String,
+    pub attempts:     i32,
+    pub run_after:    chrono::NaiveDateTime,
+    pub last_error:   Option<String>,
+}
+
+/// The parts of a contact the geocoder needs.
+#[derive(Debug)]
+pub struct ContactAddress {
+    pub address:  Option<String>,
+    pub city:     String,
+    pub state:    Option<String>,
+    pub zip_code: Option<String>,
+    pub country:  Option<String>,
+    pub verified: bool,
+}
+
+impl ContactAddress {
+    pub fn full_address(&self) -> String {
+        join_address(&[
+            self.address.as_deref(),
+            Some(self.city.as_str()),
+            self.state.as_deref(),
+            self.zip_code.as_deref(),
+            self.country.as_deref(),
+        ])
+    }
+
+    pub fn differs_from(&self, other: &ContactAddress) -> bool {
+        self.full_address() != other.full_address()
+    }
+}
+
+/// A contact whose typed address disagrees with where the geocoder put it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressMismatch {
+    pub contact_id:           i32,
+    pub display_name:         String,
+    pub address:              Option<String>,
+    pub city:                 String,
+    pub state:                Option<String>,
+    pub zip_code:             Option<String>,
+    pub country:              Option<String>,
+    pub geocoded_address:     Option<String>,
+    pub geocoded_locality:    Option<String>,
+    pub geocoded_region:      Option<String>,
+    pub geocoded_postal_code: Option<String>,
+    pub geocoded_country:     Option<String>,
+    pub address_mismatches:   Vec<String>,
+}
+
+/// Fields of the typed address that don't match the geocoded one. Fields
+/// left blank aren't compared, and "N.C.", "NC" and "North Carolina" are
+/// all the same region.
+pub fn address_mismatches(typed: &ContactAddress, result: &GeocodeResult) -> Vec<String> {
+    let components = &result.components;
+    let mut fields = Vec::new();
+
+    if let Some(locality) = &components.locality {
+        if !same_name(&typed.city, &[locality]) {
+            fields.push("city".to_string());
+        }
+    }
+
+    if let (Some(state), Some(region)) = (non_blank(&typed.state), &components.region) {
+        let region_name = components.region_name.as_ref().unwrap_or(region);
+        if !same_name(state, &[region, region_name]) {
+            fields.push("state".to_string());
+        }
+    }
+
+    if let (Some(zip_code), Some(postal_code)) = (non_blank(&typed.zip_code), &components.postal_code) {
+        // ZIP+4 and similar extensions still count as a match
+        let (zip_code, postal_code) = (simplify(zip_code), simplify(postal_code));
+        if !zip_code.starts_with(&postal_code) && !postal_code.starts_with(&zip_code) {
+            fields.push("zipCode".to_string());
+        }
+    }
+
+    if let (Some(country), Some(code)) = (non_blank(&typed.country), &result.country) {
+        let country_name = components.country_name.as_ref().unwrap_or(code);
+        // Google ends its formatted addresses with a short country name, e.g. "USA"
+        let last_part    = result.formatted_address
+            .rsplit(',')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        if !same_name(country, &[code, country_name, &last_part]) {
+            fields.push("country".to_string());
+        }
+    }
+
+    fields
+}
+
+fn non_blank(value: &Option<String>) -> Option<&String> {
+    value.as_ref().filter(|v| !v.trim().is_empty())
+}
+
+fn simplify(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn same_name(typed: &str, names: &[&String]) -> bool {
+    let typed = simplify(typed);
+
+    names.iter().any(|name| simplify(name) == typed)
+}
+
+#[tracing::instrument(
+    name = "Saving geocoding result to contact",
+    skip(result, transaction)
+)]
+pub async fn save_geocode_result(
+    contact_id:  &i32,
+    result:      &GeocodeResult,
+    mismatches:  &[String],
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<(), sqlx::Error> {
+    let components = &result.components;
+
+    sqlx::query!(
+        r#"
+        UPDATE contacts
+        SET
+            latitude = $1,
+            longitude = $2,
+            geocoded_address = $3,
+            geocoded_locality = $4,
+            geocoded_region = $5,
+            geocoded_postal_code = $6,
+            geocoded_country = $7,
+            address_mismatches = $8,
+            geocoded_at = current_timestamp
+        WHERE contact_id = $9
+        "#,
+        result.location.lat,
+        result.location.lng,
+        result.formatted_address,
+        components.locality,
+        components.region,
+        components.postal_code,
+        result.country,
+        mismatches,
+        contact_id
+    ).execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+/// Re-queueing a job a worker has claimed drops the claim, so a result for
+/// the old address isn't saved over the new one
+#[tracing::instrument(
+    name = "Queueing geocoding job",
+    skip(executor)
+)]
+pub async fn enqueue_geocoding_job(
+    contact_id: &i32,
+    reason:     GeocodingReason,
+    executor:   impl PgExecutor<'_>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO geocoding_jobs (contact_id, reason)
+        VALUES ($1, $2)
+        ON CONFLICT (contact_id) WHERE status = 'queued'
+        DO UPDATE SET
+            reason = EXCLUDED.reason,
+            attempts = 0,
+            run_after = current_timestamp,
+            last_error = NULL,
+            locked_until = NULL,
+            updated_at = current_timestamp
+        "#,
+        contact_id,
+        reason.as_str()
+    ).execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Queues a job when a verified contact's address no longer matches
+/// `previous`. Unverified contacts get geocoded once they're approved.
+#[tracing::instrument(
+    name = "Queueing geocoding job if address changed",
+    skip(previous, transaction)
+)]
+pub async fn enqueue_if_address_changed(
+    contact_id:  &i32,
+    previous:    &ContactAddress,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<(), sqlx::Error> {
+    let current = query_contact_address(contact_id, &mut *transaction).await?;
+
+    if let Some(current) = current {
+        if current.verified && current.differs_from(previous) {
+            enqueue_geocoding_job(contact_id, GeocodingReason::AddressChange, transaction).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Queues every verified contact that has no coordinates yet. Returns how
+/// many jobs were queued.
+#[tracing::instrument(
+    name = "Queueing geocoding jobs for contacts missing coordinates",
+    skip(pool)
+)]
+pub async fn enqueue_missing_coordinates(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO geocoding_jobs (contact_id, reason)
+        SELECT contact_id, 'backfill'
+        FROM contacts
+        WHERE verified = true
+        AND (latitude IS NULL OR longitude IS NULL)
+        ON CONFLICT (contact_id) WHERE status = 'queued'
+        DO NOTHING
+        "#
+    ).execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[tracing::instrument(
+    name = "Querying contact address",
+    skip(executor)
+)]
+pub async fn query_contact_address(
+    contact_id: &i32,
+    executor:   impl PgExecutor<'_>
+) -> Result<Option<ContactAddress>, sqlx::Error> {
+    let address = sqlx::query_as!(
+        ContactAddress,
+        r#"
+        SELECT address, city, state, zip_code, country, verified
+        FROM contacts
+        WHERE contact_id = $1
+        "#,
+        contact_id
+    ).fetch_optional(executor)
+    .await?;
+
+    Ok(address)
+}
+
+#[tracing::instrument(
+    name = "Querying geocoding jobs",
+    skip(pool)
+)]
+pub async fn query_geocoding_jobs(
+    status: GeocodingStatus,
+    pool:   &PgPool
+) -> Result<Vec<GeocodingJob>, sqlx::Error> {
+    let jobs = sqlx::query_as!(
+        GeocodingJob,
+        r#"
+        SELECT
+            j.job_id, j.contact_id, c.display_name, j.reason, j.status,
+            j.attempts, j.run_after, j.last_error
+        FROM geocoding_jobs j
+        JOIN contacts c ON c.contact_id = j.contact_id
+        WHERE j.status = $1
+        ORDER BY j.updated_at DESC
+        "#,
+        status.as_str()
+    ).fetch_all(pool)
+    .await?;
+
+    Ok(jobs)
+}
+
+#[tracing::instrument(
+    name = "Querying contacts with mismatched addresses",
+    skip(pool)
+)]
+pub async fn query_address_mismatches(
+    pool: &PgPool
+) -> Result<Vec<AddressMismatch>, sqlx::Error> {
+    let contacts = sqlx::query_as!(
+        AddressMismatch,
+        r#"
+        SELECT
+            contact_id, display_name, address, city, state, zip_code, country,
+            geocoded_address, geocoded_locality, geocoded_region,
+            geocoded_postal_code, geocoded_country, address_mismatches
+        FROM contacts
+        WHERE cardinality(address_mismatches) > 0
+        ORDER BY contact_id
+        "#
+    ).fetch_all(pool)
+    .await?;
+
+    Ok(contacts)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{address_mismatches, ContactAddress};
+    use crate::geocoder::{AddressComponents, GeocodeResult};
+    use crate::gmaps_api_client::Location;
+
+    fn typed(city: &str, state: &str, zip_code: &str, country: Option<&str>) -> ContactAddress {
+        ContactAddress {
+            address:  Some("16 Lexington Ave".to_string()),
+            city:     city.to_string(),
+            state:    Some(state.to_string()),
+            zip_code: Some(zip_code.to_string()),
+            country:  country.map(str::to_string),
+            verified: true,
+        }
+    }
+
+    fn result() -> GeocodeResult {
+        GeocodeResult {
+            location:          Location { lat: 35.5951, lng: -82.5515 },
+            formatted_address: "16 Lexington Ave, Asheville, NC 28801, USA".to_string(),
+            country:           Some("US".to_string()),
+            place_id:          None,
+            confidence:        1.0,
+            components:        AddressComponents {
+                locality:     Some("Asheville".to_string()),
+                region:       Some("NC".to_string()),
+                region_name:  Some("North Carolina".to_string()),
+                postal_code:  Some("28801".to_string()),
+                country_name: Some("United States".to_string()),
+            },
+        }
+    }
+
+    #[test]
+    fn spelling_variants_match() {
+        for state in ["NC", "N.C.", "north carolina"] {
+            assert!(address_mismatches(&typed("asheville", state, "28801-1234", Some("USA")), &result()).is_empty());
+        }
+    }
+
+    #[test]
+    fn differing_fields_are_reported() {
+        let mismatches = address_mismatches(&typed("Black Mountain", "SC", "28801", Some("Canada")), &result());
+
+        assert_eq!(vec!["city", "state", "country"], mismatches);
+    }
+
+    #[test]
+    fn blank_fields_are_not_compared() {
+        let mut contact = typed("Asheville", "", "", None);
+        contact.state   = None;
+
+        assert!(address_mismatches(&contact, &result()).is_empty());
+    }
+}
@@ -0,0 +1,464 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::domain::StringInput;
+use crate::email_templates::{escape_html, fill, placeholders};
+
+/// Placeholders a template can use, written as `{{venue_name}}`
+pub const INQUIRY_PLACEHOLDERS: [&str; 4] = ["venue_name", "city", "dates", "band_link"];
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InquiryTemplateData {
+    pub template_name: String,
+    pub subject:       String,
+    pub body:          String,
+}
+
+#[derive(Debug)]
+pub struct NewInquiryTemplate {
+    pub template_name: String,
+    pub subject:       String,
+    pub body:          String,
+}
+
+impl TryFrom<InquiryTemplateData> for NewInquiryTemplate {
+    type Error = String;
+
+    fn try_from(value: InquiryTemplateData) -> Result<Self, Self::Error> {
+        // StringInput would strip the braces out of the placeholders. The
+        // body is escaped when it's rendered to HTML instead.
+        let template_name = StringInput::parse(value.template_name);
+        let subject       = value.subject;
+        let body          = value.body;
+
+        if template_name.trim().is_empty() {
+            return Err("Template name cannot be empty".to_string())
+        }
+
+        if subject.trim().is_empty() || body.trim().is_empty() {
+            return Err("Template needs a subject and a body".to_string())
+        }
+
+        for text in [&subject, &body] {
+            for placeholder in placeholders(text) {
+                if !INQUIRY_PLACEHOLDERS.contains(&placeholder) {
+                    return Err(format!("{{{{{}}}}} is not a supported placeholder", placeholder))
+                }
+            }
+        }
+
+        Ok(Self { template_name, subject, body })
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditInquiryTemplateData {
+    pub template_id: Uuid,
+    #[serde(flatten)]
+    pub template:    InquiryTemplateData,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InquiryTemplateDeleteData {
+    pub template_id: Uuid,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InquiryTemplate {
+    pub template_id:   Uuid,
+    pub user_id:       Uuid,
+    pub template_name: String,
+    pub subject:       String,
+    pub body:          String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendInquiryData {
+    pub template_id:    Uuid,
+    pub contact_id:     i32,
+    pub requested_from: Option<NaiveDate>,
+    pub requested_to:   Option<NaiveDate>,
+    pub band_link:      Option<String>,
+}
+
+/// What the placeholders are filled in with for one contact
+#[derive(Debug)]
+pub struct InquiryValues {
+    pub venue_name: String,
+    pub city:       String,
+    pub dates:      Option<String>,
+    pub band_link:  Option<String>,
+}
+
+impl InquiryValues {
+    fn get(&self, placeholder: &str) -> Option<&str> {
+        match placeholder {
+            "venue_name" => Some(&self.venue_name),
+            "city" => Some(&self.city),
+            "dates" => self.dates.as_deref(),
+            "band_link" => self.band_link.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RenderedInquiry {
+    pub subject:   String,
+    pub html_body: String,
+    pub text_body: String,
+}
+
+impl InquiryTemplate {
+    /// Fills in the placeholders. Fails if the template uses one there's no
+    /// value for, rather than sending a venue a blank.
+    pub fn render(&self, values: &InquiryValues) -> Result<RenderedInquiry, String> {
+        let missing   = |name: String| format!("No value for {{{{{}}}}} in this inquiry", name);
+        let subject   = fill(&self.subject, |name| values.get(name).map(str::to_string))
+            .map_err(missing)?;
+        let text_body = fill(&self.body, |name| values.get(name).map(str::to_string))
+            .map_err(missing)?;
+        // The body is plain text, so all of it gets escaped, not just the values
+        let html_body = fill(&escape_html(&self.body), |name| values.get(name).map(escape_html))
+            .map_err(missing)?
+            .replace('\n', "<br />");
+
+        Ok(RenderedInquiry { subject, html_body, text_body })
+    }
+}
+
+/// Formats requested dates the way they read in an email
+pub fn format_requested_dates(
+    from: &Option<NaiveDate>,
+    to:   &Option<NaiveDate>
+) -> Result<Option<String>, String> {
+    let format = |date: &NaiveDate| date.format("%B %-d, %Y").to_string();
+
+    match (from, to) {
+        (Some(from), Some(to)) if from > to => {
+            Err("Requested dates cannot end before they start".to_string())
+        }
+        (Some(from), Some(to)) if from == to => Ok(Some(format(from))),
+        (Some(from), Some(to)) => Ok(Some(format!("{} to {}", format(from), format(to)))),
+        (Some(date), None) | (None, Some(date)) => Ok(Some(format(date))),
+        (None, None) => Ok(None),
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentInquiry {
+    pub inquiry_id:  Uuid,
+    pub contact_id:  i32,
+    pub template_id: Option<Uuid>,
+    pub outreach_id: Option<Uuid>,
+    pub recipient:   String,
+    pub subject:     String,
+    pub body:        String,
+    pub sent_at:     NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct NewSentInquiry<'a> {
+    pub contact_id:  i32,
+    pub template_id: Uuid,
+    pub outreach_id: Uuid,
+    pub recipient:   &'a str,
+    pub subject:     &'a str,
+    pub body:        &'a str,
+}
+
+/// Locks the user's row first, so sends racing each other count one at a
+/// time and can't all slip under the daily limit. The lock is held until
+/// the transaction ends.
+#[tracing::instrument(
+    name = "Counting inquiries sent in the last day",
+    skip(transaction)
+)]
+pub async fn count_recent_inquiries(
+    user_id:     &Uuid,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<i64, sqlx::Error> {
+    sqlx::query!(
+        r#"SELECT user_id FROM users WHERE user_id = $1 FOR UPDATE"#,
+        user_id
+    ).fetch_one(&mut *transaction)
+    .await?;
+
+    let row = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM sent_inquiries
+        WHERE user_id = $1 AND sent_at > current_timestamp - INTERVAL '1 day'
+        "#,
+        user_id
+    ).fetch_one(transaction)
+    .await?;
+
+    Ok(row.count)
+}
+
+#[tracing::instrument(
+    name = "Deleting inquiry template",
+    skip(pool)
+)]
+pub async fn delete_inquiry_template(
+    template_id: &Uuid,
+    user_id:     &Uuid,
+    pool:        &PgPool
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM inquiry_templates WHERE template_id = $1 AND user_id = $2
+        "#,
+        template_id,
+        user_id
+    ).execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Updating inquiry template",
+    skip(template, pool)
+)]
+pub async fn edit_inquiry_template(
+    template_id: &Uuid,
+    template:    &NewInquiryTemplate,
+    user_id:     &Uuid,
+    pool:        &PgPool
+) -> Result<Option<InquiryTemplate>, sqlx::Error> {
+    sqlx::query_as!(
+        InquiryTemplate,
+        r#"
+        UPDATE inquiry_templates
+        SET template_name = $3, subject = $4, body = $5, updated_at = current_timestamp
+        WHERE template_id = $1 AND user_id = $2
+        RETURNING template_id, user_id, template_name, subject, body
+        "#,
+        template_id,
+        user_id,
+        template.template_name,
+        template.subject,
+        template.body
+    ).fetch_optional(pool)
+    .await
+}
+
+#[tracing::instrument(
+    name = "Saving inquiry template",
+    skip(template, pool)
+)]
+pub async fn insert_inquiry_template(
+    template: &NewInquiryTemplate,
+    user_id:  &Uuid,
+    pool:     &PgPool
+) -> Result<InquiryTemplate, sqlx::Error> {
+    sqlx::query_as!(
+        InquiryTemplate,
+        r#"
+        INSERT INTO inquiry_templates (user_id, template_name, subject, body)
+        VALUES ($1, $2, $3, $4)
+        RETURNING template_id, user_id, template_name, subject, body
+        "#,
+        user_id,
+        template.template_name,
+        template.subject,
+        template.body
+    ).fetch_one(pool)
+    .await
+}
+
+#[tracing::instrument(
+    name = "Logging sent inquiry",
+    skip(inquiry, transaction)
+)]
+pub async fn insert_sent_inquiry(
+    inquiry:     &NewSentInquiry<'_>,
+    user_id:     &Uuid,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<SentInquiry, sqlx::Error> {
+    sqlx::query_as!(
+        SentInquiry,
+        r#"
+        INSERT INTO sent_inquiries (user_id, contact_id, template_id, outreach_id,
+                                    recipient, subject, body)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING inquiry_id, contact_id, template_id, outreach_id,
+                  recipient, subject, body, sent_at
+        "#,
+        user_id,
+        inquiry.contact_id,
+        inquiry.template_id,
+        inquiry.outreach_id,
+        inquiry.recipient,
+        inquiry.subject,
+        inquiry.body
+    ).fetch_one(transaction)
+    .await
+}
+
+#[tracing::instrument(
+    name = "Querying inquiry template",
+    skip(pool)
+)]
+pub async fn query_inquiry_template(
+    template_id: &Uuid,
+    user_id:     &Uuid,
+    pool:        &PgPool
+) -> Result<Option<InquiryTemplate>, sqlx::Error> {
+    sqlx::query_as!(
+        InquiryTemplate,
+        r#"
+        SELECT template_id, user_id, template_name, subject, body
+        FROM inquiry_templates
+        WHERE template_id = $1 AND user_id = $2
+        "#,
+        template_id,
+        user_id
+    ).fetch_optional(pool)
+    .await
+}
+
+#[tracing::instrument(
+    name = "Querying user's inquiry templates",
+    skip(pool)
+)]
+pub async fn query_inquiry_templates(
+    user_id: &Uuid,
+    pool:    &PgPool
+) -> Result<Vec<InquiryTemplate>, sqlx::Error> {
+    sqlx::query_as!(
+        InquiryTemplate,
+        r#"
+        SELECT template_id, user_id, template_name, subject, body
+        FROM inquiry_templates
+        WHERE user_id = $1
+        ORDER BY template_name
+        "#,
+        user_id
+    ).fetch_all(pool)
+    .await
+}
+
+#[tracing::instrument(
+    name = "Querying user's sent inquiries",
+    skip(pool)
+)]
+pub async fn query_sent_inquiries(
+    user_id: &Uuid,
+    pool:    &PgPool
+) -> Result<Vec<SentInquiry>, sqlx::Error> {
+    sqlx::query_as!(
+        SentInquiry,
+        r#"
+        SELECT inquiry_id, contact_id, template_id, outreach_id,
+               recipient, subject, body, sent_at
+        FROM sent_inquiries
+        WHERE user_id = $1
+        ORDER BY sent_at DESC
+        "#,
+        user_id
+    ).fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use claims::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    use crate::domain::{
+        format_requested_dates,
+        InquiryTemplate,
+        InquiryTemplateData,
+        InquiryValues,
+        NewInquiryTemplate
+    };
+
+    fn template(subject: &str, body: &str) -> InquiryTemplate {
+        InquiryTemplate {
+            template_id:   Uuid::new_v4(),
+            user_id:       Uuid::new_v4(),
+            template_name: "default".to_string(),
+            subject:       subject.to_string(),
+            body:          body.to_string(),
+        }
+    }
+
+    fn values() -> InquiryValues {
+        InquiryValues {
+            venue_name: "The Mothlight".to_string(),
+            city:       "asheville".to_string(),
+            dates:      Some("June 1, 2024".to_string()),
+            band_link:  None,
+        }
+    }
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, d).unwrap()
+    }
+
+    #[test]
+    fn placeholders_are_filled_in() {
+        let template = template(
+            "Show at {{venue_name}}?",
+            "Hi {{ venue_name }},\nWe're in {{city}} on {{dates}}."
+        );
+        let rendered = template.render(&values()).unwrap();
+
+        assert_eq!("Show at The Mothlight?", rendered.subject);
+        assert_eq!("Hi The Mothlight,\nWe're in asheville on June 1, 2024.", rendered.text_body);
+        assert_eq!("Hi The Mothlight,<br />We&#39;re in asheville on June 1, 2024.", rendered.html_body);
+    }
+
+    #[test]
+    fn html_body_is_escaped() {
+        let mut values = values();
+        values.venue_name = "<b>Bar & Grill</b>".to_string();
+        let rendered = template("hi", "{{venue_name}} <3").render(&values).unwrap();
+
+        assert_eq!("&lt;b&gt;Bar &amp; Grill&lt;/b&gt; &lt;3", rendered.html_body);
+        assert_eq!("<b>Bar & Grill</b> <3", rendered.text_body);
+    }
+
+    #[test]
+    fn missing_values_fail_to_render() {
+        assert_err!(template("hi", "listen at {{band_link}}").render(&values()));
+    }
+
+    #[test]
+    fn unknown_placeholders_are_rejected() {
+        let data = |body: &str| InquiryTemplateData {
+            template_name: "default".to_string(),
+            subject:       "Booking inquiry".to_string(),
+            body:          body.to_string(),
+        };
+
+        assert_err!(NewInquiryTemplate::try_from(data("Hi {{booker_name}}")));
+        assert_ok!(NewInquiryTemplate::try_from(data("Hi {{venue_name}}")));
+    }
+
+    #[test]
+    fn requested_dates_are_formatted() {
+        assert_eq!(
+            Ok(Some("June 1, 2024 to June 3, 2024".to_string())),
+            format_requested_dates(&Some(date(1)), &Some(date(3)))
+        );
+        assert_eq!(
+            Ok(Some("June 1, 2024".to_string())),
+            format_requested_dates(&Some(date(1)), &Some(date(1)))
+        );
+        assert_eq!(Ok(None), format_requested_dates(&None, &None));
+        assert_err!(format_requested_dates(&Some(date(3)), &Some(date(1))));
+    }
+}
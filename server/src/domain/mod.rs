@@ -1,13 +1,45 @@
 pub mod contact;
+pub mod contact_geo;
+pub mod contact_import;
+pub mod contact_share;
+pub mod contact_search;
+pub mod email_outbox;
 pub mod genre;
+pub mod geocoding_job;
 pub mod input_validator;
+pub mod inquiry;
+pub mod organisation;
+pub mod outreach;
 pub mod review;
+pub mod route_plan;
+pub mod tour;
+pub mod tour_calendar;
+pub mod tour_legs;
 pub mod user;
 pub mod user_email;
 
 pub use contact::*;
+pub use contact_geo::*;
+pub use contact_import::*;
+pub use contact_share::*;
+pub use contact_search::*;
+pub use email_outbox::*;
 pub use genre::*;
+pub use geocoding_job::*;
 pub use input_validator::*;
+pub use inquiry::*;
+pub use organisation::*;
+pub use outreach::*;
 pub use review::*;
+pub use route_plan::*;
+pub use tour::*;
+pub use tour_calendar::*;
+pub use tour_legs::*;
 pub use user::*;
-pub use user_email::*;
\ No newline at end of file
+pub use user_email::*;
+
+/// Reads a text column into the enum it holds. Failing means the column's
+/// CHECK and the enum have drifted apart, so it's reported as a decode error.
+pub(crate) fn decode_enum<T: TryFrom<String, Error = String>>(value: String) -> Result<T, sqlx::Error> {
+    T::try_from(value).map_err(|e| sqlx::Error::Decode(e.into()))
+}
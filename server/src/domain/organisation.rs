@@ -0,0 +1,410 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::domain::{decode_enum, StringInput};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OrgRole {
+    Owner,
+    Booker,
+    Member,
+}
+
+impl OrgRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrgRole::Owner => "owner",
+            OrgRole::Booker => "booker",
+            OrgRole::Member => "member",
+        }
+    }
+
+    /// Owners and bookers can add and change the organisation's contacts,
+    /// tours and reviews. Members can only look at them.
+    pub fn can_edit(&self) -> bool {
+        matches!(self, Self::Owner | Self::Booker)
+    }
+
+    pub fn can_manage_members(&self) -> bool {
+        matches!(self, Self::Owner)
+    }
+}
+
+impl TryFrom<String> for OrgRole {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "owner" => Ok(Self::Owner),
+            "booker" => Ok(Self::Booker),
+            "member" => Ok(Self::Member),
+            other => Err(format!("{} is not a supported role", other))
+        }
+    }
+}
+
+/// The organisation a user is acting for, picked with `/user/organisations/switch`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActiveOrganisation {
+    pub org_id: Uuid,
+    pub role:   OrgRole,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewOrganisationData {
+    pub org_name: String,
+}
+
+#[derive(Debug)]
+pub struct NewOrganisation {
+    pub org_name: String,
+}
+
+impl TryFrom<NewOrganisationData> for NewOrganisation {
+    type Error = String;
+
+    fn try_from(value: NewOrganisationData) -> Result<Self, Self::Error> {
+        let org_name = StringInput::parse(value.org_name);
+
+        if org_name.trim().is_empty() {
+            return Err("Organisation name cannot be empty".to_string())
+        }
+
+        Ok(Self { org_name })
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteMemberData {
+    pub email: String,
+    pub role:  Option<OrgRole>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteParams {
+    pub token: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberData {
+    pub user_id: Uuid,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchOrganisationData {
+    /// `None` goes back to acting for yourself
+    pub org_id: Option<Uuid>,
+}
+
+/// An organisation as one of its members sees it
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Organisation {
+    pub org_id:   Uuid,
+    pub org_name: String,
+    pub role:     OrgRole,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrgMember {
+    pub user_id: Uuid,
+    pub email:   String,
+    pub role:    OrgRole,
+}
+
+#[derive(Debug)]
+pub struct OrgInvite {
+    pub org_id:     Uuid,
+    pub role:       OrgRole,
+    pub created_at: NaiveDateTime,
+}
+
+#[tracing::instrument(
+    name = "Removing invite",
+    skip(invite_token, transaction)
+)]
+pub async fn delete_invite(
+    invite_token: &str,
+    transaction:  &mut Transaction<'_, Postgres>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM organisation_invites WHERE invite_token = $1
+        "#,
+        invite_token
+    ).execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Removing organisation member",
+    skip(pool)
+)]
+pub async fn delete_member(
+    org_id:  &Uuid,
+    user_id: &Uuid,
+    pool:    &PgPool
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM organisation_members WHERE org_id = $1 AND user_id = $2
+        "#,
+        org_id,
+        user_id
+    ).execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Saving invite to organisation",
+    skip(invite_token, email, transaction)
+)]
+pub async fn insert_invite(
+    org_id:       &Uuid,
+    email:        &str,
+    role:         OrgRole,
+    invited_by:   &Uuid,
+    invite_token: &str,
+    transaction:  &mut Transaction<'_, Postgres>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO organisation_invites (invite_token, org_id, email, role, invited_by)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        invite_token,
+        org_id,
+        email,
+        role.as_str(),
+        invited_by
+    ).execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Saving new organisation to database",
+    skip(org, transaction)
+)]
+pub async fn insert_organisation(
+    org:         &NewOrganisation,
+    user_id:     &Uuid,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<Organisation, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO organisations (org_name, created_by)
+        VALUES ($1, $2)
+        RETURNING org_id, org_name
+        "#,
+        org.org_name,
+        user_id
+    ).fetch_one(&mut *transaction)
+    .await?;
+
+    upsert_member(&row.org_id, user_id, OrgRole::Owner, transaction).await?;
+
+    Ok(Organisation {
+        org_id:   row.org_id,
+        org_name: row.org_name,
+        role:     OrgRole::Owner,
+    })
+}
+
+/// Finds an invite addressed to the user's email
+#[tracing::instrument(
+    name = "Querying invite for user",
+    skip(invite_token, pool)
+)]
+pub async fn query_invite_for_user(
+    invite_token: &str,
+    user_id:      &Uuid,
+    pool:         &PgPool
+) -> Result<Option<OrgInvite>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT i.org_id, i.role, i.created_at
+        FROM organisation_invites i
+        JOIN users u ON lower(u.email) = lower(i.email)
+        WHERE i.invite_token = $1 AND u.user_id = $2
+        "#,
+        invite_token,
+        user_id
+    ).fetch_optional(pool)
+    .await?;
+
+    row.map(|row| Ok(OrgInvite {
+        org_id:     row.org_id,
+        role:       decode_enum(row.role)?,
+        created_at: row.created_at,
+    }))
+    .transpose()
+}
+
+#[tracing::instrument(
+    name = "Querying organisation members",
+    skip(pool)
+)]
+pub async fn query_members(
+    org_id: &Uuid,
+    pool:   &PgPool
+) -> Result<Vec<OrgMember>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT m.user_id, u.email, m.role
+        FROM organisation_members m
+        JOIN users u ON u.user_id = m.user_id
+        WHERE m.org_id = $1
+        ORDER BY u.email
+        "#,
+        org_id
+    ).fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| Ok(OrgMember {
+            user_id: row.user_id,
+            email:   row.email,
+            role:    decode_enum(row.role)?,
+        }))
+        .collect()
+}
+
+#[tracing::instrument(
+    name = "Querying user's role in organisation",
+    skip(pool)
+)]
+pub async fn query_membership(
+    org_id:  &Uuid,
+    user_id: &Uuid,
+    pool:    &PgPool
+) -> Result<Option<OrgRole>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT role FROM organisation_members WHERE org_id = $1 AND user_id = $2
+        "#,
+        org_id,
+        user_id
+    ).fetch_optional(pool)
+    .await?;
+
+    row.map(|row| decode_enum(row.role)).transpose()
+}
+
+#[tracing::instrument(
+    name = "Querying organisation name",
+    skip(pool)
+)]
+pub async fn query_organisation_name(
+    org_id: &Uuid,
+    pool:   &PgPool
+) -> Result<Option<String>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT org_name FROM organisations WHERE org_id = $1
+        "#,
+        org_id
+    ).fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| row.org_name))
+}
+
+#[tracing::instrument(
+    name = "Querying user's organisations",
+    skip(pool)
+)]
+pub async fn query_user_organisations(
+    user_id: &Uuid,
+    pool:    &PgPool
+) -> Result<Vec<Organisation>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT o.org_id, o.org_name, m.role
+        FROM organisations o
+        JOIN organisation_members m ON m.org_id = o.org_id
+        WHERE m.user_id = $1
+        ORDER BY o.org_name
+        "#,
+        user_id
+    ).fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| Ok(Organisation {
+            org_id:   row.org_id,
+            org_name: row.org_name,
+            role:     decode_enum(row.role)?,
+        }))
+        .collect()
+}
+
+#[tracing::instrument(
+    name = "Adding member to organisation",
+    skip(transaction)
+)]
+pub async fn upsert_member(
+    org_id:      &Uuid,
+    user_id:     &Uuid,
+    role:        OrgRole,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO organisation_members (org_id, user_id, role)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (org_id, user_id)
+        DO UPDATE SET role = EXCLUDED.role, updated_at = current_timestamp
+        "#,
+        org_id,
+        user_id,
+        role.as_str()
+    ).execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::assert_err;
+
+    use crate::domain::{NewOrganisation, NewOrganisationData, OrgRole};
+
+    #[test]
+    fn role_round_trips_through_strings() {
+        for role in [OrgRole::Owner, OrgRole::Booker, OrgRole::Member] {
+            assert_eq!(OrgRole::try_from(role.as_str().to_string()), Ok(role));
+        }
+        assert_err!(OrgRole::try_from("drummer".to_string()));
+    }
+
+    #[test]
+    fn only_owners_manage_members_and_members_only_read() {
+        assert!(OrgRole::Owner.can_manage_members());
+        assert!(!OrgRole::Booker.can_manage_members());
+        assert!(OrgRole::Booker.can_edit());
+        assert!(!OrgRole::Member.can_edit());
+    }
+
+    #[test]
+    fn empty_organisation_name_is_rejected() {
+        let data = NewOrganisationData { org_name: "   ".to_string() };
+
+        assert_err!(NewOrganisation::try_from(data));
+    }
+}
@@ -0,0 +1,481 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::domain::{decode_enum, StringInput};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutreachChannel {
+    Email,
+    Form,
+    Phone,
+    Social,
+}
+
+impl OutreachChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutreachChannel::Email => "email",
+            OutreachChannel::Form => "form",
+            OutreachChannel::Phone => "phone",
+            OutreachChannel::Social => "social",
+        }
+    }
+}
+
+impl TryFrom<String> for OutreachChannel {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "email" => Ok(Self::Email),
+            "form" => Ok(Self::Form),
+            "phone" => Ok(Self::Phone),
+            "social" => Ok(Self::Social),
+            other => Err(format!("{} is not a supported channel", other))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutreachOutcome {
+    NoReply,
+    Declined,
+    Hold,
+    Offer,
+}
+
+impl OutreachOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutreachOutcome::NoReply => "no_reply",
+            OutreachOutcome::Declined => "declined",
+            OutreachOutcome::Hold => "hold",
+            OutreachOutcome::Offer => "offer",
+        }
+    }
+}
+
+impl TryFrom<String> for OutreachOutcome {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "no_reply" => Ok(Self::NoReply),
+            "declined" => Ok(Self::Declined),
+            "hold" => Ok(Self::Hold),
+            "offer" => Ok(Self::Offer),
+            other => Err(format!("{} is not a supported outcome", other))
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewOutreachData {
+    pub contact_id:     i32,
+    pub outreach_date:  NaiveDate,
+    pub channel:        OutreachChannel,
+    pub requested_from: Option<NaiveDate>,
+    pub requested_to:   Option<NaiveDate>,
+    pub outcome:        Option<OutreachOutcome>,
+    pub notes:          Option<String>,
+}
+
+#[derive(Debug)]
+pub struct NewOutreach {
+    pub contact_id:     i32,
+    pub outreach_date:  NaiveDate,
+    pub channel:        OutreachChannel,
+    pub requested_from: Option<NaiveDate>,
+    pub requested_to:   Option<NaiveDate>,
+    pub outcome:        OutreachOutcome,
+    pub notes:          Option<String>,
+}
+
+impl TryFrom<NewOutreachData> for NewOutreach {
+    type Error = String;
+
+    fn try_from(value: NewOutreachData) -> Result<Self, Self::Error> {
+        validate_requested_dates(&value.requested_from, &value.requested_to)?;
+
+        Ok(Self {
+            contact_id:     value.contact_id,
+            outreach_date:  value.outreach_date,
+            channel:        value.channel,
+            requested_from: value.requested_from,
+            requested_to:   value.requested_to,
+            outcome:        value.outcome.unwrap_or(OutreachOutcome::NoReply),
+            notes:          value.notes.map(StringInput::parse),
+        })
+    }
+}
+
+/// Replaces everything but the contact, so a reply can be recorded against
+/// the inquiry it answers
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditOutreachData {
+    pub outreach_id:    Uuid,
+    pub outreach_date:  NaiveDate,
+    pub channel:        OutreachChannel,
+    pub requested_from: Option<NaiveDate>,
+    pub requested_to:   Option<NaiveDate>,
+    pub outcome:        OutreachOutcome,
+    pub notes:          Option<String>,
+}
+
+impl TryFrom<EditOutreachData> for EditOutreach {
+    type Error = String;
+
+    fn try_from(value: EditOutreachData) -> Result<Self, Self::Error> {
+        validate_requested_dates(&value.requested_from, &value.requested_to)?;
+
+        Ok(Self {
+            outreach_id:    value.outreach_id,
+            outreach_date:  value.outreach_date,
+            channel:        value.channel,
+            requested_from: value.requested_from,
+            requested_to:   value.requested_to,
+            outcome:        value.outcome,
+            notes:          value.notes.map(StringInput::parse),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct EditOutreach {
+    pub outreach_id:    Uuid,
+    pub outreach_date:  NaiveDate,
+    pub channel:        OutreachChannel,
+    pub requested_from: Option<NaiveDate>,
+    pub requested_to:   Option<NaiveDate>,
+    pub outcome:        OutreachOutcome,
+    pub notes:          Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutreachDeleteData {
+    pub outreach_id: Uuid,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutreachParams {
+    pub contact_id: i32,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowUpParams {
+    /// Days without a reply before an inquiry is due a follow-up
+    pub days: Option<i32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Outreach {
+    pub outreach_id:    Uuid,
+    pub contact_id:     i32,
+    pub user_id:        Uuid,
+    pub outreach_date:  NaiveDate,
+    pub channel:        OutreachChannel,
+    pub requested_from: Option<NaiveDate>,
+    pub requested_to:   Option<NaiveDate>,
+    pub outcome:        OutreachOutcome,
+    pub notes:          Option<String>,
+}
+
+/// An unanswered inquiry, with enough of the contact to chase it up
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowUp {
+    #[serde(flatten)]
+    pub outreach:     Outreach,
+    pub display_name: String,
+    pub email:        Option<String>,
+    pub contact_form: Option<String>,
+    pub days_waiting: i32,
+}
+
+struct OutreachRow {
+    outreach_id:    Uuid,
+    contact_id:     i32,
+    user_id:        Uuid,
+    outreach_date:  NaiveDate,
+    channel:        String,
+    requested_from: Option<NaiveDate>,
+    requested_to:   Option<NaiveDate>,
+    outcome:        String,
+    notes:          Option<String>,
+}
+
+impl TryFrom<OutreachRow> for Outreach {
+    type Error = sqlx::Error;
+
+    fn try_from(row: OutreachRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            outreach_id:    row.outreach_id,
+            contact_id:     row.contact_id,
+            user_id:        row.user_id,
+            outreach_date:  row.outreach_date,
+            channel:        decode_enum(row.channel)?,
+            requested_from: row.requested_from,
+            requested_to:   row.requested_to,
+            outcome:        decode_enum(row.outcome)?,
+            notes:          row.notes,
+        })
+    }
+}
+
+fn validate_requested_dates(
+    from: &Option<NaiveDate>,
+    to:   &Option<NaiveDate>
+) -> Result<(), String> {
+    match (from, to) {
+        (Some(from), Some(to)) if from > to => {
+            Err("Requested dates cannot end before they start".to_string())
+        }
+        _ => Ok(())
+    }
+}
+
+/// Returns false if the user has no outreach with that id
+#[tracing::instrument(
+    name = "Deleting outreach entry",
+    skip(pool)
+)]
+pub async fn delete_outreach(
+    outreach_id: &Uuid,
+    user_id:     &Uuid,
+    pool:        &PgPool
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM outreach WHERE outreach_id = $1 AND user_id = $2
+        "#,
+        outreach_id,
+        user_id
+    ).execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[tracing::instrument(
+    name = "Updating outreach entry",
+    skip(outreach, pool)
+)]
+pub async fn edit_outreach(
+    outreach: &EditOutreach,
+    user_id:  &Uuid,
+    pool:     &PgPool
+) -> Result<Option<Outreach>, sqlx::Error> {
+    let row = sqlx::query_as!(
+        OutreachRow,
+        r#"
+        UPDATE outreach
+        SET outreach_date = $3, channel = $4, requested_from = $5, requested_to = $6,
+            outcome = $7, notes = $8, updated_at = current_timestamp
+        WHERE outreach_id = $1 AND user_id = $2
+        RETURNING outreach_id, contact_id, user_id, outreach_date, channel,
+                  requested_from, requested_to, outcome, notes
+        "#,
+        outreach.outreach_id,
+        user_id,
+        outreach.outreach_date,
+        outreach.channel.as_str(),
+        outreach.requested_from,
+        outreach.requested_to,
+        outreach.outcome.as_str(),
+        outreach.notes
+    ).fetch_optional(pool)
+    .await?;
+
+    row.map(Outreach::try_from).transpose()
+}
+
+#[tracing::instrument(
+    name = "Saving outreach entry",
+    skip(outreach, transaction)
+)]
+pub async fn insert_outreach(
+    outreach:    &NewOutreach,
+    user_id:     &Uuid,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<Outreach, sqlx::Error> {
+    let row = sqlx::query_as!(
+        OutreachRow,
+        r#"
+        INSERT INTO outreach (contact_id, user_id, outreach_date, channel,
+                              requested_from, requested_to, outcome, notes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING outreach_id, contact_id, user_id, outreach_date, channel,
+                  requested_from, requested_to, outcome, notes
+        "#,
+        outreach.contact_id,
+        user_id,
+        outreach.outreach_date,
+        outreach.channel.as_str(),
+        outreach.requested_from,
+        outreach.requested_to,
+        outreach.outcome.as_str(),
+        outreach.notes
+    ).fetch_one(transaction)
+    .await?;
+
+    row.try_into()
+}
+
+/// Contacts whose most recent inquiry from the user has gone unanswered for
+/// at least `days`. Logging a new inquiry resets the clock.
+#[tracing::instrument(
+    name = "Querying outreach due a follow-up",
+    skip(pool)
+)]
+pub async fn query_follow_ups_due(
+    user_id: &Uuid,
+    days:    i32,
+    pool:    &PgPool
+) -> Result<Vec<FollowUp>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT o.outreach_id, o.contact_id, o.user_id, o.outreach_date, o.channel,
+               o.requested_from, o.requested_to, o.outcome, o.notes,
+               c.display_name, c.email, c.contact_form,
+               (current_date - o.outreach_date) AS "days_waiting!"
+        FROM (
+            SELECT DISTINCT ON (contact_id) *
+            FROM outreach
+            WHERE user_id = $1
+            ORDER BY contact_id, outreach_date DESC, created_at DESC
+        ) o
+        JOIN contacts c ON c.contact_id = o.contact_id
+        WHERE o.outcome = 'no_reply'
+        AND o.outreach_date <= current_date - $2::int
+        ORDER BY o.outreach_date, c.display_name
+        "#,
+        user_id,
+        days
+    ).fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let outreach = OutreachRow {
+                outreach_id:    row.outreach_id,
+                contact_id:     row.contact_id,
+                user_id:        row.user_id,
+                outreach_date:  row.outreach_date,
+                channel:        row.channel,
+                requested_from: row.requested_from,
+                requested_to:   row.requested_to,
+                outcome:        row.outcome,
+                notes:          row.notes,
+            };
+
+            Ok(FollowUp {
+                outreach:     outreach.try_into()?,
+                display_name: row.display_name,
+                email:        row.email,
+                contact_form: row.contact_form,
+                days_waiting: row.days_waiting,
+            })
+        })
+        .collect()
+}
+
+#[tracing::instrument(
+    name = "Querying outreach for contact",
+    skip(pool)
+)]
+pub async fn query_outreach_for_contact(
+    contact_id: &i32,
+    user_id:    &Uuid,
+    pool:       &PgPool
+) -> Result<Vec<Outreach>, sqlx::Error> {
+    let rows = sqlx::query_as!(
+        OutreachRow,
+        r#"
+        SELECT outreach_id, contact_id, user_id, outreach_date, channel,
+               requested_from, requested_to, outcome, notes
+        FROM outreach
+        WHERE contact_id = $1 AND user_id = $2
+        ORDER BY outreach_date DESC, created_at DESC
+        "#,
+        contact_id,
+        user_id
+    ).fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(Outreach::try_from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use claims::{assert_err, assert_ok};
+
+    use crate::domain::{
+        NewOutreach,
+        NewOutreachData,
+        OutreachChannel,
+        OutreachOutcome
+    };
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, d).unwrap()
+    }
+
+    fn data(from: Option<NaiveDate>, to: Option<NaiveDate>) -> NewOutreachData {
+        NewOutreachData {
+            contact_id:     1,
+            outreach_date:  date(1),
+            channel:        OutreachChannel::Email,
+            requested_from: from,
+            requested_to:   to,
+            outcome:        None,
+            notes:          None,
+        }
+    }
+
+    #[test]
+    fn channel_and_outcome_round_trip_through_strings() {
+        for channel in [
+            OutreachChannel::Email,
+            OutreachChannel::Form,
+            OutreachChannel::Phone,
+            OutreachChannel::Social
+        ] {
+            assert_eq!(OutreachChannel::try_from(channel.as_str().to_string()), Ok(channel));
+        }
+
+        for outcome in [
+            OutreachOutcome::NoReply,
+            OutreachOutcome::Declined,
+            OutreachOutcome::Hold,
+            OutreachOutcome::Offer
+        ] {
+            assert_eq!(OutreachOutcome::try_from(outcome.as_str().to_string()), Ok(outcome));
+        }
+
+        assert_err!(OutreachChannel::try_from("carrier pigeon".to_string()));
+    }
+
+    #[test]
+    fn requested_dates_cannot_end_before_they_start() {
+        assert_err!(NewOutreach::try_from(data(Some(date(10)), Some(date(1)))));
+        assert_ok!(NewOutreach::try_from(data(Some(date(1)), None)));
+    }
+
+    #[test]
+    fn new_outreach_defaults_to_no_reply() {
+        let outreach = NewOutreach::try_from(data(None, None)).unwrap();
+
+        assert_eq!(OutreachOutcome::NoReply, outreach.outcome);
+    }
+}
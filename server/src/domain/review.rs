@@ -43,6 +43,7 @@ pub struct Review {
     pub review_id:  Uuid,
     pub contact_id: i32,
     pub user_id:    Uuid,
+    pub org_id:     Option<Uuid>,
     pub title:      String,
     pub body:       String,
     pub rating:     i32
@@ -87,6 +88,7 @@ impl TryFrom<ReviewEditData> for Review {
             contact_id: value.contact_id,
             rating: value.rating,
             user_id: value.user_id,
+            org_id: None,
             review_id: value.review_id
         })
     }
@@ -138,7 +140,7 @@ pub async fn edit_review(
         UPDATE reviews
         SET title = $1, body = $2, rating = $3
         WHERE review_id = $4
-        RETURNING review_id, contact_id, user_id, title, body, rating
+        RETURNING review_id, contact_id, user_id, org_id, title, body, rating
         "#,
         review.title,
         review.body,
@@ -157,16 +159,18 @@ pub async fn edit_review(
 pub async fn insert_review(
     review:      NewReview,
     user_id:     &Uuid,
+    org_id:      Option<Uuid>,
     transaction: &mut Transaction<'_, Postgres>,
 ) -> Result<Review, sqlx::Error> {
     let review = sqlx::query_as!(
         Review,
         r#"
-        INSERT INTO reviews (user_id, contact_id, title, body, rating)
-        VALUES ($1, $2, $3, $4, $5)
-        RETURNING review_id, contact_id, user_id, title, body, rating
+        INSERT INTO reviews (user_id, org_id, contact_id, title, body, rating)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING review_id, contact_id, user_id, org_id, title, body, rating
         "#,
         user_id,
+        org_id,
         review.contact_id,
         review.title,
         review.body,
@@ -178,6 +182,27 @@ pub async fn insert_review(
     Ok(review)
 }
 
+#[tracing::instrument(
+    skip(pool, review_id)
+)]
+pub async fn query_review_by_id(
+    review_id: &Uuid,
+    pool:      &PgPool
+) -> Result<Option<Review>, sqlx::Error> {
+    let review = sqlx::query_as!(
+        Review,
+        r#"
+        SELECT review_id, contact_id, user_id, org_id, title, body, rating
+        FROM reviews
+        WHERE review_id = $1
+        "#,
+        review_id
+    ).fetch_optional(pool)
+    .await?;
+
+    Ok(review)
+}
+
 #[tracing::instrument(
     skip(pool)
 )]
@@ -207,8 +232,10 @@ pub async fn query_recent_reviews(
 )]
 pub async fn query_reviews_by_user(
     user_id: &Uuid,
+    org_id:  Option<Uuid>,
     pool:    &PgPool
 ) -> Result<Vec<RenderedReview>, sqlx::Error> {
+    // Includes the organisation's reviews when the user is acting for one
     let reviews = sqlx::query_as!(
         RenderedReview,
         r#"
@@ -216,9 +243,10 @@ pub async fn query_reviews_by_user(
         FROM reviews r
         JOIN users u ON r.user_id = u.user_id
         JOIN contacts c ON r.contact_id = c.contact_id
-        WHERE r.user_id = $1
+        WHERE r.user_id = $1 OR r.org_id = $2
         "#,
-        user_id
+        user_id,
+        org_id
     ).fetch_all(pool)
     .await?;
 
@@ -0,0 +1,628 @@
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use std::cmp::Ordering;
+use uuid::Uuid;
+
+use crate::domain::{ContactResponse, StringInput, Tour};
+use crate::error::RoutingError;
+use crate::gmaps_api_client::Location;
+use crate::routing::RouteLeg;
+
+const DEFAULT_MAX_DRIVE_HOURS: f64 = 8.0;
+const MAX_CANDIDATES: usize        = 200;
+const MAX_DATES: usize             = 60;
+const MAX_PASSES: usize            = 50;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePlanData {
+    pub start_city:      String,
+    pub end_city:        String,
+    pub dates:           Vec<NaiveDate>,
+    pub candidate_ids:   Vec<i32>,
+    /// Contacts must book at least one of these
+    pub genres:          Option<Vec<i32>>,
+    pub min_capacity:    Option<i32>,
+    pub age_ranges:      Option<Vec<String>>,
+    pub max_drive_hours: Option<f64>,
+}
+
+#[derive(Debug)]
+pub struct RoutePlanRequest {
+    pub start_city:      String,
+    pub end_city:        String,
+    pub dates:           Vec<NaiveDate>,
+    pub candidate_ids:   Vec<i32>,
+    pub genres:          Option<Vec<i32>>,
+    pub min_capacity:    Option<i32>,
+    pub age_ranges:      Option<Vec<String>>,
+    pub max_drive_hours: f64,
+}
+
+impl TryFrom<RoutePlanData> for RoutePlanRequest {
+    type Error = String;
+
+    fn try_from(value: RoutePlanData) -> Result<Self, Self::Error> {
+        let start_city = StringInput::parse(value.start_city);
+        let end_city   = StringInput::parse(value.end_city);
+
+        if start_city.trim().is_empty() || end_city.trim().is_empty() {
+            return Err("Start and end cities are required".to_string())
+        }
+
+        let mut dates = value.dates;
+        dates.sort();
+        dates.dedup();
+
+        if dates.is_empty() || dates.len() > MAX_DATES {
+            return Err(format!("Between 1 and {} dates are required", MAX_DATES))
+        }
+
+        let mut candidate_ids = value.candidate_ids;
+        candidate_ids.sort();
+        candidate_ids.dedup();
+
+        if candidate_ids.is_empty() || candidate_ids.len() > MAX_CANDIDATES {
+            return Err(format!("Between 1 and {} candidates are required", MAX_CANDIDATES))
+        }
+
+        let max_drive_hours = match value.max_drive_hours {
+            None => DEFAULT_MAX_DRIVE_HOURS,
+            Some(hours) if hours > 0.0 && hours <= 24.0 => hours,
+            Some(_) => return Err("maxDriveHours must be between 0 and 24".to_string()),
+        };
+
+        Ok(Self {
+            start_city,
+            end_city,
+            dates,
+            candidate_ids,
+            genres:          value.genres.filter(|g| !g.is_empty()),
+            min_capacity:    value.min_capacity,
+            age_ranges:      value.age_ranges.filter(|a| !a.is_empty()),
+            max_drive_hours,
+        })
+    }
+}
+
+impl RoutePlanRequest {
+    /// Whether a contact can be placed on the route at all. Contacts without
+    /// coordinates can't be routed, and an unknown capacity doesn't meet a
+    /// minimum.
+    pub fn accepts(&self, contact: &ContactResponse) -> bool {
+        let located = contact.latitude.is_some() && contact.longitude.is_some();
+
+        let genres = match &self.genres {
+            Some(genres) => contact.genres.iter().any(|g| genres.contains(&g.genre_id)),
+            None => true,
+        };
+
+        let capacity = match self.min_capacity {
+            Some(min) => contact.capacity.map_or(false, |c| c >= min),
+            None => true,
+        };
+
+        let age_range = match &self.age_ranges {
+            Some(age_ranges) => age_ranges.contains(&contact.age_range),
+            None => true,
+        };
+
+        located && genres && capacity && age_range
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedStop {
+    pub show_date:     NaiveDate,
+    pub contact:       ContactResponse,
+    /// From the previous stop, or the start city for the first one
+    pub distance_km:   f64,
+    pub drive_minutes: f64,
+    pub estimated:     bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePlan {
+    pub start:               Location,
+    pub end:                 Location,
+    pub stops:               Vec<PlannedStop>,
+    /// Dates no candidate could fill within the drive limit
+    pub open_dates:          Vec<NaiveDate>,
+    pub final_leg:           RouteLeg,
+    pub total_distance_km:   f64,
+    pub total_drive_minutes: f64,
+    /// Some of the legs are straight-line guesses
+    pub estimated:           bool,
+    /// Candidates left out for not meeting the constraints
+    pub rejected_ids:        Vec<i32>,
+}
+
+impl RoutePlan {
+    /// `matrix` covers the start, then `candidates` in order, then the end.
+    /// Fails if the end can't be reached by road, which would otherwise
+    /// leave the totals infinite.
+    pub fn new(
+        request:      &RoutePlanRequest,
+        start:        Location,
+        end:          Location,
+        candidates:   Vec<ContactResponse>,
+        rejected_ids: Vec<i32>,
+        matrix:       &[Vec<RouteLeg>]
+    ) -> Result<Self, RoutingError> {
+        let end_index = candidates.len() + 1;
+        let slots     = optimise_route(&request.dates, matrix, request.max_drive_hours * 60.0);
+
+        let mut stops       = Vec::new();
+        let mut open_dates  = Vec::new();
+        let mut previous    = 0;
+        let mut unreachable = false;
+
+        for (show_date, slot) in request.dates.iter().zip(slots) {
+            match slot {
+                Some(index) => {
+                    let leg = matrix[previous][index];
+                    unreachable |= !leg.is_reachable();
+                    stops.push(PlannedStop {
+                        show_date:     *show_date,
+                        contact:       candidates[index - 1].clone(),
+                        distance_km:   leg.distance_km,
+                        drive_minutes: leg.drive_minutes,
+                        estimated:     leg.estimated,
+                    });
+                    previous = index;
+                }
+                None => open_dates.push(*show_date),
+            }
+        }
+
+        let final_leg = matrix[previous][end_index];
+
+        if unreachable || !final_leg.is_reachable() {
+            return Err(RoutingError::NoRouteFound(
+                "The end city can't be reached by road from the start or any candidate".to_string()
+            ))
+        }
+
+        Ok(Self {
+            start,
+            end,
+            total_distance_km:   stops.iter().map(|s| s.distance_km).sum::<f64>() + final_leg.distance_km,
+            total_drive_minutes: stops.iter().map(|s| s.drive_minutes).sum::<f64>() + final_leg.drive_minutes,
+            estimated:           stops.iter().any(|s| s.estimated) || final_leg.estimated,
+            stops,
+            open_dates,
+            final_leg,
+            rejected_ids,
+        })
+    }
+}
+
+/// Picks a stop for each date, minimising total distance from the start
+/// (index 0) through the stops to the end (the last index) without any leg
+/// taking longer than the days between its two dates allow. The band is
+/// assumed to leave the day before the first date and arrive the day after
+/// the last. Filling dates matters more than distance, so a date is only left
+/// open when no remaining candidate fits.
+///
+/// Finding the true optimum is a travelling salesman problem, so this builds
+/// a greedy route and improves it by swapping stops until nothing helps.
+pub fn optimise_route(
+    dates:             &[NaiveDate],
+    matrix:            &[Vec<RouteLeg>],
+    max_drive_minutes: f64
+) -> Vec<Option<usize>> {
+    let planner = RoutePlanner { dates, matrix, max_drive_minutes };
+    let mut plan = planner.greedy();
+    let mut best = planner.score(&plan);
+
+    for _ in 0..MAX_PASSES {
+        let mut improved = false;
+
+        // Put a different candidate, or nobody, on each date
+        for i in 0..plan.len() {
+            for candidate in planner.candidates().map(Some).chain([None]) {
+                if candidate.is_some() && plan.contains(&candidate) {
+                    continue
+                }
+
+                let previous = std::mem::replace(&mut plan[i], candidate);
+                let score    = planner.score(&plan);
+
+                if score.better_than(&best) {
+                    best     = score;
+                    improved = true;
+                } else {
+                    plan[i] = previous;
+                }
+            }
+        }
+
+        // Reorder stops between dates
+        for i in 0..plan.len() {
+            for j in i + 1..plan.len() {
+                plan.swap(i, j);
+                let score = planner.score(&plan);
+
+                if score.better_than(&best) {
+                    best     = score;
+                    improved = true;
+                } else {
+                    plan.swap(i, j);
+                }
+            }
+        }
+
+        if !improved {
+            break
+        }
+    }
+
+    plan
+}
+
+struct RoutePlanner<'a> {
+    dates:             &'a [NaiveDate],
+    matrix:            &'a [Vec<RouteLeg>],
+    max_drive_minutes: f64,
+}
+
+/// Compared in field order, lower is better
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct RouteScore {
+    unreachable: usize,
+    too_long:    usize,
+    open_dates:  usize,
+    distance_km: f64,
+}
+
+impl RouteScore {
+    fn better_than(&self, other: &RouteScore) -> bool {
+        let key = |s: &RouteScore| (s.unreachable, s.too_long, s.open_dates);
+
+        match key(self).cmp(&key(other)) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            // Ignore floating point noise so swaps can't cycle forever
+            Ordering::Equal => other.distance_km - self.distance_km > 1e-6,
+        }
+    }
+}
+
+impl<'a> RoutePlanner<'a> {
+    fn end(&self) -> usize {
+        self.matrix.len() - 1
+    }
+
+    fn candidates(&self) -> std::ops::Range<usize> {
+        1..self.end()
+    }
+
+    fn fits(&self, from: usize, to: usize, days: i64) -> bool {
+        let allowed = days.max(1) as f64 * self.max_drive_minutes;
+        self.matrix[from][to].drive_minutes <= allowed
+    }
+
+    fn greedy(&self) -> Vec<Option<usize>> {
+        let mut plan     = vec![None; self.dates.len()];
+        let mut previous = (0, self.dates[0] - Duration::days(1));
+
+        for (i, date) in self.dates.iter().enumerate() {
+            let days = (*date - previous.1).num_days();
+            // Prefer stops that head towards the end rather than away from it
+            let next = self.candidates()
+                .filter(|c| !plan.contains(&Some(*c)))
+                .filter(|c| self.fits(previous.0, *c, days))
+                .min_by(|a, b| {
+                    let cost = |c: usize| self.matrix[previous.0][c].distance_km + self.matrix[c][self.end()].distance_km;
+                    cost(*a).partial_cmp(&cost(*b)).unwrap_or(Ordering::Equal)
+                });
+
+            if let Some(next) = next {
+                plan[i]  = Some(next);
+                previous = (next, *date);
+            }
+        }
+
+        plan
+    }
+
+    fn score(&self, plan: &[Option<usize>]) -> RouteScore {
+        let mut score    = RouteScore { unreachable: 0, too_long: 0, open_dates: 0, distance_km: 0.0 };
+        let mut previous = (0, self.dates[0] - Duration::days(1));
+
+        let stops = self.dates
+            .iter()
+            .zip(plan)
+            .filter_map(|(date, slot)| slot.map(|index| (index, *date)))
+            .chain([(self.end(), *self.dates.last().unwrap() + Duration::days(1))]);
+
+        for (index, date) in stops {
+            if !self.matrix[previous.0][index].is_reachable() {
+                score.unreachable += 1;
+            } else if !self.fits(previous.0, index, (date - previous.1).num_days()) {
+                score.too_long += 1;
+            }
+            score.distance_km += self.matrix[previous.0][index].distance_km;
+            previous = (index, date);
+        }
+
+        score.open_dates = plan.iter().filter(|slot| slot.is_none()).count();
+        score
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveRoutePlanData {
+    pub tour_name: String,
+    pub stops:     Vec<SavedStopData>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedStopData {
+    pub show_date:  NaiveDate,
+    pub contact_id: i32,
+}
+
+#[derive(Debug)]
+pub struct SavedRoutePlan {
+    pub tour_name:  String,
+    pub start_date: NaiveDate,
+    pub end_date:   NaiveDate,
+    pub stops:      Vec<(NaiveDate, i32)>,
+}
+
+impl TryFrom<SaveRoutePlanData> for SavedRoutePlan {
+    type Error = String;
+
+    fn try_from(value: SaveRoutePlanData) -> Result<Self, Self::Error> {
+        let tour_name = StringInput::parse(value.tour_name);
+
+        if tour_name.trim().is_empty() {
+            return Err("Tour name cannot be empty".to_string())
+        }
+
+        let mut stops: Vec<(NaiveDate, i32)> = value.stops
+            .into_iter()
+            .map(|s| (s.show_date, s.contact_id))
+            .collect();
+        stops.sort();
+        stops.dedup();
+
+        let (start_date, end_date) = match (stops.first(), stops.last()) {
+            (Some(first), Some(last)) => (first.0, last.0),
+            _ => return Err("A route needs at least one stop".to_string()),
+        };
+
+        Ok(Self {
+            tour_name,
+            start_date,
+            end_date,
+            stops,
+        })
+    }
+}
+
+impl SavedRoutePlan {
+    pub fn contact_ids(&self) -> Vec<i32> {
+        let mut ids: Vec<i32> = self.stops.iter().map(|s| s.1).collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+}
+
+#[tracing::instrument(
+    name = "Saving route plan as a tour",
+    skip(plan, user_id, transaction)
+)]
+pub async fn insert_route_plan(
+    plan:        &SavedRoutePlan,
+    user_id:     &Uuid,
+    org_id:      Option<Uuid>,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<Tour, sqlx::Error> {
+    let tour = sqlx::query_as!(
+        Tour,
+        r#"
+        INSERT INTO tours (tour_name, start_date, end_date, user_id, org_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING tour_id, user_id, org_id, tour_name, start_date, end_date
+        "#,
+        plan.tour_name,
+        plan.start_date,
+        plan.end_date,
+        user_id,
+        org_id
+    ).fetch_one(&mut *transaction)
+    .await?;
+
+    let mut dates: Vec<NaiveDate> = plan.stops.iter().map(|s| s.0).collect();
+    dates.dedup();
+
+    for show_date in dates {
+        let tour_date_id = sqlx::query!(
+            r#"
+            INSERT INTO tour_dates (tour_id, show_date)
+            VALUES ($1, $2)
+            RETURNING tour_date_id
+            "#,
+            tour.tour_id,
+            show_date
+        ).fetch_one(&mut *transaction)
+        .await?
+        .tour_date_id;
+
+        for (_, contact_id) in plan.stops.iter().filter(|s| s.0 == show_date) {
+            sqlx::query!(
+                r#"
+                INSERT INTO tour_dates_contacts (tour_date_id, contact_id)
+                VALUES ($1, $2)
+                "#,
+                tour_date_id,
+                contact_id
+            ).execute(&mut *transaction)
+            .await?;
+        }
+    }
+
+    Ok(tour)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use claims::{assert_err, assert_ok};
+
+    use crate::domain::{
+        optimise_route,
+        RoutePlan,
+        RoutePlanData,
+        RoutePlanRequest,
+        SaveRoutePlanData,
+        SavedRoutePlan,
+        SavedStopData
+    };
+    use crate::gmaps_api_client::Location;
+    use crate::routing::RouteLeg;
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, d).unwrap()
+    }
+
+    /// Stops on a line, an hour's drive per 100km
+    fn line_matrix(positions: &[f64]) -> Vec<Vec<RouteLeg>> {
+        positions
+            .iter()
+            .map(|a| positions.iter().map(|b| {
+                let distance_km = (a - b).abs();
+                RouteLeg { distance_km, drive_minutes: distance_km * 0.6, estimated: false }
+            }).collect())
+            .collect()
+    }
+
+    fn plan_data() -> RoutePlanData {
+        RoutePlanData {
+            start_city:      "asheville".to_string(),
+            end_city:        "raleigh".to_string(),
+            dates:           vec![date(3), date(1), date(3)],
+            candidate_ids:   vec![2, 1, 2],
+            genres:          Some(vec![]),
+            min_capacity:    None,
+            age_ranges:      None,
+            max_drive_hours: None,
+        }
+    }
+
+    #[test]
+    fn request_sorts_and_dedups_dates_and_candidates() {
+        let request = RoutePlanRequest::try_from(plan_data()).unwrap();
+
+        assert_eq!(vec![date(1), date(3)], request.dates);
+        assert_eq!(vec![1, 2], request.candidate_ids);
+        assert_eq!(None, request.genres);
+        assert_eq!(8.0, request.max_drive_hours);
+    }
+
+    #[test]
+    fn request_needs_dates_and_candidates() {
+        let mut data = plan_data();
+        data.dates   = vec![];
+        assert_err!(RoutePlanRequest::try_from(data));
+
+        let mut data      = plan_data();
+        data.candidate_ids = vec![];
+        assert_err!(RoutePlanRequest::try_from(data));
+
+        let mut data        = plan_data();
+        data.max_drive_hours = Some(0.0);
+        assert_err!(RoutePlanRequest::try_from(data));
+    }
+
+    #[test]
+    fn stops_are_ordered_from_start_to_end() {
+        // Start at 0, end at 1000, candidates scattered in between
+        let matrix = line_matrix(&[0.0, 700.0, 300.0, 500.0, 1000.0]);
+        let plan   = optimise_route(&[date(1), date(2), date(3)], &matrix, 480.0);
+
+        assert_eq!(vec![Some(2), Some(3), Some(1)], plan);
+    }
+
+    #[test]
+    fn best_candidates_are_picked_from_a_larger_pool() {
+        // The detour to 2000 is never worth it
+        let matrix = line_matrix(&[0.0, 2000.0, 400.0, 600.0, 1000.0]);
+        let plan   = optimise_route(&[date(1), date(2)], &matrix, 480.0);
+
+        assert_eq!(vec![Some(2), Some(3)], plan);
+    }
+
+    #[test]
+    fn dates_are_left_open_when_nothing_fits() {
+        // Only one candidate, and it's too far for a single day
+        let matrix = line_matrix(&[0.0, 1000.0, 1200.0]);
+        let plan   = optimise_route(&[date(1), date(5)], &matrix, 480.0);
+
+        assert_eq!(vec![None, Some(1)], plan);
+    }
+
+    #[test]
+    fn unreachable_candidates_are_not_picked() {
+        let mut matrix = line_matrix(&[0.0, 100.0, 300.0, 1000.0]);
+        for i in 0..matrix.len() {
+            if i != 1 {
+                matrix[i][1] = RouteLeg::UNREACHABLE;
+                matrix[1][i] = RouteLeg::UNREACHABLE;
+            }
+        }
+        let plan = optimise_route(&[date(1), date(2)], &matrix, 480.0);
+
+        assert!(!plan.contains(&Some(1)));
+        assert!(plan.contains(&Some(2)));
+    }
+
+    #[test]
+    fn plan_fails_when_the_end_is_unreachable() {
+        let request    = RoutePlanRequest::try_from(plan_data()).unwrap();
+        let location   = Location { lat: 35.6, lng: -82.5 };
+        let mut matrix = line_matrix(&[0.0, 500.0]);
+        matrix[0][1] = RouteLeg::UNREACHABLE;
+
+        assert!(RoutePlan::new(&request, location, location, vec![], vec![], &matrix).is_err());
+
+        let matrix = line_matrix(&[0.0, 500.0]);
+        let plan   = RoutePlan::new(&request, location, location, vec![], vec![], &matrix).unwrap();
+        assert_eq!(500.0, plan.total_distance_km);
+    }
+
+    #[test]
+    fn saved_plan_spans_its_stops() {
+        let data = SaveRoutePlanData {
+            tour_name: "planned".to_string(),
+            stops:     vec![
+                SavedStopData { show_date: date(9), contact_id: 1 },
+                SavedStopData { show_date: date(2), contact_id: 2 },
+            ],
+        };
+        let plan = SavedRoutePlan::try_from(data).unwrap();
+
+        assert_eq!(date(2), plan.start_date);
+        assert_eq!(date(9), plan.end_date);
+    }
+
+    #[test]
+    fn saved_plan_needs_stops() {
+        let data = SaveRoutePlanData { tour_name: "planned".to_string(), stops: vec![] };
+        assert_err!(SavedRoutePlan::try_from(data));
+
+        let data = SaveRoutePlanData {
+            tour_name: "planned".to_string(),
+            stops:     vec![SavedStopData { show_date: date(2), contact_id: 2 }],
+        };
+        assert_ok!(SavedRoutePlan::try_from(data));
+    }
+}
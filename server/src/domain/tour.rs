@@ -0,0 +1,672 @@
+use chrono::NaiveDate;
+use futures_util::stream::{BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::domain::{decode_enum, query_contacts_by_ids, ContactResponse, StringInput};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum BookingStatus {
+    #[serde(rename = "contacted")]
+    Contacted,
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "confirmed")]
+    Confirmed,
+    #[serde(rename = "n/a")]
+    NotApplicable,
+}
+
+impl BookingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookingStatus::Contacted => "contacted",
+            BookingStatus::Pending => "pending",
+            BookingStatus::Confirmed => "confirmed",
+            BookingStatus::NotApplicable => "n/a",
+        }
+    }
+}
+
+impl TryFrom<String> for BookingStatus {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "contacted" => Ok(Self::Contacted),
+            "pending" => Ok(Self::Pending),
+            "confirmed" => Ok(Self::Confirmed),
+            "n/a" => Ok(Self::NotApplicable),
+            other => Err(format!("{} is not a supported status", other))
+        }
+    }
+}
+
+/// A booked date as it's exported. Only confirmed bookings are exported
+/// unless pending ones are asked for.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedDate {
+    pub tour_date_id: Uuid,
+    pub contact_id:   i32,
+    pub status:       BookingStatus,
+    pub show_date:    NaiveDate,
+    pub display_name: String,
+    pub address:      Option<String>,
+    pub city:         String,
+    pub state:        Option<String>,
+    pub zip_code:     Option<String>,
+    pub country:      Option<String>,
+    pub latitude:     Option<f32>,
+    pub longitude:    Option<f32>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tour {
+    pub tour_id:    Uuid,
+    pub user_id:    Uuid,
+    pub org_id:     Option<Uuid>,
+    pub tour_name:  String,
+    pub start_date: NaiveDate,
+    pub end_date:   NaiveDate,
+}
+
+impl Tour {
+    pub fn includes(&self, date: &NaiveDate) -> bool {
+        &self.start_date <= date && date <= &self.end_date
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTourData {
+    pub tour_name:  String,
+    pub start_date: NaiveDate,
+    pub end_date:   NaiveDate,
+}
+
+#[derive(Debug)]
+pub struct NewTour {
+    pub tour_name:  String,
+    pub start_date: NaiveDate,
+    pub end_date:   NaiveDate,
+}
+
+impl TryFrom<NewTourData> for NewTour {
+    type Error = String;
+
+    fn try_from(value: NewTourData) -> Result<Self, Self::Error> {
+        let tour_name = StringInput::parse(value.tour_name);
+
+        if tour_name.trim().is_empty() {
+            return Err("Tour name cannot be empty".to_string())
+        }
+
+        if value.start_date > value.end_date {
+            return Err("Tour cannot end before it starts".to_string())
+        }
+
+        Ok(Self {
+            tour_name,
+            start_date: value.start_date,
+            end_date:   value.end_date
+        })
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditTourData {
+    pub tour_id:    Uuid,
+    pub user_id:    Uuid,
+    pub tour_name:  String,
+    pub start_date: NaiveDate,
+    pub end_date:   NaiveDate,
+}
+
+impl TryFrom<EditTourData> for Tour {
+    type Error = String;
+
+    fn try_from(value: EditTourData) -> Result<Self, Self::Error> {
+        let tour_name = StringInput::parse(value.tour_name);
+
+        if tour_name.trim().is_empty() {
+            return Err("Tour name cannot be empty".to_string())
+        }
+
+        if value.start_date > value.end_date {
+            return Err("Tour cannot end before it starts".to_string())
+        }
+
+        Ok(Self {
+            tour_id:    value.tour_id,
+            user_id:    value.user_id,
+            org_id:     None,
+            tour_name,
+            start_date: value.start_date,
+            end_date:   value.end_date
+        })
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourParams {
+    pub tour_id: Uuid,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourDeleteData {
+    pub tour_id: Uuid,
+    pub user_id: Uuid,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourDate {
+    pub tour_date_id: Uuid,
+    pub tour_id:      Uuid,
+    pub show_date:    NaiveDate,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTourDateData {
+    pub tour_id:   Uuid,
+    pub show_date: NaiveDate,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveTourDateData {
+    pub tour_date_id: Uuid,
+    pub show_date:    NaiveDate,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourDateDeleteData {
+    pub tour_date_id: Uuid,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourDateContactData {
+    pub tour_date_id: Uuid,
+    pub contact_id:   i32,
+    pub status:       BookingStatus,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourDateContactDeleteData {
+    pub tour_date_id: Uuid,
+    pub contact_id:   i32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourDateContact {
+    pub status:  BookingStatus,
+    pub contact: ContactResponse,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourDateResponse {
+    pub tour_date_id: Uuid,
+    pub show_date:    NaiveDate,
+    pub contacts:     Vec<TourDateContact>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourResponse {
+    pub tour_id:    Uuid,
+    pub user_id:    Uuid,
+    pub org_id:     Option<Uuid>,
+    pub tour_name:  String,
+    pub start_date: NaiveDate,
+    pub end_date:   NaiveDate,
+    pub dates:      Vec<TourDateResponse>,
+}
+
+#[tracing::instrument(
+    name = "Deleting a tour from the database",
+    skip(tour_id, pool)
+)]
+pub async fn delete_tour(
+    tour_id: &Uuid,
+    pool:    &PgPool
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM tours WHERE tour_id = $1
+        "#,
+        tour_id
+    ).execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Deleting a tour date from the database",
+    skip(tour_date_id, pool)
+)]
+pub async fn delete_tour_date(
+    tour_date_id: &Uuid,
+    pool:         &PgPool
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM tour_dates WHERE tour_date_id = $1
+        "#,
+        tour_date_id
+    ).execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Removing contact from tour date",
+    skip(tour_date_id, contact_id, pool)
+)]
+pub async fn delete_tour_date_contact(
+    tour_date_id: &Uuid,
+    contact_id:   &i32,
+    pool:         &PgPool
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM tour_dates_contacts
+        WHERE tour_date_id = $1 AND contact_id = $2
+        "#,
+        tour_date_id,
+        contact_id
+    ).execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Saving new tour to database",
+    skip(tour, user_id, pool)
+)]
+pub async fn insert_tour(
+    tour:    &NewTour,
+    user_id: &Uuid,
+    org_id:  Option<Uuid>,
+    pool:    &PgPool
+) -> Result<Tour, sqlx::Error> {
+    let tour = sqlx::query_as!(
+        Tour,
+        r#"
+        INSERT INTO tours (tour_name, start_date, end_date, user_id, org_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING tour_id, user_id, org_id, tour_name, start_date, end_date
+        "#,
+        tour.tour_name,
+        tour.start_date,
+        tour.end_date,
+        user_id,
+        org_id
+    ).fetch_one(pool)
+    .await?;
+
+    Ok(tour)
+}
+
+#[tracing::instrument(
+    name = "Saving new tour date to database",
+    skip(tour_id, show_date, pool)
+)]
+pub async fn insert_tour_date(
+    tour_id:   &Uuid,
+    show_date: &NaiveDate,
+    pool:      &PgPool
+) -> Result<TourDate, sqlx::Error> {
+    let tour_date = sqlx::query_as!(
+        TourDate,
+        r#"
+        INSERT INTO tour_dates (tour_id, show_date)
+        VALUES ($1, $2)
+        RETURNING tour_date_id, tour_id, show_date
+        "#,
+        tour_id,
+        show_date
+    ).fetch_one(pool)
+    .await?;
+
+    Ok(tour_date)
+}
+
+/// Yields the dates one row at a time as they come back from the database,
+/// so exports don't have to hold the whole tour in memory
+pub fn stream_exported_dates<'a>(
+    tour_id:         &'a Uuid,
+    include_pending: bool,
+    pool:            &'a PgPool
+) -> BoxStream<'a, Result<ExportedDate, sqlx::Error>> {
+    sqlx::query!(
+        r#"
+        SELECT td.tour_date_id, tdc.contact_id, tdc.status, td.show_date,
+               c.display_name, c.address, c.city, c.state, c.zip_code, c.country,
+               c.latitude, c.longitude
+        FROM tour_dates td
+        JOIN tour_dates_contacts tdc ON td.tour_date_id = tdc.tour_date_id
+        JOIN contacts c ON c.contact_id = tdc.contact_id
+        WHERE td.tour_id = $1
+        AND (tdc.status = 'confirmed' OR ($2 AND tdc.status = 'pending'))
+        ORDER BY td.show_date, c.display_name
+        "#,
+        tour_id,
+        include_pending
+    ).fetch(pool)
+    .map(|row| {
+        let row    = row?;
+        let status = decode_enum(row.status)?;
+
+        Ok(ExportedDate {
+            tour_date_id: row.tour_date_id,
+            contact_id:   row.contact_id,
+            status,
+            show_date:    row.show_date,
+            display_name: row.display_name,
+            address:      row.address,
+            city:         row.city,
+            state:        row.state,
+            zip_code:     row.zip_code,
+            country:      row.country,
+            latitude:     row.latitude,
+            longitude:    row.longitude,
+        })
+    })
+    .boxed()
+}
+
+#[tracing::instrument(
+    name = "Querying tour from DB",
+    skip(tour_id, pool)
+)]
+pub async fn query_tour_by_id(
+    tour_id: &Uuid,
+    pool:    &PgPool
+) -> Result<Option<Tour>, sqlx::Error> {
+    let tour = sqlx::query_as!(
+        Tour,
+        r#"
+        SELECT tour_id, user_id, org_id, tour_name, start_date, end_date
+        FROM tours
+        WHERE tour_id = $1
+        "#,
+        tour_id
+    ).fetch_optional(pool)
+    .await?;
+
+    Ok(tour)
+}
+
+#[tracing::instrument(
+    name = "Querying tour date from DB",
+    skip(tour_date_id, pool)
+)]
+pub async fn query_tour_date_by_id(
+    tour_date_id: &Uuid,
+    pool:         &PgPool
+) -> Result<Option<TourDate>, sqlx::Error> {
+    let tour_date = sqlx::query_as!(
+        TourDate,
+        r#"
+        SELECT tour_date_id, tour_id, show_date
+        FROM tour_dates
+        WHERE tour_date_id = $1
+        "#,
+        tour_date_id
+    ).fetch_optional(pool)
+    .await?;
+
+    Ok(tour_date)
+}
+
+#[tracing::instrument(
+    name = "Querying dates and contacts for tour",
+    skip(tour_id, pool)
+)]
+pub async fn query_tour_dates(
+    tour_id: &Uuid,
+    pool:    &PgPool
+) -> Result<Vec<TourDateResponse>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT td.tour_date_id, td.show_date,
+               tdc.contact_id as "contact_id?", tdc.status as "status?"
+        FROM tour_dates td
+        LEFT JOIN tour_dates_contacts tdc ON td.tour_date_id = tdc.tour_date_id
+        WHERE td.tour_id = $1
+        ORDER BY td.show_date, tdc.created_at
+        "#,
+        tour_id
+    ).fetch_all(pool)
+    .await?;
+
+    // Fetched together rather than one date at a time. They stay on the tour
+    // even if a share is revoked later, so nothing is filtered out here.
+    let contact_ids: Vec<i32> = rows.iter().filter_map(|row| row.contact_id).collect();
+    let contacts: HashMap<i32, ContactResponse> = query_contacts_by_ids(pool, &contact_ids)
+        .await?
+        .into_iter()
+        .map(|contact| (contact.contact_id, contact))
+        .collect();
+
+    let mut dates: Vec<TourDateResponse> = Vec::new();
+
+    for row in rows {
+        let contact = match (row.contact_id, row.status) {
+            (Some(contact_id), Some(status)) => {
+                let status = decode_enum(status)?;
+
+                contacts
+                    .get(&contact_id)
+                    .map(|contact| TourDateContact { status, contact: contact.clone() })
+            }
+            _ => None
+        };
+
+        if let Some(existing_date) = dates.iter_mut().find(|d| d.tour_date_id == row.tour_date_id) {
+            existing_date.contacts.extend(contact);
+        } else {
+            dates.push(TourDateResponse {
+                tour_date_id: row.tour_date_id,
+                show_date:    row.show_date,
+                contacts:     contact.into_iter().collect()
+            });
+        }
+    }
+
+    Ok(dates)
+}
+
+#[tracing::instrument(
+    name = "Querying User's tours from DB",
+    skip(user_id, pool)
+)]
+pub async fn query_tours_by_user(
+    user_id: &Uuid,
+    org_id:  Option<Uuid>,
+    pool:    &PgPool
+) -> Result<Vec<Tour>, sqlx::Error> {
+    // The user's own tours, plus the organisation's if they're acting for one
+    let tours = sqlx::query_as!(
+        Tour,
+        r#"
+        SELECT tour_id, user_id, org_id, tour_name, start_date, end_date
+        FROM tours
+        WHERE (org_id IS NULL AND user_id = $1) OR org_id = $2
+        ORDER BY start_date
+        "#,
+        user_id,
+        org_id
+    ).fetch_all(pool)
+    .await?;
+
+    Ok(tours)
+}
+
+#[tracing::instrument(
+    name = "Counting tour dates outside of range",
+    skip(tour, pool)
+)]
+pub async fn count_dates_outside_tour(
+    tour: &Tour,
+    pool: &PgPool
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM tour_dates
+        WHERE tour_id = $1 AND (show_date < $2 OR show_date > $3)
+        "#,
+        tour.tour_id,
+        tour.start_date,
+        tour.end_date
+    ).fetch_one(pool)
+    .await?;
+
+    Ok(result.count)
+}
+
+#[tracing::instrument(
+    name = "Updating tour in database",
+    skip(tour, pool)
+)]
+pub async fn update_tour(
+    tour: &Tour,
+    pool: &PgPool
+) -> Result<Tour, sqlx::Error> {
+    let tour = sqlx::query_as!(
+        Tour,
+        r#"
+        UPDATE tours
+        SET tour_name = $1, start_date = $2, end_date = $3, updated_at = current_timestamp
+        WHERE tour_id = $4
+        RETURNING tour_id, user_id, org_id, tour_name, start_date, end_date
+        "#,
+        tour.tour_name,
+        tour.start_date,
+        tour.end_date,
+        tour.tour_id
+    ).fetch_one(pool)
+    .await?;
+
+    Ok(tour)
+}
+
+#[tracing::instrument(
+    name = "Moving tour date in database",
+    skip(tour_date_id, show_date, pool)
+)]
+pub async fn update_tour_date(
+    tour_date_id: &Uuid,
+    show_date:    &NaiveDate,
+    pool:         &PgPool
+) -> Result<TourDate, sqlx::Error> {
+    let tour_date = sqlx::query_as!(
+        TourDate,
+        r#"
+        UPDATE tour_dates
+        SET show_date = $1, updated_at = current_timestamp
+        WHERE tour_date_id = $2
+        RETURNING tour_date_id, tour_id, show_date
+        "#,
+        show_date,
+        tour_date_id
+    ).fetch_one(pool)
+    .await?;
+
+    Ok(tour_date)
+}
+
+#[tracing::instrument(
+    name = "Setting contact status for tour date",
+    skip(tour_date_id, contact_id, status, pool)
+)]
+pub async fn upsert_tour_date_contact(
+    tour_date_id: &Uuid,
+    contact_id:   &i32,
+    status:       BookingStatus,
+    pool:         &PgPool
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO tour_dates_contacts (tour_date_id, contact_id, status)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (tour_date_id, contact_id)
+        DO UPDATE SET status = EXCLUDED.status, updated_at = current_timestamp
+        "#,
+        tour_date_id,
+        contact_id,
+        status.as_str()
+    ).execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use claims::{assert_err, assert_ok};
+
+    use crate::domain::{BookingStatus, NewTour, NewTourData};
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, d).unwrap()
+    }
+
+    #[test]
+    fn booking_status_round_trips_through_strings() {
+        for status in [
+            BookingStatus::Contacted,
+            BookingStatus::Pending,
+            BookingStatus::Confirmed,
+            BookingStatus::NotApplicable
+        ] {
+            let parsed = BookingStatus::try_from(status.as_str().to_string());
+            assert_eq!(parsed, Ok(status));
+        }
+    }
+
+    #[test]
+    fn unknown_booking_status_is_rejected() {
+        assert_err!(BookingStatus::try_from("cancelled".to_string()));
+    }
+
+    #[test]
+    fn tour_cannot_end_before_it_starts() {
+        let data = NewTourData {
+            tour_name:  "summer".to_string(),
+            start_date: date(10),
+            end_date:   date(1),
+        };
+
+        assert_err!(NewTour::try_from(data));
+    }
+
+    #[test]
+    fn single_day_tour_is_accepted() {
+        let data = NewTourData {
+            tour_name:  "one-off".to_string(),
+            start_date: date(1),
+            end_date:   date(1),
+        };
+
+        assert_ok!(NewTour::try_from(data));
+    }
+}
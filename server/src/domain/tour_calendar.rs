@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarExportParams {
+    pub tour_id:         Uuid,
+    /// Adds pending dates as tentative events
+    pub include_pending: Option<bool>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarFeedParams {
+    pub token:           String,
+    pub include_pending: Option<bool>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarSubscription {
+    pub token: String,
+    /// Add `&includePending=true` for tentative events
+    pub url:   String,
+}
+
+#[tracing::instrument(
+    name = "Revoking calendar tokens for tour",
+    skip(tour_id, transaction)
+)]
+pub async fn delete_calendar_tokens(
+    tour_id:     &Uuid,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM calendar_tokens WHERE tour_id = $1
+        "#,
+        tour_id
+    ).execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Storing calendar token in the database",
+    skip(calendar_token, transaction)
+)]
+pub async fn insert_calendar_token(
+    tour_id:        &Uuid,
+    user_id:        &Uuid,
+    calendar_token: &str,
+    transaction:    &mut Transaction<'_, Postgres>
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO calendar_tokens (calendar_token, tour_id, user_id)
+        VALUES ($1, $2, $3)
+        "#,
+        calendar_token,
+        tour_id,
+        user_id
+    ).execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Looking up tour from calendar token",
+    skip(calendar_token, pool)
+)]
+pub async fn query_tour_id_by_calendar_token(
+    calendar_token: &str,
+    pool:           &PgPool
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT tour_id FROM calendar_tokens WHERE calendar_token = $1
+        "#,
+        calendar_token
+    ).fetch_optional(pool)
+    .await?;
+
+    Ok(result.map(|r| r.tour_id))
+}
@@ -0,0 +1,207 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::{BookingStatus, TourDateResponse};
+use crate::gmaps_api_client::Location;
+use crate::routing::RouteLeg;
+
+const DEFAULT_MAX_DRIVE_HOURS: f64 = 8.0;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourLegsParams {
+    pub tour_id:         Uuid,
+    /// How long the band is willing to drive per day between shows
+    pub max_drive_hours: Option<f64>,
+}
+
+impl TourLegsParams {
+    pub fn max_drive_hours(&self) -> Result<f64, String> {
+        match self.max_drive_hours {
+            None => Ok(DEFAULT_MAX_DRIVE_HOURS),
+            Some(hours) if hours > 0.0 && hours <= 24.0 => Ok(hours),
+            Some(_) => Err("maxDriveHours must be between 0 and 24".to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourStop {
+    pub tour_date_id: Uuid,
+    pub show_date:    NaiveDate,
+    pub contact_id:   i32,
+    pub display_name: String,
+    pub status:       BookingStatus,
+    pub location:     Location,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourLeg {
+    pub from:          TourStop,
+    pub to:            TourStop,
+    pub distance_km:   f64,
+    pub drive_minutes: f64,
+    pub days_between:  i64,
+    /// The drive doesn't fit in the days between the two shows
+    pub too_long:      bool,
+    /// The routing server couldn't be used, so this is a straight-line guess
+    pub estimated:     bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TourLegs {
+    pub max_drive_hours:   f64,
+    pub total_distance_km: f64,
+    /// Some of the legs are straight-line guesses
+    pub estimated:         bool,
+    pub legs:              Vec<TourLeg>,
+    /// Dates with a booking whose venue has no coordinates yet, so they were
+    /// left out of the route
+    pub unlocated_dates:   Vec<Uuid>,
+}
+
+impl TourLegs {
+    /// Pairs up consecutive `stops` with the legs the routing provider
+    /// returned for them.
+    pub fn new(
+        stops:           Vec<TourStop>,
+        route_legs:      Vec<RouteLeg>,
+        max_drive_hours: f64,
+        unlocated_dates: Vec<Uuid>
+    ) -> Self {
+        let legs: Vec<TourLeg> = stops
+            .windows(2)
+            .zip(route_legs)
+            .map(|(pair, leg)| {
+                let days_between = (pair[1].show_date - pair[0].show_date).num_days();
+                // Two shows on the same day still get one day's worth of driving
+                let allowed_minutes = days_between.max(1) as f64 * max_drive_hours * 60.0;
+
+                TourLeg {
+                    from:          pair[0].clone(),
+                    to:            pair[1].clone(),
+                    distance_km:   leg.distance_km,
+                    drive_minutes: leg.drive_minutes,
+                    days_between,
+                    too_long:      leg.drive_minutes > allowed_minutes,
+                    estimated:     leg.estimated,
+                }
+            })
+            .collect();
+
+        Self {
+            max_drive_hours,
+            total_distance_km: legs.iter().map(|l| l.distance_km).sum(),
+            estimated:         legs.iter().any(|l| l.estimated),
+            legs,
+            unlocated_dates,
+        }
+    }
+}
+
+/// Picks the venue to route through for each date, preferring the firmest
+/// booking. Returns the stops in date order along with the dates that have a
+/// booking but couldn't be placed on the map.
+pub fn tour_stops(dates: &[TourDateResponse]) -> (Vec<TourStop>, Vec<Uuid>) {
+    let mut stops     = Vec::new();
+    let mut unlocated = Vec::new();
+
+    for date in dates {
+        let booking = match date.contacts
+            .iter()
+            .filter(|c| c.status != BookingStatus::NotApplicable)
+            .max_by_key(|c| status_rank(c.status))
+        {
+            Some(booking) => booking,
+            None => continue,
+        };
+
+        match (booking.contact.latitude, booking.contact.longitude) {
+            (Some(lat), Some(lng)) => stops.push(TourStop {
+                tour_date_id: date.tour_date_id,
+                show_date:    date.show_date,
+                contact_id:   booking.contact.contact_id,
+                display_name: booking.contact.display_name.clone(),
+                status:       booking.status,
+                location:     Location { lat, lng },
+            }),
+            _ => unlocated.push(date.tour_date_id),
+        }
+    }
+
+    stops.sort_by_key(|s| s.show_date);
+
+    (stops, unlocated)
+}
+
+fn status_rank(status: BookingStatus) -> u8 {
+    match status {
+        BookingStatus::Confirmed => 3,
+        BookingStatus::Pending => 2,
+        BookingStatus::Contacted => 1,
+        BookingStatus::NotApplicable => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use claims::{assert_err, assert_ok};
+    use uuid::Uuid;
+
+    use crate::domain::{BookingStatus, TourLegs, TourLegsParams, TourStop};
+    use crate::gmaps_api_client::Location;
+    use crate::routing::RouteLeg;
+
+    fn stop(day: u32) -> TourStop {
+        TourStop {
+            tour_date_id: Uuid::new_v4(),
+            show_date:    NaiveDate::from_ymd_opt(2024, 6, day).unwrap(),
+            contact_id:   day as i32,
+            display_name: format!("venue {}", day),
+            status:       BookingStatus::Confirmed,
+            location:     Location { lat: 35.0, lng: -80.0 },
+        }
+    }
+
+    fn leg(hours: f64) -> RouteLeg {
+        RouteLeg { distance_km: hours * 100.0, drive_minutes: hours * 60.0, estimated: false }
+    }
+
+    #[test]
+    fn legs_longer_than_the_gap_allows_are_flagged() {
+        let legs = TourLegs::new(
+            vec![stop(1), stop(2), stop(4)],
+            vec![leg(9.0), leg(9.0)],
+            8.0,
+            Vec::new()
+        );
+
+        assert!(legs.legs[0].too_long);
+        assert_eq!(2, legs.legs[1].days_between);
+        assert!(!legs.legs[1].too_long);
+        assert_eq!(1800.0, legs.total_distance_km);
+    }
+
+    #[test]
+    fn same_day_shows_get_a_days_driving() {
+        let legs = TourLegs::new(vec![stop(1), stop(1)], vec![leg(2.0)], 8.0, Vec::new());
+
+        assert_eq!(0, legs.legs[0].days_between);
+        assert!(!legs.legs[0].too_long);
+    }
+
+    #[test]
+    fn max_drive_hours_must_fit_in_a_day() {
+        let params = |hours| TourLegsParams { tour_id: Uuid::new_v4(), max_drive_hours: hours };
+
+        assert_eq!(Ok(8.0), params(None).max_drive_hours());
+        assert_ok!(params(Some(24.0)).max_drive_hours());
+        assert_err!(params(Some(0.0)).max_drive_hours());
+        assert_err!(params(Some(25.0)).max_drive_hours());
+    }
+}
@@ -1,12 +1,14 @@
 use serde::{Deserialize, Serialize};
 use secrecy::Secret;
+use sqlx::PgPool;
 use uuid::Uuid;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CleanUser {
     pub user_id: uuid::Uuid,
     pub email:   String,
-    pub role:    String
+    pub role:    String,
+    pub status:  UserStatus
 }
 
 #[derive(Debug, Deserialize)]
@@ -14,7 +16,8 @@ pub struct User {
     pub user_id:       uuid::Uuid,
     pub email:         String,
     pub password_hash: Secret<String>,
-    pub role:          String
+    pub role:          String,
+    pub status:        UserStatus
 }
 
 #[derive(Debug, Deserialize)]
@@ -27,4 +30,132 @@ pub struct UserLogin {
 #[serde(rename_all = "camelCase")]
 pub struct UserParams {
     pub user_id: Uuid
-}
\ No newline at end of file
+}
+
+/// Where an account stands. Only confirmed users can log in or use the
+/// `/user` routes.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserStatus {
+    /// Signed up but hasn't confirmed their email yet
+    Pending,
+    Confirmed,
+    /// Blocked for now, e.g. while a report is looked into
+    Suspended,
+    Banned,
+}
+
+impl UserStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserStatus::Pending => "pending",
+            UserStatus::Confirmed => "confirmed",
+            UserStatus::Suspended => "suspended",
+            UserStatus::Banned => "banned",
+        }
+    }
+}
+
+impl TryFrom<String> for UserStatus {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "pending" => Ok(Self::Pending),
+            "confirmed" => Ok(Self::Confirmed),
+            "suspended" => Ok(Self::Suspended),
+            "banned" => Ok(Self::Banned),
+            other => Err(format!("{} is not a supported status", other))
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserStatusData {
+    pub user_id: Uuid,
+    pub status:  UserStatus
+}
+
+#[tracing::instrument(
+    name = "Querying user status",
+    skip(pool)
+)]
+pub async fn query_user_status(
+    user_id: &Uuid,
+    pool:    &PgPool
+) -> Result<Option<UserStatus>, anyhow::Error> {
+    let result = sqlx::query!(
+        r#"SELECT status FROM users WHERE user_id = $1"#,
+        user_id
+    ).fetch_optional(pool)
+    .await?;
+
+    result
+        .map(|row| UserStatus::try_from(row.status).map_err(anyhow::Error::msg))
+        .transpose()
+}
+
+/// What's checked about a user on every authenticated request
+#[derive(Debug)]
+pub struct UserAccess {
+    pub status:             UserStatus,
+    /// Their role requires two-factor and they haven't turned it on
+    pub two_factor_missing: bool,
+}
+
+#[tracing::instrument(
+    name = "Querying user access",
+    skip(pool)
+)]
+pub async fn query_user_access(
+    user_id: &Uuid,
+    pool:    &PgPool
+) -> Result<Option<UserAccess>, anyhow::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT
+            u.status,
+            (
+                EXISTS (SELECT 1 FROM two_factor_requirements r WHERE r.role = u.role)
+                AND NOT EXISTS (
+                    SELECT 1 FROM user_totp t
+                    WHERE t.user_id = u.user_id AND t.enabled_at IS NOT NULL
+                )
+            ) AS "two_factor_missing!"
+        FROM users u
+        WHERE u.user_id = $1
+        "#,
+        user_id
+    ).fetch_optional(pool)
+    .await?;
+
+    result
+        .map(|row| {
+            Ok(UserAccess {
+                status:             UserStatus::try_from(row.status).map_err(anyhow::Error::msg)?,
+                two_factor_missing: row.two_factor_missing,
+            })
+        })
+        .transpose()
+}
+
+/// Returns false if there's no user with that id
+#[tracing::instrument(
+    name = "Updating user status",
+    skip(pool)
+)]
+pub async fn update_user_status(
+    user_id: &Uuid,
+    status:  UserStatus,
+    pool:    &PgPool
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"UPDATE users SET status = $1 WHERE user_id = $2"#,
+        status.as_str(),
+        user_id
+    ).execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use crate::email_client::{EmailMessage, Mailer};
+use crate::error::MailerError;
+
+/// Writes each email to its own `.eml` file, which mail clients can open,
+/// so signup and password reset links can be followed without a mail
+/// provider.
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(dir: impl Into<PathBuf>) -> FileMailer {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: &EmailMessage<'_>) -> Result<(), MailerError> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        // Timestamp first so a directory listing reads oldest to newest
+        let filename = format!(
+            "{}-{}.eml",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+            uuid::Uuid::new_v4().simple()
+        );
+        let path     = self.dir.join(filename);
+
+        tokio::fs::write(&path, email.to_mime()).await?;
+        tracing::info!("Wrote email to {} to {}", email.to, path.display());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::assert_ok;
+
+    use crate::email_client::{EmailMessage, FileMailer, Mailer};
+
+    #[tokio::test]
+    async fn each_email_is_written_to_its_own_file() {
+        let dir    = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let mailer = FileMailer::new(&dir);
+        let email  = EmailMessage {
+            from:      "bookings@byot.example.com",
+            to:        "new-user@example.com",
+            reply_to:  None,
+            subject:   "Confirm your account",
+            html_body: "<a href=\"http://localhost/confirm\">here</a>",
+            text_body: "Visit http://localhost/confirm",
+        };
+
+        assert_ok!(mailer.send(&email).await);
+        assert_ok!(mailer.send(&email).await);
+
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(2, files.len());
+
+        let path     = files[0].as_ref().unwrap().path();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(Some("eml"), path.extension().and_then(|e| e.to_str()));
+        assert!(contents.contains("To: new-user@example.com"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,279 @@
+mod file_drop;
+mod postmark;
+mod smtp;
+
+pub use file_drop::*;
+pub use postmark::*;
+pub use smtp::*;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use secrecy::Secret;
+use serde_aux::field_attributes::deserialize_number_from_string;
+use std::sync::Arc;
+
+use crate::domain::UserEmail;
+use crate::error::MailerError;
+
+/// Hands a finished email to something that delivers it.
+#[async_trait::async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &EmailMessage<'_>) -> Result<(), MailerError>;
+}
+
+#[derive(Debug)]
+pub struct EmailMessage<'a> {
+    pub from:      &'a str,
+    pub to:        &'a str,
+    pub reply_to:  Option<&'a str>,
+    pub subject:   &'a str,
+    pub html_body: &'a str,
+    pub text_body: &'a str,
+}
+
+impl EmailMessage<'_> {
+    /// Renders the message as RFC 5322 text with plain and HTML
+    /// alternatives, as written to `.eml` files and sent over SMTP
+    pub fn to_mime(&self) -> String {
+        let boundary = format!("byot-{}", uuid::Uuid::new_v4().simple());
+        let domain   = self.from.rsplit('@').next().unwrap_or("localhost");
+        let mut mime = String::new();
+
+        mime.push_str(&format!("From: {}\r\n", single_line(self.from)));
+        mime.push_str(&format!("To: {}\r\n", single_line(self.to)));
+        if let Some(reply_to) = self.reply_to {
+            mime.push_str(&format!("Reply-To: {}\r\n", single_line(reply_to)));
+        }
+        mime.push_str(&format!("Subject: {}\r\n", encode_header(self.subject)));
+        mime.push_str(&format!("Date: {}\r\n", chrono::Utc::now().to_rfc2822()));
+        mime.push_str(&format!("Message-ID: <{}@{}>\r\n", uuid::Uuid::new_v4(), domain));
+        mime.push_str("MIME-Version: 1.0\r\n");
+        mime.push_str(&format!(
+            "Content-Type: multipart/alternative; boundary=\"{}\"\r\n\r\n",
+            boundary
+        ));
+
+        for (content_type, body) in [("text/plain", self.text_body), ("text/html", self.html_body)] {
+            mime.push_str(&format!("--{}\r\n", boundary));
+            mime.push_str(&format!("Content-Type: {}; charset=utf-8\r\n", content_type));
+            mime.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+            mime.push_str(&encode_body(body));
+        }
+        mime.push_str(&format!("--{}--\r\n", boundary));
+
+        mime
+    }
+}
+
+/// True if a value would end its header line early and start another. These
+/// come from users, e.g. inquiry subjects, so they're never trusted.
+pub fn has_line_break(value: &str) -> bool {
+    value.contains(['\r', '\n'])
+}
+
+/// Folds any line breaks into spaces so a value can't add its own headers
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+/// Bytes of text per encoded word. Base64 makes 60 characters of them, so
+/// with `=?utf-8?B?` and `?=` a word stays within RFC 2047's 75.
+const ENCODED_WORD_BYTES: usize = 45;
+
+/// RFC 2047 encodes headers that aren't plain ASCII. Long values are split
+/// into several encoded words, one per folded line, without splitting a
+/// character between them.
+fn encode_header(value: &str) -> String {
+    let value = single_line(value);
+
+    if value.is_ascii() {
+        return value
+    }
+
+    let mut words = Vec::new();
+    let mut start = 0;
+
+    while start < value.len() {
+        let mut end = (start + ENCODED_WORD_BYTES).min(value.len());
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        words.push(format!("=?utf-8?B?{}?=", STANDARD.encode(&value[start..end])));
+        start = end;
+    }
+
+    words.join("\r\n ")
+}
+
+/// Base64 wrapped at 76 characters, so any UTF-8 survives any relay
+fn encode_body(body: &str) -> String {
+    let encoded = STANDARD.encode(body);
+
+    encoded.as_bytes()
+        .chunks(76)
+        .map(|line| format!("{}\r\n", String::from_utf8_lossy(line)))
+        .collect()
+}
+
+/// Sends mail from the platform's address through whichever [`Mailer`] is
+/// configured.
+pub struct EmailClient {
+    mailer: Arc<dyn Mailer>,
+    sender: UserEmail,
+}
+
+impl EmailClient {
+    pub fn new(mailer: Arc<dyn Mailer>, sender: UserEmail) -> EmailClient {
+        Self { mailer, sender }
+    }
+
+    pub async fn send_email(
+        &self,
+        recipient: &UserEmail,
+        subject:   &str,
+        html_body: &str,
+        text_body: &str,
+    ) -> Result<(), MailerError> {
+        self.send_email_with_reply_to(recipient, None, subject, html_body, text_body).await
+    }
+
+    /// Sends from the platform's address so the sending domain stays
+    /// verified, with replies going to `reply_to`
+    pub async fn send_email_with_reply_to(
+        &self,
+        recipient: &UserEmail,
+        reply_to:  Option<&UserEmail>,
+        subject:   &str,
+        html_body: &str,
+        text_body: &str,
+    ) -> Result<(), MailerError> {
+        let email = EmailMessage {
+            from:      self.sender.as_ref(),
+            to:        recipient.as_ref(),
+            reply_to:  reply_to.map(|email| email.as_ref()),
+            subject,
+            html_body,
+            text_body,
+        };
+
+        self.mailer.send(&email).await
+    }
+}
+
+/// How mail leaves the server, picked with `backend`.
+#[derive(serde::Deserialize, Clone)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum MailerSettings {
+    Postmark {
+        base_url:       String,
+        auth_token:     Secret<String>,
+        #[serde(deserialize_with = "deserialize_number_from_string")]
+        timeout_millis: u64,
+    },
+    Smtp(SmtpSettings),
+    /// Writes each email to a `.eml` file in `path` instead of sending it.
+    /// Meant for local development.
+    File {
+        path: String,
+    },
+}
+
+impl MailerSettings {
+    pub fn build(self) -> Result<Arc<dyn Mailer>, anyhow::Error> {
+        let mailer: Arc<dyn Mailer> = match self {
+            Self::Postmark { base_url, auth_token, timeout_millis } => Arc::new(
+                PostmarkClient::new(
+                    base_url,
+                    auth_token,
+                    std::time::Duration::from_millis(timeout_millis)
+                )
+            ),
+            Self::Smtp(settings) => Arc::new(SmtpMailer::new(settings)?),
+            Self::File { path } => Arc::new(FileMailer::new(path)),
+        };
+
+        Ok(mailer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+
+    use crate::email_client::EmailMessage;
+
+    fn message(subject: &'static str) -> EmailMessage<'static> {
+        EmailMessage {
+            from:      "bookings@byot.example.com",
+            to:        "venue@example.com",
+            reply_to:  Some("band@example.com"),
+            subject,
+            html_body: "<p>Hi</p>",
+            text_body: "Hi",
+        }
+    }
+
+    #[test]
+    fn mime_has_headers_and_both_alternatives() {
+        let mime = message("Booking inquiry").to_mime();
+
+        assert!(mime.contains("From: bookings@byot.example.com\r\n"));
+        assert!(mime.contains("To: venue@example.com\r\n"));
+        assert!(mime.contains("Reply-To: band@example.com\r\n"));
+        assert!(mime.contains("Subject: Booking inquiry\r\n"));
+        assert!(mime.contains("Message-ID: <"));
+        assert!(mime.contains("Content-Type: text/plain; charset=utf-8"));
+        assert!(mime.contains("Content-Type: text/html; charset=utf-8"));
+        // base64 of "<p>Hi</p>"
+        assert!(mime.contains("PHA+SGk8L3A+\r\n"));
+    }
+
+    #[test]
+    fn non_ascii_subjects_are_encoded() {
+        let mime = message("Show at Café Oto?").to_mime();
+
+        assert!(mime.contains("Subject: =?utf-8?B?"));
+        assert!(!mime.contains("Café"));
+    }
+
+    #[test]
+    fn long_subjects_are_split_into_short_encoded_words() {
+        let subject = "Ça va? ".repeat(20);
+        let mime    = EmailMessage { subject: &subject, ..message("") }.to_mime();
+        let header  = mime
+            .split("\r\n")
+            .skip_while(|line| !line.starts_with("Subject: "))
+            .take_while(|line| line.starts_with("Subject: ") || line.starts_with(' '))
+            .collect::<Vec<_>>();
+
+        assert!(header.len() > 1);
+
+        let decoded = header.iter()
+            .map(|line| line.trim_start_matches("Subject: ").trim())
+            .map(|word| {
+                assert!(word.len() <= 75);
+                let encoded = word.strip_prefix("=?utf-8?B?").unwrap().strip_suffix("?=").unwrap();
+                String::from_utf8(STANDARD.decode(encoded).unwrap()).unwrap()
+            })
+            .collect::<String>();
+
+        assert_eq!(subject, decoded);
+    }
+
+    #[test]
+    fn line_breaks_cannot_add_headers() {
+        let mime = EmailMessage {
+            to:       "venue@example.com\r\nCc: cc@example.com",
+            reply_to: Some("band@example.com\nSender: x@example.com"),
+            ..message("Hi\r\nBcc: victim@example.com")
+        }.to_mime();
+
+        assert!(mime.contains("Subject: Hi  Bcc: victim@example.com\r\n"));
+        assert!(mime.contains("To: venue@example.com  Cc: cc@example.com\r\n"));
+        assert!(!mime.contains("\r\nBcc:"));
+        assert!(!mime.contains("\r\nCc:"));
+        assert!(!mime.contains("\nSender:"));
+    }
+}
@@ -1,22 +1,21 @@
 use reqwest::Client;
 use secrecy::{ExposeSecret, Secret};
 
-use crate::domain::UserEmail;
+use crate::email_client::{EmailMessage, Mailer};
+use crate::error::MailerError;
 
-pub struct EmailClient {
+pub struct PostmarkClient {
     http_client: Client,
     base_url:    String,
-    sender:      UserEmail,
     auth_token:  Secret<String>,
 }
 
-impl EmailClient {
+impl PostmarkClient {
     pub fn new(
         base_url:   String,
-        sender:     UserEmail,
         auth_token: Secret<String>,
         timeout:    std::time::Duration
-    ) -> EmailClient {
+    ) -> PostmarkClient {
         let http_client = Client::builder()
             .timeout(timeout)
             .build()
@@ -24,25 +23,22 @@ impl EmailClient {
         Self {
             http_client,
             base_url,
-            sender,
             auth_token,
         }
     }
+}
 
-    pub async fn send_email(
-        &self,
-        recipient: &UserEmail,
-        subject:   &str,
-        html_body: &str,
-        text_body: &str,
-    ) -> Result<(), reqwest::Error> {
+#[async_trait::async_trait]
+impl Mailer for PostmarkClient {
+    async fn send(&self, email: &EmailMessage<'_>) -> Result<(), MailerError> {
         let url  = format!("{}/email", self.base_url);
         let body = SendEmailRequest {
-            from:      self.sender.as_ref(),
-            to:        recipient.as_ref(),
-            subject:   subject,
-            html_body: html_body,
-            text_body: text_body
+            from:      email.from,
+            to:        email.to,
+            reply_to:  email.reply_to,
+            subject:   email.subject,
+            html_body: email.html_body,
+            text_body: email.text_body
         };
         self.http_client
             .post(&url)
@@ -64,6 +60,8 @@ impl EmailClient {
 struct SendEmailRequest<'a> {
     from:      &'a str,
     to:        &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_to:  Option<&'a str>,
     subject:   &'a str,
     html_body: &'a str,
     text_body: &'a str,
@@ -76,11 +74,12 @@ mod tests {
     use fake::faker::lorem::en::{Paragraph, Sentence};
     use fake::{Fake, Faker};
     use secrecy::Secret;
-    use wiremock::matchers::{any, header, header_exists, method, path};
+    use wiremock::matchers::{any, body_partial_json, header, header_exists, method, path};
+    use std::sync::Arc;
     use wiremock::{Mock, MockServer, Request, ResponseTemplate};
 
     use crate::domain::UserEmail;
-    use crate::email_client::EmailClient;
+    use crate::email_client::{EmailClient, PostmarkClient};
 
     struct SendEmailBodyMatcher;
 
@@ -121,6 +120,27 @@ mod tests {
             .await;
     }
 
+    #[tokio::test]
+    async fn send_email_with_reply_to_sets_the_reply_to_field() {
+        let mock_server  = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+        let reply_to     = email();
+
+        Mock::given(path("/email"))
+            .and(method("POST"))
+            .and(body_partial_json(serde_json::json!({ "ReplyTo": reply_to.as_ref() })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let outcome = email_client
+            .send_email_with_reply_to(&email(), Some(&reply_to), &subject(), &content(), &content())
+            .await;
+
+        assert_ok!(outcome);
+    }
+
     #[tokio::test]
     async fn send_email_succeeds_if_the_server_returns_200() {
         let mock_server  = MockServer::start().await;
@@ -191,11 +211,12 @@ mod tests {
     }
 
     fn email_client(base_url: String) -> EmailClient {
-        EmailClient::new(
-            base_url, 
-            email(), 
+        let postmark = PostmarkClient::new(
+            base_url,
             Secret::new(Faker.fake()),
             std::time::Duration::from_millis(200)
-        )
+        );
+
+        EmailClient::new(Arc::new(postmark), email())
     }
 }
\ No newline at end of file
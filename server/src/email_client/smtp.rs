@@ -0,0 +1,358 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use secrecy::{ExposeSecret, Secret};
+use serde_aux::field_attributes::deserialize_number_from_string;
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use tokio_rustls::TlsConnector;
+
+use crate::email_client::{has_line_break, EmailMessage, Mailer};
+use crate::error::MailerError;
+
+#[derive(serde::Deserialize, Clone)]
+pub struct SmtpSettings {
+    pub host:           String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub port:           u16,
+    pub username:       Option<String>,
+    pub password:       Option<Secret<String>>,
+    #[serde(default)]
+    pub tls:            SmtpTls,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub timeout_millis: u64,
+}
+
+/// How the connection to the SMTP server is secured
+#[derive(serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Plain text throughout, e.g. for Mailpit or MailHog on localhost.
+    /// Credentials are only sent this way to a loopback host.
+    None,
+    /// Upgrade with STARTTLS, usually on port 587
+    #[default]
+    StartTls,
+    /// TLS from the start, usually on port 465
+    Tls,
+}
+
+fn is_loopback(host: &str) -> bool {
+    host == "localhost" || host.parse::<IpAddr>().map_or(false, |ip| ip.is_loopback())
+}
+
+trait SmtpStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> SmtpStream for T {}
+
+/// Delivers mail to an SMTP relay, one connection per email.
+pub struct SmtpMailer {
+    settings:  SmtpSettings,
+    connector: TlsConnector,
+}
+
+impl SmtpMailer {
+    pub fn new(settings: SmtpSettings) -> Result<SmtpMailer, anyhow::Error> {
+        if settings.username.is_some() != settings.password.is_some() {
+            anyhow::bail!("SMTP username and password must be set together");
+        }
+
+        // AUTH PLAIN is only base64, so anyone on the network could read it
+        if settings.username.is_some() && settings.tls == SmtpTls::None && !is_loopback(&settings.host) {
+            anyhow::bail!("SMTP credentials can only be sent without TLS to localhost");
+        }
+
+        let mut roots = RootCertStore::empty();
+        roots.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|anchor| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                anchor.subject,
+                anchor.spki,
+                anchor.name_constraints
+            )
+        }));
+
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        Ok(Self {
+            settings,
+            connector: TlsConnector::from(Arc::new(config)),
+        })
+    }
+
+    async fn upgrade(&self, stream: Box<dyn SmtpStream>) -> Result<Box<dyn SmtpStream>, MailerError> {
+        let server_name = ServerName::try_from(self.settings.host.as_str())
+            .map_err(|_| MailerError::SmtpError(format!("Invalid SMTP host {}", self.settings.host)))?;
+        let stream      = self.connector.connect(server_name, stream).await?;
+
+        Ok(Box::new(stream))
+    }
+
+    async fn deliver(&self, email: &EmailMessage<'_>) -> Result<(), MailerError> {
+        // Addresses go into SMTP commands as they are
+        if has_line_break(email.from) || has_line_break(email.to) {
+            return Err(MailerError::SmtpError("Email addresses can't contain line breaks".to_string()))
+        }
+
+        let address = (self.settings.host.as_str(), self.settings.port);
+        let mut stream: Box<dyn SmtpStream> = Box::new(TcpStream::connect(address).await?);
+
+        if self.settings.tls == SmtpTls::Tls {
+            stream = self.upgrade(stream).await?;
+        }
+
+        let hello    = email.from.rsplit('@').next().unwrap_or("localhost");
+        let mut conn = Connection::new(stream);
+
+        conn.expect_reply(220).await?;
+        conn.command(&format!("EHLO {}", hello), 250).await?;
+
+        if self.settings.tls == SmtpTls::StartTls {
+            conn.command("STARTTLS", 220).await?;
+            conn = Connection::new(self.upgrade(conn.into_inner()).await?);
+            conn.command(&format!("EHLO {}", hello), 250).await?;
+        }
+
+        if let (Some(username), Some(password)) = (&self.settings.username, &self.settings.password) {
+            let credentials = format!("\0{}\0{}", username, password.expose_secret());
+            conn.command(&format!("AUTH PLAIN {}", STANDARD.encode(credentials)), 235).await?;
+        }
+
+        conn.command(&format!("MAIL FROM:<{}>", email.from), 250).await?;
+        conn.command(&format!("RCPT TO:<{}>", email.to), 250).await?;
+        conn.command("DATA", 354).await?;
+        conn.send_data(&email.to_mime()).await?;
+
+        // The message is accepted by now, so a failed goodbye doesn't matter
+        let _ = conn.command("QUIT", 221).await;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &EmailMessage<'_>) -> Result<(), MailerError> {
+        let timeout = std::time::Duration::from_millis(self.settings.timeout_millis);
+
+        tokio::time::timeout(timeout, self.deliver(email))
+            .await
+            .map_err(|_| MailerError::SmtpError("Timed out talking to SMTP server".to_string()))?
+    }
+}
+
+struct Connection {
+    stream: BufReader<Box<dyn SmtpStream>>,
+}
+
+impl Connection {
+    fn new(stream: Box<dyn SmtpStream>) -> Self {
+        Self { stream: BufReader::new(stream) }
+    }
+
+    fn into_inner(self) -> Box<dyn SmtpStream> {
+        self.stream.into_inner()
+    }
+
+    async fn command(&mut self, line: &str, expected: u16) -> Result<(), MailerError> {
+        self.write(&format!("{}\r\n", line)).await?;
+        self.expect_reply(expected).await
+    }
+
+    /// Sends the message body, dot-stuffed and terminated with a lone `.`
+    async fn send_data(&mut self, data: &str) -> Result<(), MailerError> {
+        let mut body = String::with_capacity(data.len() + 5);
+
+        for line in data.split_terminator("\r\n") {
+            if line.starts_with('.') {
+                body.push('.');
+            }
+            body.push_str(line);
+            body.push_str("\r\n");
+        }
+        body.push_str(".\r\n");
+
+        self.write(&body).await?;
+        self.expect_reply(250).await
+    }
+
+    async fn write(&mut self, data: &str) -> Result<(), MailerError> {
+        let stream = self.stream.get_mut();
+        stream.write_all(data.as_bytes()).await?;
+        stream.flush().await?;
+
+        Ok(())
+    }
+
+    /// Reads a possibly multi-line reply like `250-first` ... `250 last`
+    async fn expect_reply(&mut self, expected: u16) -> Result<(), MailerError> {
+        loop {
+            let mut line = String::new();
+            if self.stream.read_line(&mut line).await? == 0 {
+                return Err(MailerError::SmtpError("SMTP server closed the connection".to_string()))
+            }
+
+            let code = line.get(..3).and_then(|code| code.parse::<u16>().ok());
+            if code != Some(expected) {
+                return Err(MailerError::SmtpError(format!(
+                    "Expected {} from SMTP server, got: {}",
+                    expected,
+                    line.trim_end()
+                )))
+            }
+
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_err, assert_ok};
+    use secrecy::Secret;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    use crate::email_client::{EmailMessage, Mailer, SmtpMailer, SmtpSettings, SmtpTls};
+
+    /// Accepts one connection and answers like a relay, except for
+    /// `RCPT TO` which gets `rcpt_reply`. Returns everything it was sent.
+    async fn fake_smtp_server(rcpt_reply: &'static str) -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port     = listener.local_addr().unwrap().port();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log      = received.clone();
+
+        tokio::spawn(async move {
+            let (stream, _)  = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines    = BufReader::new(read).lines();
+            let mut in_data  = false;
+
+            write.write_all(b"220 fake ESMTP\r\n").await.unwrap();
+
+            while let Ok(Some(line)) = lines.next_line().await {
+                log.lock().unwrap().push(line.clone());
+
+                let reply = if in_data {
+                    if line != "." {
+                        continue
+                    }
+                    in_data = false;
+                    "250 queued\r\n"
+                } else if line.starts_with("EHLO") {
+                    "250-fake\r\n250 AUTH PLAIN\r\n"
+                } else if line.starts_with("AUTH") {
+                    "235 ok\r\n"
+                } else if line.starts_with("RCPT") {
+                    rcpt_reply
+                } else if line == "DATA" {
+                    in_data = true;
+                    "354 go ahead\r\n"
+                } else if line == "QUIT" {
+                    "221 bye\r\n"
+                } else {
+                    "250 ok\r\n"
+                };
+
+                if write.write_all(reply.as_bytes()).await.is_err() {
+                    break
+                }
+            }
+        });
+
+        (port, received)
+    }
+
+    fn mailer(port: u16) -> SmtpMailer {
+        SmtpMailer::new(SmtpSettings {
+            host:           "127.0.0.1".to_string(),
+            port,
+            username:       Some("user".to_string()),
+            password:       Some(Secret::new("pass".to_string())),
+            tls:            SmtpTls::None,
+            timeout_millis: 2000,
+        }).unwrap()
+    }
+
+    fn email() -> EmailMessage<'static> {
+        EmailMessage {
+            from:      "bookings@byot.example.com",
+            to:        "venue@example.com",
+            reply_to:  None,
+            subject:   "Booking inquiry",
+            html_body: "<p>Hi</p>",
+            text_body: "Hi",
+        }
+    }
+
+    #[tokio::test]
+    async fn email_is_delivered_over_smtp() {
+        let (port, received) = fake_smtp_server("250 ok\r\n").await;
+
+        assert_ok!(mailer(port).send(&email()).await);
+
+        let received = received.lock().unwrap();
+        assert_eq!("EHLO byot.example.com", received[0]);
+        // base64 of "\0user\0pass"
+        assert_eq!("AUTH PLAIN AHVzZXIAcGFzcw==", received[1]);
+        assert!(received.contains(&"MAIL FROM:<bookings@byot.example.com>".to_string()));
+        assert!(received.contains(&"RCPT TO:<venue@example.com>".to_string()));
+        assert!(received.contains(&"Subject: Booking inquiry".to_string()));
+        assert_eq!(Some(&"QUIT".to_string()), received.last());
+    }
+
+    #[tokio::test]
+    async fn rejected_recipients_fail_the_send() {
+        let (port, _) = fake_smtp_server("550 no such user\r\n").await;
+
+        assert_err!(mailer(port).send(&email()).await);
+    }
+
+    #[tokio::test]
+    async fn addresses_with_line_breaks_are_not_sent() {
+        let (port, received) = fake_smtp_server("250 ok\r\n").await;
+        let email = EmailMessage { to: "venue@example.com>\r\nRCPT TO:<victim@example.com", ..email() };
+
+        assert_err!(mailer(port).send(&email).await);
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn username_without_password_is_rejected() {
+        let settings = SmtpSettings {
+            host:           "localhost".to_string(),
+            port:           25,
+            username:       Some("user".to_string()),
+            password:       None,
+            tls:            SmtpTls::StartTls,
+            timeout_millis: 2000,
+        };
+
+        assert!(SmtpMailer::new(settings).is_err());
+    }
+
+    #[test]
+    fn credentials_need_tls_unless_the_host_is_local() {
+        let settings = |host: &str, tls| SmtpSettings {
+            host:           host.to_string(),
+            port:           25,
+            username:       Some("user".to_string()),
+            password:       Some(Secret::new("pass".to_string())),
+            tls,
+            timeout_millis: 2000,
+        };
+
+        assert!(SmtpMailer::new(settings("smtp.example.com", SmtpTls::None)).is_err());
+        assert!(SmtpMailer::new(settings("smtp.example.com", SmtpTls::StartTls)).is_ok());
+        assert!(SmtpMailer::new(settings("localhost", SmtpTls::None)).is_ok());
+        assert!(SmtpMailer::new(settings("::1", SmtpTls::None)).is_ok());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::error::TemplateError;
+
+/// Emails the server sends on its own. The wording lives in
+/// `templates/email/<name>` so it can change without touching the code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmailTemplate {
+    ConfirmAccount,
+    OrganisationInvite,
+    ResetPassword,
+}
+
+struct TemplateSource {
+    subject: &'static str,
+    html:    &'static str,
+    text:    &'static str,
+}
+
+const LAYOUT_HTML: &str = include_str!("../../templates/email/layout.html");
+const LAYOUT_TEXT: &str = include_str!("../../templates/email/layout.txt");
+
+macro_rules! template_source {
+    ($name:literal) => {
+        TemplateSource {
+            subject: include_str!(concat!("../../templates/email/", $name, "/subject.txt")),
+            html:    include_str!(concat!("../../templates/email/", $name, "/body.html")),
+            text:    include_str!(concat!("../../templates/email/", $name, "/body.txt")),
+        }
+    };
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject:   String,
+    pub html_body: String,
+    pub text_body: String,
+}
+
+impl EmailTemplate {
+    pub const ALL: [EmailTemplate; 3] = [
+        Self::ConfirmAccount,
+        Self::OrganisationInvite,
+        Self::ResetPassword,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ConfirmAccount => "confirm_account",
+            Self::OrganisationInvite => "organisation_invite",
+            Self::ResetPassword => "reset_password",
+        }
+    }
+
+    fn source(&self) -> TemplateSource {
+        match self {
+            Self::ConfirmAccount => template_source!("confirm_account"),
+            Self::OrganisationInvite => template_source!("organisation_invite"),
+            Self::ResetPassword => template_source!("reset_password"),
+        }
+    }
+
+    /// Fills in the template and wraps both bodies in the shared layout.
+    /// Values are escaped in the HTML body.
+    pub fn render(&self, values: &[(&str, &str)]) -> Result<RenderedEmail, TemplateError> {
+        let source  = self.source();
+        let lookup  = |name: &str| values.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+        let missing = |name: String| TemplateError::MissingValue(self.name(), name);
+
+        let subject = fill(source.subject.trim(), |name| lookup(name).map(str::to_string))
+            .map_err(missing)?;
+        let html    = fill(source.html.trim_end(), |name| lookup(name).map(escape_html))
+            .map_err(missing)?;
+        let text    = fill(source.text.trim_end(), |name| lookup(name).map(str::to_string))
+            .map_err(missing)?;
+
+        let html_body = fill(LAYOUT_HTML, |name| match name {
+            "subject" => Some(escape_html(&subject)),
+            "content" => Some(html.clone()),
+            _ => None,
+        }).map_err(missing)?;
+        let text_body = fill(LAYOUT_TEXT, |name| match name {
+            "subject" => Some(subject.clone()),
+            "content" => Some(text.clone()),
+            _ => None,
+        }).map_err(missing)?;
+
+        Ok(RenderedEmail { subject, html_body, text_body })
+    }
+}
+
+/// Names between `{{` and `}}`, trimmed
+pub fn placeholders(text: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest  = text;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+
+        found.push(after[..end].trim());
+        rest = &after[end + 2..];
+    }
+
+    found
+}
+
+/// Replaces each `{{ name }}` with whatever `lookup` gives for it, which is
+/// expected to do any escaping. Fails with the name of the first placeholder
+/// that has no value.
+pub fn fill(
+    text:   &str,
+    lookup: impl Fn(&str) -> Option<String>
+) -> Result<String, String> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest   = text;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+        let name  = after[..end].trim();
+        let value = lookup(name).ok_or_else(|| name.to_string())?;
+
+        output.push_str(&rest[..start]);
+        output.push_str(&value);
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+pub fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::assert_err;
+    use std::path::PathBuf;
+
+    use crate::email_templates::EmailTemplate;
+
+    fn values(template: EmailTemplate) -> Vec<(&'static str, &'static str)> {
+        match template {
+            EmailTemplate::ConfirmAccount => vec![
+                ("confirmation_link", "https://byot.example.com/confirm?token=abc123"),
+            ],
+            EmailTemplate::OrganisationInvite => vec![
+                ("org_name", "Dogs & <Cats>"),
+                ("invite_link", "https://byot.example.com/user/organisations/accept-invite?token=abc123"),
+            ],
+            EmailTemplate::ResetPassword => vec![
+                ("reset_link", "https://byot.example.com/reset-password?reset_token=abc123"),
+            ],
+        }
+    }
+
+    /// Compares against the file in `snapshots`. Run with `UPDATE_SNAPSHOTS=1`
+    /// to write new ones, then review the diff.
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/email_templates/snapshots")
+            .join(name);
+
+        if std::env::var("UPDATE_SNAPSHOTS").is_ok() {
+            std::fs::write(&path, actual).unwrap();
+            return
+        }
+
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("No snapshot for {}, run with UPDATE_SNAPSHOTS=1", name));
+        assert_eq!(expected, actual, "{} doesn't match its snapshot", name);
+    }
+
+    #[test]
+    fn rendered_templates_match_snapshots() {
+        for template in EmailTemplate::ALL {
+            let rendered = template.render(&values(template)).unwrap();
+            let snapshot = format!("Subject: {}\n\n{}", rendered.subject, rendered.text_body);
+
+            assert_snapshot(&format!("{}.txt", template.name()), &snapshot);
+            assert_snapshot(&format!("{}.html", template.name()), &rendered.html_body);
+        }
+    }
+
+    #[test]
+    fn values_are_escaped_in_html_only() {
+        let rendered = EmailTemplate::OrganisationInvite
+            .render(&values(EmailTemplate::OrganisationInvite))
+            .unwrap();
+
+        assert!(rendered.html_body.contains("join Dogs &amp; &lt;Cats&gt; on"));
+        assert!(rendered.text_body.contains("join Dogs & <Cats> on"));
+    }
+
+    #[test]
+    fn missing_values_fail_to_render() {
+        assert_err!(EmailTemplate::ResetPassword.render(&[]));
+    }
+}
@@ -0,0 +1,206 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::configuration::Settings;
+use crate::domain::UserEmail;
+use crate::email_client::EmailClient;
+use crate::startup::get_connection_pool;
+
+const MAX_ATTEMPTS:      i32 = 8;
+const BASE_BACKOFF_SECS: i64 = 30;
+
+pub enum ExecutionOutcome {
+    TaskCompleted,
+    EmptyQueue,
+}
+
+pub async fn run_worker_until_stopped(configuration: Settings) -> Result<(), anyhow::Error> {
+    let connection_pool = get_connection_pool(&configuration.database);
+    let email_client    = configuration.email_client.client()?;
+
+    worker_loop(connection_pool, email_client).await
+}
+
+async fn worker_loop(
+    pool:         PgPool,
+    email_client: EmailClient
+) -> Result<(), anyhow::Error> {
+    loop {
+        match try_execute_task(&pool, &email_client).await {
+            Ok(ExecutionOutcome::EmptyQueue) => {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            }
+            Err(_) => {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            Ok(ExecutionOutcome::TaskCompleted) => {}
+        }
+    }
+}
+
+#[tracing::instrument(
+    skip_all,
+    fields(
+        email_id  = tracing::field::Empty,
+        recipient = tracing::field::Empty
+    ),
+    err
+)]
+pub async fn try_execute_task(
+    pool:         &PgPool,
+    email_client: &EmailClient
+) -> Result<ExecutionOutcome, anyhow::Error> {
+    let task = dequeue_task(pool).await?;
+    if task.is_none() {
+        return Ok(ExecutionOutcome::EmptyQueue)
+    }
+
+    let (mut transaction, email) = task.unwrap();
+    tracing::Span::current()
+        .record("email_id", &tracing::field::display(email.email_id))
+        .record("recipient", &tracing::field::display(&email.recipient));
+
+    match deliver(email_client, &email).await {
+        Ok(()) => {
+            mark_email_sent(&mut transaction, email.email_id).await?;
+        }
+        Err(e) => {
+            tracing::warn!(
+                error.message = %e,
+                "Failed to send email to {}",
+                email.recipient
+            );
+            record_failure(&mut transaction, email.email_id, email.attempts + 1, &e).await?;
+        }
+    }
+
+    transaction.commit().await?;
+
+    Ok(ExecutionOutcome::TaskCompleted)
+}
+
+/// Seconds to wait before retrying an email that has failed `attempts`
+/// times, doubling each time.
+pub fn backoff_secs(attempts: i32) -> i64 {
+    BASE_BACKOFF_SECS * 2_i64.pow(attempts.saturating_sub(1).clamp(0, 10) as u32)
+}
+
+type PgTransaction = Transaction<'static, Postgres>;
+
+struct QueuedEmail {
+    email_id:  Uuid,
+    recipient: String,
+    reply_to:  Option<String>,
+    subject:   String,
+    html_body: String,
+    text_body: String,
+    attempts:  i32,
+}
+
+async fn deliver(email_client: &EmailClient, email: &QueuedEmail) -> Result<(), String> {
+    let recipient = UserEmail::parse(email.recipient.clone())?;
+    let reply_to  = email.reply_to.clone().map(UserEmail::parse).transpose()?;
+
+    email_client.send_email_with_reply_to(
+        &recipient,
+        reply_to.as_ref(),
+        &email.subject,
+        &email.html_body,
+        &email.text_body
+    ).await
+    .map_err(|e| e.to_string())
+}
+
+#[tracing::instrument(skip_all)]
+async fn dequeue_task(
+    pool: &PgPool
+) -> Result<Option<(PgTransaction, QueuedEmail)>, anyhow::Error> {
+    let mut transaction = pool.begin().await?;
+    let email = sqlx::query_as!(
+        QueuedEmail,
+        r#"
+        SELECT email_id, recipient, reply_to, subject, html_body, text_body, attempts
+        FROM email_outbox
+        WHERE status = 'queued'
+        AND run_after <= current_timestamp
+        ORDER BY run_after
+        FOR UPDATE
+        SKIP LOCKED
+        LIMIT 1
+        "#
+    )
+    .fetch_optional(&mut transaction)
+    .await?;
+
+    Ok(email.map(|email| (transaction, email)))
+}
+
+#[tracing::instrument(skip_all)]
+async fn mark_email_sent(
+    transaction: &mut PgTransaction,
+    email_id:    Uuid
+) -> Result<(), anyhow::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE email_outbox
+        SET
+            status = 'sent',
+            attempts = attempts + 1,
+            last_error = NULL,
+            updated_at = current_timestamp
+        WHERE email_id = $1
+        "#,
+        email_id
+    )
+    .execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+/// Retries with backoff until `MAX_ATTEMPTS`, after which the email is
+/// left as failed for an admin to look at.
+#[tracing::instrument(skip_all)]
+async fn record_failure(
+    transaction: &mut PgTransaction,
+    email_id:    Uuid,
+    attempts:    i32,
+    error:       &str
+) -> Result<(), anyhow::Error> {
+    let status = if attempts >= MAX_ATTEMPTS { "failed" } else { "queued" };
+
+    sqlx::query!(
+        r#"
+        UPDATE email_outbox
+        SET
+            status = $2,
+            attempts = $3,
+            last_error = $4,
+            run_after = current_timestamp + $5 * INTERVAL '1 second',
+            updated_at = current_timestamp
+        WHERE email_id = $1
+        "#,
+        email_id,
+        status,
+        attempts,
+        error,
+        backoff_secs(attempts) as f64
+    )
+    .execute(transaction)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::email_worker::backoff_secs;
+
+    #[test]
+    fn backoff_doubles_after_each_attempt() {
+        assert_eq!(30, backoff_secs(1));
+        assert_eq!(60, backoff_secs(2));
+        assert_eq!(240, backoff_secs(4));
+    }
+}
@@ -3,6 +3,7 @@ use std::convert::From;
 use tokio::task::JoinError;
 
 use crate::auth::AuthError;
+use crate::domain::UserStatus;
 use crate::utils::error_chain_fmt;
 
 #[derive(thiserror::Error)]
@@ -13,6 +14,9 @@ pub enum AdminError {
     #[error("Invalid token")]
     InvalidToken,
 
+    #[error("Two-factor authentication is required for admins")]
+    TwoFactorRequired,
+
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 
@@ -25,6 +29,7 @@ impl ResponseError for AdminError {
         match self {
             Self::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::InvalidToken => StatusCode::UNAUTHORIZED,
+            Self::TwoFactorRequired => StatusCode::FORBIDDEN,
             Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::ValidationError(_) => StatusCode::BAD_REQUEST,
         }
@@ -45,6 +50,9 @@ pub enum ContentError {
     #[error("{0}")]
     DatabaseError(#[from] sqlx::Error),
 
+    #[error("{0}")]
+    RateLimitError(String),
+
     #[error("Could not connect to Redis")]
     RedisError(#[from] redis::RedisError),
 
@@ -60,6 +68,7 @@ impl ResponseError for ContentError {
         match self {
             ContentError::AuthorizationError => StatusCode::UNAUTHORIZED,
             ContentError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ContentError::RateLimitError(_) => StatusCode::TOO_MANY_REQUESTS,
             ContentError::RedisError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ContentError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ContentError::ValidationError(_) => StatusCode::BAD_REQUEST,
@@ -83,6 +92,9 @@ pub enum GeocodingError {
 
     #[error("Something went wrong")]
     UnexpectedError(#[from] anyhow::Error),
+
+    #[error("{0}")]
+    ValidationError(String),
 }
 
 impl std::fmt::Debug for GeocodingError {
@@ -97,15 +109,47 @@ impl ResponseError for GeocodingError {
             GeocodingError::ApiError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             GeocodingError::NoResultsFound(_) => StatusCode::BAD_REQUEST,
             GeocodingError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            GeocodingError::ValidationError(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl From<GeocodingError> for ContentError {
+    fn from(e: GeocodingError) -> Self {
+        match e {
+            GeocodingError::NoResultsFound(_) => ContentError::ValidationError(e.to_string()),
+            GeocodingError::ValidationError(message) => ContentError::ValidationError(message),
+            e => ContentError::UnexpectedError(e.into()),
         }
     }
 }
 
 #[derive(thiserror::Error)]
 pub enum LoginError {
+    #[error("This account has been banned")]
+    AccountBanned,
+
+    #[error("Please confirm your email before logging in")]
+    AccountPending,
+
+    #[error("This account has been suspended")]
+    AccountSuspended,
+
     #[error("Authentication failed")]
     AuthError(#[from] AuthError),
 
+    #[error("Session has expired, please log in again")]
+    InvalidRefreshToken,
+
+    #[error("Login has expired, please log in again")]
+    InvalidTwoFactorChallenge,
+
+    #[error("Invalid two-factor code")]
+    InvalidTwoFactorCode,
+
+    #[error("Too many wrong two-factor codes, please try again later")]
+    TwoFactorLocked,
+
     #[error("Something went wrong")]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -113,18 +157,55 @@ pub enum LoginError {
 impl ResponseError for LoginError {
     fn status_code(&self) -> StatusCode {
         match self {
+            LoginError::AccountBanned => StatusCode::FORBIDDEN,
+            LoginError::AccountPending => StatusCode::FORBIDDEN,
+            LoginError::AccountSuspended => StatusCode::FORBIDDEN,
             LoginError::AuthError(_) => StatusCode::UNAUTHORIZED,
+            LoginError::InvalidRefreshToken => StatusCode::UNAUTHORIZED,
+            LoginError::InvalidTwoFactorChallenge => StatusCode::UNAUTHORIZED,
+            LoginError::InvalidTwoFactorCode => StatusCode::UNAUTHORIZED,
+            LoginError::TwoFactorLocked => StatusCode::TOO_MANY_REQUESTS,
             LoginError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR
         }
     }
 }
 
+impl LoginError {
+    /// The error for an account that isn't allowed in, if it isn't
+    pub fn for_status(status: UserStatus) -> Option<Self> {
+        match status {
+            UserStatus::Pending => Some(LoginError::AccountPending),
+            UserStatus::Confirmed => None,
+            UserStatus::Suspended => Some(LoginError::AccountSuspended),
+            UserStatus::Banned => Some(LoginError::AccountBanned),
+        }
+    }
+}
+
 impl std::fmt::Debug for LoginError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         error_chain_fmt(self, f)
     }
 }
 
+#[derive(thiserror::Error)]
+pub enum MailerError {
+    #[error("An error occurred while contacting the API: {0}")]
+    ApiError(#[from] reqwest::Error),
+
+    #[error("Failed to write email: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("{0}")]
+    SmtpError(String),
+}
+
+impl std::fmt::Debug for MailerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
 #[derive(thiserror::Error)]
 pub enum RegisterError {
     #[error("Authentication failed")]
@@ -159,6 +240,45 @@ impl From<JoinError> for RegisterError {
     }
 }
 
+#[derive(thiserror::Error)]
+pub enum RoutingError {
+    #[error("An error occurred while contacting the routing server: {0}")]
+    ApiError(#[from] reqwest::Error),
+
+    #[error("No route found: {0}")]
+    NoRouteFound(String),
+
+    #[error("Something went wrong")]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl std::fmt::Debug for RoutingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl From<RoutingError> for ContentError {
+    fn from(e: RoutingError) -> Self {
+        match e {
+            RoutingError::NoRouteFound(message) => ContentError::ValidationError(message),
+            e => ContentError::UnexpectedError(e.into()),
+        }
+    }
+}
+
+#[derive(thiserror::Error)]
+pub enum TemplateError {
+    #[error("No value for {{{{{1}}}}} in the {0} email template")]
+    MissingValue(&'static str, String),
+}
+
+impl std::fmt::Debug for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
 #[derive(thiserror::Error)]
 pub enum TokenError {
     #[error("{0}")] 
@@ -167,6 +287,9 @@ pub enum TokenError {
     #[error("Invalid token")]
     InvalidToken,
 
+    #[error("{0}")]
+    RateLimitError(String),
+
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 
@@ -182,6 +305,7 @@ impl ResponseError for TokenError {
         match self {
             Self::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::InvalidToken => StatusCode::UNAUTHORIZED,
+            Self::RateLimitError(_) => StatusCode::TOO_MANY_REQUESTS,
             Self::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::UnknownEmail => StatusCode::UNAUTHORIZED,
             Self::ValidationError(_) => StatusCode::BAD_REQUEST,
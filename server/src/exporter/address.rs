@@ -0,0 +1,29 @@
+/// Joins the parts of an address that are present, skipping blanks so a
+/// missing `state` doesn't leave a dangling separator.
+pub fn join_address(parts: &[Option<&str>]) -> String {
+    parts.iter()
+        .flatten()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exporter::join_address;
+
+    #[test]
+    fn full_address_is_joined_with_commas() {
+        let parts = [Some("16 Lexington"), Some("Asheville"), Some("NC"), Some("28806")];
+
+        assert_eq!(join_address(&parts), "16 Lexington, Asheville, NC, 28806");
+    }
+
+    #[test]
+    fn missing_and_blank_parts_are_skipped() {
+        let parts = [None, Some("Asheville"), Some("  "), Some("USA")];
+
+        assert_eq!(join_address(&parts), "Asheville, USA");
+    }
+}
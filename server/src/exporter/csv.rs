@@ -0,0 +1,68 @@
+use std::borrow::Cow;
+
+/// What spreadsheets take as the start of a formula
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// Venue and contact names come from users, so a field a spreadsheet would
+/// run as a formula gets a leading `'` and is shown as text instead
+pub fn spreadsheet_safe(field: &str) -> Cow<'_, str> {
+    if field.starts_with(FORMULA_PREFIXES) {
+        Cow::Owned(format!("'{}", field))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// One CRLF terminated row, quoted as RFC 4180 needs
+pub fn csv_record<S: AsRef<str>>(fields: &[S]) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .terminator(csv::Terminator::CRLF)
+        .from_writer(Vec::new());
+
+    writer
+        .write_record(fields.iter().map(|f| spreadsheet_safe(f.as_ref()).into_owned()))
+        .expect("Writing to a Vec can't fail");
+
+    let record = writer.into_inner().expect("Writing to a Vec can't fail");
+
+    String::from_utf8(record).expect("Every field was a str")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exporter::csv_record;
+
+    #[test]
+    fn plain_fields_are_unchanged() {
+        assert_eq!(csv_record(&["Static Age", "2024-06-03"]), "Static Age,2024-06-03\r\n");
+    }
+
+    #[test]
+    fn fields_with_commas_are_quoted() {
+        assert_eq!(csv_record(&["Brooklyn, NY"]), "\"Brooklyn, NY\"\r\n");
+    }
+
+    #[test]
+    fn quotes_are_doubled() {
+        assert_eq!(csv_record(&["The \"Pit\""]), "\"The \"\"Pit\"\"\"\r\n");
+    }
+
+    #[test]
+    fn line_breaks_are_quoted() {
+        assert_eq!(csv_record(&["line\nbreak"]), "\"line\nbreak\"\r\n");
+    }
+
+    #[test]
+    fn record_ends_with_crlf() {
+        assert_eq!(csv_record(&["a", "b,c", ""]), "a,\"b,c\",\r\n");
+    }
+
+    #[test]
+    fn formulas_are_escaped() {
+        assert_eq!(
+            csv_record(&["=HYPERLINK(\"http://evil\")", "+1", "-1", "@SUM(A1)", "\tx"]),
+            "\"'=HYPERLINK(\"\"http://evil\"\")\",'+1,'-1,'@SUM(A1),'\tx\r\n"
+        );
+        assert_eq!(csv_record(&["\r=1"]), "\"'\r=1\"\r\n");
+    }
+}
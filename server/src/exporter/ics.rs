@@ -0,0 +1,194 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::domain::{BookingStatus, ExportedDate};
+use crate::exporter::{join_address, TourExporter};
+
+const LINE_ENDING: &str = "\r\n";
+/// Lines longer than this many bytes have to be folded (RFC 5545 3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// iCalendar feed with an all-day event per booked date. Pending bookings
+/// are marked tentative.
+pub struct IcsExporter {
+    calendar_name: String,
+    stamp:         DateTime<Utc>,
+}
+
+impl IcsExporter {
+    pub fn new(calendar_name: String) -> Self {
+        Self {
+            calendar_name,
+            stamp: Utc::now(),
+        }
+    }
+}
+
+impl TourExporter for IcsExporter {
+    fn content_type(&self) -> &'static str {
+        "text/calendar; charset=utf-8"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "ics"
+    }
+
+    fn header(&self) -> Option<String> {
+        Some(ics_lines(&[
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//byot//tour calendar//EN".to_string(),
+            "CALSCALE:GREGORIAN".to_string(),
+            "METHOD:PUBLISH".to_string(),
+            format!("X-WR-CALNAME:{}", escape_ics_text(&self.calendar_name)),
+        ]))
+    }
+
+    fn row(&self, date: &ExportedDate) -> String {
+        let location = join_address(&[
+            date.address.as_deref(),
+            Some(date.city.as_str()),
+            date.state.as_deref(),
+            date.zip_code.as_deref(),
+            date.country.as_deref(),
+        ]);
+        let status = match date.status {
+            BookingStatus::Confirmed => "CONFIRMED",
+            _ => "TENTATIVE",
+        };
+
+        let mut lines = vec![
+            "BEGIN:VEVENT".to_string(),
+            // Stable across exports so calendars update events in place
+            format!("UID:{}-{}@byot", date.tour_date_id, date.contact_id),
+            format!("DTSTAMP:{}", self.stamp.format("%Y%m%dT%H%M%SZ")),
+            format!("DTSTART;VALUE=DATE:{}", date.show_date.format("%Y%m%d")),
+            format!("DTEND;VALUE=DATE:{}", (date.show_date + Duration::days(1)).format("%Y%m%d")),
+            format!("SUMMARY:{}", escape_ics_text(&date.display_name)),
+            format!("LOCATION:{}", escape_ics_text(&location)),
+            format!("STATUS:{}", status),
+        ];
+
+        if let (Some(lat), Some(lng)) = (date.latitude, date.longitude) {
+            lines.push(format!("GEO:{};{}", lat, lng));
+        }
+
+        lines.push("END:VEVENT".to_string());
+
+        ics_lines(&lines)
+    }
+
+    fn footer(&self) -> Option<String> {
+        Some(ics_lines(&["END:VCALENDAR".to_string()]))
+    }
+}
+
+/// Escapes backslashes, separators and line breaks in TEXT values
+pub fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Splits a content line into 75 byte chunks, continuing each with a space.
+/// Never splits inside a multi-byte character.
+pub fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width  = 0;
+
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str(LINE_ENDING);
+            folded.push(' ');
+            // The leading space counts towards the next line
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+
+    folded
+}
+
+fn ics_lines(lines: &[String]) -> String {
+    lines.iter()
+        .map(|l| format!("{}{}", fold_ics_line(l), LINE_ENDING))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    use crate::domain::{BookingStatus, ExportedDate};
+    use crate::exporter::{escape_ics_text, fold_ics_line, IcsExporter, TourExporter};
+
+    fn date(status: BookingStatus, latitude: Option<f32>) -> ExportedDate {
+        ExportedDate {
+            tour_date_id: Uuid::nil(),
+            contact_id:   7,
+            status,
+            show_date:    NaiveDate::from_ymd_opt(2024, 6, 30).unwrap(),
+            display_name: "The Mothlight, Asheville".to_string(),
+            address:      Some("701 Haywood Rd".to_string()),
+            city:         "Asheville".to_string(),
+            state:        Some("NC".to_string()),
+            zip_code:     None,
+            country:      None,
+            latitude,
+            longitude:    Some(-82.5),
+        }
+    }
+
+    #[test]
+    fn confirmed_date_is_an_all_day_event() {
+        let event = IcsExporter::new("summer".to_string())
+            .row(&date(BookingStatus::Confirmed, Some(35.5)));
+
+        assert!(event.starts_with("BEGIN:VEVENT\r\n"));
+        assert!(event.contains("UID:00000000-0000-0000-0000-000000000000-7@byot\r\n"));
+        assert!(event.contains("DTSTART;VALUE=DATE:20240630\r\n"));
+        assert!(event.contains("DTEND;VALUE=DATE:20240701\r\n"));
+        assert!(event.contains("SUMMARY:The Mothlight\\, Asheville\r\n"));
+        assert!(event.contains("LOCATION:701 Haywood Rd\\, Asheville\\, NC\r\n"));
+        assert!(event.contains("GEO:35.5;-82.5\r\n"));
+        assert!(event.contains("STATUS:CONFIRMED\r\n"));
+        assert!(event.ends_with("END:VEVENT\r\n"));
+    }
+
+    #[test]
+    fn pending_date_is_tentative_and_geo_needs_both_coordinates() {
+        let event = IcsExporter::new("summer".to_string())
+            .row(&date(BookingStatus::Pending, None));
+
+        assert!(event.contains("STATUS:TENTATIVE\r\n"));
+        assert!(!event.contains("GEO:"));
+    }
+
+    #[test]
+    fn calendar_is_wrapped_in_vcalendar() {
+        let exporter = IcsExporter::new("summer; 2024".to_string());
+
+        assert!(exporter.header().unwrap().starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(exporter.header().unwrap().contains("X-WR-CALNAME:summer\\; 2024\r\n"));
+        assert_eq!(exporter.footer().unwrap(), "END:VCALENDAR\r\n");
+    }
+
+    #[test]
+    fn text_values_are_escaped() {
+        assert_eq!(escape_ics_text("a\\b;c,d\ne"), "a\\\\b\\;c\\,d\\ne");
+    }
+
+    #[test]
+    fn long_lines_are_folded_on_character_boundaries() {
+        let line   = format!("SUMMARY:{}", "é".repeat(60));
+        let folded = fold_ics_line(&line);
+
+        for part in folded.split("\r\n") {
+            assert!(part.len() <= 75);
+        }
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
@@ -0,0 +1,28 @@
+mod address;
+mod csv;
+mod ics;
+mod songkick;
+
+pub use address::*;
+pub use csv::*;
+pub use ics::*;
+pub use songkick::*;
+
+use crate::domain::ExportedDate;
+
+/// A file format that confirmed tour dates can be exported to.
+pub trait TourExporter {
+    fn content_type(&self) -> &'static str;
+
+    fn file_extension(&self) -> &'static str;
+
+    /// Written once before any dates, e.g. a CSV header row
+    fn header(&self) -> Option<String>;
+
+    fn row(&self, date: &ExportedDate) -> String;
+
+    /// Written once after every date, e.g. a closing tag
+    fn footer(&self) -> Option<String> {
+        None
+    }
+}
@@ -0,0 +1,76 @@
+use crate::domain::ExportedDate;
+use crate::exporter::{csv_record, TourExporter};
+
+const SONGKICK_COLUMNS: [&str; 7] = [
+    "Date", "Venue Name", "Address", "City", "State", "Zip", "Country"
+];
+
+/// Matches the column layout of Songkick's event import spreadsheet
+pub struct SongkickExporter;
+
+impl TourExporter for SongkickExporter {
+    fn content_type(&self) -> &'static str {
+        "text/csv; charset=utf-8"
+    }
+
+    fn file_extension(&self) -> &'static str {
+        "csv"
+    }
+
+    fn header(&self) -> Option<String> {
+        Some(csv_record(&SONGKICK_COLUMNS))
+    }
+
+    fn row(&self, date: &ExportedDate) -> String {
+        let show_date = date.show_date.format("%Y-%m-%d").to_string();
+
+        csv_record(&[
+            show_date.as_str(),
+            &date.display_name,
+            date.address.as_deref().unwrap_or_default(),
+            &date.city,
+            date.state.as_deref().unwrap_or_default(),
+            date.zip_code.as_deref().unwrap_or_default(),
+            date.country.as_deref().unwrap_or_default(),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    use crate::domain::{BookingStatus, ExportedDate};
+    use crate::exporter::{SongkickExporter, TourExporter};
+
+    #[test]
+    fn header_matches_songkick_layout() {
+        let header = SongkickExporter.header().unwrap();
+
+        assert_eq!(header, "Date,Venue Name,Address,City,State,Zip,Country\r\n");
+    }
+
+    #[test]
+    fn missing_address_parts_are_left_blank() {
+        let date = ExportedDate {
+            tour_date_id: Uuid::new_v4(),
+            contact_id:   1,
+            status:       BookingStatus::Confirmed,
+            show_date:    NaiveDate::from_ymd_opt(2024, 6, 3).unwrap(),
+            display_name: "St. Vitus".to_string(),
+            address:      Some("1120 Manhattan Ave".to_string()),
+            city:         "Brooklyn".to_string(),
+            state:        Some("NY".to_string()),
+            zip_code:     None,
+            country:      None,
+            latitude:     None,
+            longitude:    None,
+        };
+
+        assert_eq!(
+            SongkickExporter.row(&date),
+            "2024-06-03,St. Vitus,1120 Manhattan Ave,Brooklyn,NY,,\r\n"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+mod nominatim;
+mod offline;
+
+pub use nominatim::*;
+pub use offline::*;
+
+use serde::{Deserialize, Serialize};
+use serde_aux::field_attributes::deserialize_number_from_string;
+use std::sync::Arc;
+
+use crate::error::GeocodingError;
+use crate::gmaps_api_client::{GoogleMapsAPIClient, Location};
+
+/// Turns a free-text address into coordinates.
+#[async_trait::async_trait]
+pub trait Geocoder: Send + Sync {
+    async fn geocode(&self, address: &str) -> Result<GeocodeResult, GeocodingError>;
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GeocodeResult {
+    pub location:          Location,
+    pub formatted_address: String,
+    /// ISO 3166-1 alpha-2, upper case
+    pub country:           Option<String>,
+    pub place_id:          Option<String>,
+    /// 0.0 for a rough guess up to 1.0 for an exact match
+    pub confidence:        f32,
+    #[serde(default)]
+    pub components:        AddressComponents,
+}
+
+/// Normalised pieces of a geocoded address. Regions use their short code
+/// where the backend has one, e.g. "NC" rather than "North Carolina".
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressComponents {
+    pub locality:     Option<String>,
+    pub region:       Option<String>,
+    pub region_name:  Option<String>,
+    pub postal_code:  Option<String>,
+    pub country_name: Option<String>,
+}
+
+/// Which geocoder to use, picked with `backend`.
+#[derive(serde::Deserialize, Clone)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum GeocoderSettings {
+    Google(GoogleMapsAPIClient),
+    Nominatim {
+        base_url:       String,
+        user_agent:     String,
+        #[serde(deserialize_with = "deserialize_number_from_string")]
+        timeout_millis: u64,
+    },
+    Offline {
+        path: String,
+    },
+}
+
+impl GeocoderSettings {
+    pub fn build(self) -> Result<Arc<dyn Geocoder>, anyhow::Error> {
+        let geocoder: Arc<dyn Geocoder> = match self {
+            Self::Google(client) => Arc::new(client),
+            Self::Nominatim { base_url, user_agent, timeout_millis } => Arc::new(
+                NominatimClient::new(
+                    base_url,
+                    user_agent,
+                    std::time::Duration::from_millis(timeout_millis)
+                )
+            ),
+            Self::Offline { path } => Arc::new(OfflineGeocoder::from_file(&path)?),
+        };
+
+        Ok(geocoder)
+    }
+}
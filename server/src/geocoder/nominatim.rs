@@ -0,0 +1,204 @@
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::error::GeocodingError;
+use crate::geocoder::{AddressComponents, GeocodeResult, Geocoder};
+use crate::gmaps_api_client::Location;
+
+/// Client for the Nominatim search API, either the public instance or a
+/// self-hosted one. The public instance requires a descriptive user agent.
+pub struct NominatimClient {
+    http_client: Client,
+    base_url:    String,
+}
+
+impl NominatimClient {
+    pub fn new(
+        base_url:   String,
+        user_agent: String,
+        timeout:    std::time::Duration
+    ) -> NominatimClient {
+        let http_client = Client::builder()
+            .user_agent(user_agent)
+            .timeout(timeout)
+            .build()
+            .unwrap();
+        Self {
+            http_client,
+            base_url,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Geocoder for NominatimClient {
+    async fn geocode(&self, address: &str) -> Result<GeocodeResult, GeocodingError> {
+        let url     = format!("{}/search", self.base_url);
+        let results = self.http_client
+            .get(&url)
+            .query(&[
+                ("q", address),
+                ("format", "jsonv2"),
+                ("addressdetails", "1"),
+                ("limit", "1"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<SearchResult>>()
+            .await?;
+
+        let result = results.into_iter()
+            .next()
+            .ok_or_else(|| GeocodingError::NoResultsFound(address.to_string()))?;
+
+        result.try_into()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    place_id:     u64,
+    lat:          String,
+    lon:          String,
+    display_name: String,
+    #[serde(default)]
+    importance:   Option<f32>,
+    #[serde(default)]
+    address:      Option<SearchAddress>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SearchAddress {
+    city:         Option<String>,
+    town:         Option<String>,
+    village:      Option<String>,
+    hamlet:       Option<String>,
+    state:        Option<String>,
+    postcode:     Option<String>,
+    country:      Option<String>,
+    country_code: Option<String>,
+    /// e.g. "US-NC"
+    #[serde(rename = "ISO3166-2-lvl4")]
+    region_code:  Option<String>,
+}
+
+impl SearchAddress {
+    fn components(self) -> AddressComponents {
+        // Nominatim only sends an ISO region code for some countries
+        let region = self.region_code
+            .as_deref()
+            .and_then(|c| c.split_once('-'))
+            .map(|(_, region)| region.to_string())
+            .or_else(|| self.state.clone());
+
+        AddressComponents {
+            locality:     self.city.or(self.town).or(self.village).or(self.hamlet),
+            region,
+            region_name:  self.state,
+            postal_code:  self.postcode,
+            country_name: self.country,
+        }
+    }
+}
+
+impl TryFrom<SearchResult> for GeocodeResult {
+    type Error = GeocodingError;
+
+    fn try_from(value: SearchResult) -> Result<Self, Self::Error> {
+        // Nominatim sends coordinates as strings
+        let parse = |s: &str| s.parse::<f32>()
+            .map_err(|_| anyhow::anyhow!("Invalid coordinate {} from Nominatim", s));
+        let location = Location { lat: parse(&value.lat)?, lng: parse(&value.lon)? };
+        let address  = value.address.unwrap_or_default();
+
+        Ok(Self {
+            location,
+            formatted_address: value.display_name,
+            country:           address.country_code.as_ref().map(|c| c.to_uppercase()),
+            place_id:          Some(value.place_id.to_string()),
+            confidence:        value.importance.unwrap_or_default().clamp(0.0, 1.0),
+            components:        address.components(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_err, assert_ok};
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::error::GeocodingError;
+    use crate::geocoder::{Geocoder, NominatimClient};
+
+    fn client(base_url: String) -> NominatimClient {
+        NominatimClient::new(base_url, "byot-test".to_string(), std::time::Duration::from_millis(200))
+    }
+
+    #[tokio::test]
+    async fn geocode_parses_first_result() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("q", "16 Lexington Ave, Asheville"))
+            .and(query_param("format", "jsonv2"))
+            .and(header("User-Agent", "byot-test"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                "place_id":     1234,
+                "lat":          "35.5951",
+                "lon":          "-82.5515",
+                "display_name": "16, Lexington Avenue, Asheville, North Carolina, 28801, United States",
+                "importance":   0.41,
+                "address":      {
+                    "city":           "Asheville",
+                    "state":          "North Carolina",
+                    "ISO3166-2-lvl4": "US-NC",
+                    "postcode":       "28801",
+                    "country":        "United States",
+                    "country_code":   "us"
+                }
+            }])))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = client(mock_server.uri()).geocode("16 Lexington Ave, Asheville").await;
+
+        assert_ok!(&result);
+        let result = result.unwrap();
+        assert_eq!(35.5951, result.location.lat);
+        assert_eq!(Some("US".to_string()), result.country);
+        assert_eq!(Some("1234".to_string()), result.place_id);
+        assert_eq!(Some("NC".to_string()), result.components.region);
+        assert_eq!(Some("North Carolina".to_string()), result.components.region_name);
+        assert_eq!(Some("28801".to_string()), result.components.postal_code);
+    }
+
+    #[tokio::test]
+    async fn geocode_fails_without_results() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .mount(&mock_server)
+            .await;
+
+        let result = client(mock_server.uri()).geocode("nowhere").await;
+
+        assert!(matches!(result, Err(GeocodingError::NoResultsFound(_))));
+    }
+
+    #[tokio::test]
+    async fn geocode_fails_if_the_server_returns_500() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+
+        assert_err!(client(mock_server.uri()).geocode("Asheville").await);
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use crate::error::GeocodingError;
+use crate::geocoder::{GeocodeResult, Geocoder};
+
+/// Answers lookups from a JSON file mapping addresses to results, so
+/// development and CI don't need network access or an API key. Addresses
+/// are matched case-insensitively and ignoring extra whitespace.
+///
+/// ```json
+/// {
+///   "asheville": {
+///     "location": { "lat": 35.5951, "lng": -82.5515 },
+///     "formattedAddress": "Asheville, NC, USA",
+///     "country": "US",
+///     "placeId": null,
+///     "confidence": 0.5,
+///     "components": { "locality": "Asheville", "region": "NC" }
+///   }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct OfflineGeocoder {
+    results: HashMap<String, GeocodeResult>,
+}
+
+impl OfflineGeocoder {
+    pub fn new(results: HashMap<String, GeocodeResult>) -> Self {
+        let results = results
+            .into_iter()
+            .map(|(address, result)| (normalise(&address), result))
+            .collect();
+
+        Self { results }
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, anyhow::Error> {
+        let file    = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Could not read geocoder fixtures from {}: {}", path, e))?;
+        let results = serde_json::from_str(&file)?;
+
+        Ok(Self::new(results))
+    }
+}
+
+#[async_trait::async_trait]
+impl Geocoder for OfflineGeocoder {
+    async fn geocode(&self, address: &str) -> Result<GeocodeResult, GeocodingError> {
+        self.results
+            .get(&normalise(address))
+            .cloned()
+            .ok_or_else(|| GeocodingError::NoResultsFound(address.to_string()))
+    }
+}
+
+fn normalise(address: &str) -> String {
+    address
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_err, assert_ok};
+    use std::collections::HashMap;
+
+    use crate::geocoder::{GeocodeResult, Geocoder, OfflineGeocoder};
+    use crate::gmaps_api_client::Location;
+
+    fn geocoder() -> OfflineGeocoder {
+        let result = GeocodeResult {
+            location:          Location { lat: 35.5951, lng: -82.5515 },
+            formatted_address: "Asheville, NC, USA".to_string(),
+            country:           Some("US".to_string()),
+            place_id:          None,
+            confidence:        0.5,
+            components:        Default::default(),
+        };
+
+        OfflineGeocoder::new(HashMap::from([("Asheville, NC".to_string(), result)]))
+    }
+
+    #[tokio::test]
+    async fn lookup_ignores_case_and_spacing() {
+        assert_ok!(geocoder().geocode("  asheville,   nc").await);
+    }
+
+    #[tokio::test]
+    async fn unknown_address_has_no_results() {
+        assert_err!(geocoder().geocode("Richmond, VA").await);
+    }
+
+    #[test]
+    fn bundled_fixture_file_loads() {
+        assert_ok!(OfflineGeocoder::from_file("configuration/geocoder_fixtures.json"));
+    }
+
+    #[test]
+    fn missing_fixture_file_is_an_error() {
+        assert_err!(OfflineGeocoder::from_file("does/not/exist.json"));
+    }
+}
@@ -0,0 +1,238 @@
+use sqlx::{PgPool, Postgres, Transaction, types::chrono::NaiveDateTime};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::configuration::Settings;
+use crate::domain::{address_mismatches, query_contact_address, save_geocode_result};
+use crate::error::GeocodingError;
+use crate::geocoder::Geocoder;
+use crate::startup::get_connection_pool;
+
+const MAX_ATTEMPTS:       i32 = 5;
+const BASE_BACKOFF_SECS:  i64 = 60;
+/// How long a worker has a job to itself. Well past any geocoder timeout,
+/// and if the worker dies the job is picked up again after this.
+const LEASE_SECS:         i64 = 300;
+
+pub enum ExecutionOutcome {
+    TaskCompleted,
+    EmptyQueue,
+}
+
+pub async fn run_worker_until_stopped(configuration: Settings) -> Result<(), anyhow::Error> {
+    let connection_pool = get_connection_pool(&configuration.database);
+    let geocoder        = configuration.geocoder.build()?;
+
+    worker_loop(connection_pool, geocoder).await
+}
+
+async fn worker_loop(
+    pool:     PgPool,
+    geocoder: Arc<dyn Geocoder>
+) -> Result<(), anyhow::Error> {
+    loop {
+        match try_execute_task(&pool, geocoder.as_ref()).await {
+            Ok(ExecutionOutcome::EmptyQueue) => {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            }
+            Err(_) => {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            Ok(ExecutionOutcome::TaskCompleted) => {}
+        }
+    }
+}
+
+#[tracing::instrument(
+    skip_all,
+    fields(
+        job_id     = tracing::field::Empty,
+        contact_id = tracing::field::Empty
+    ),
+    err
+)]
+pub async fn try_execute_task(
+    pool:     &PgPool,
+    geocoder: &dyn Geocoder
+) -> Result<ExecutionOutcome, anyhow::Error> {
+    let task = claim_task(pool).await?;
+    if task.is_none() {
+        return Ok(ExecutionOutcome::EmptyQueue)
+    }
+
+    let job = task.unwrap();
+    tracing::Span::current()
+        .record("job_id", &tracing::field::display(job.job_id))
+        .record("contact_id", &tracing::field::display(job.contact_id));
+
+    // The job is deleted along with its contact, so this should always be found
+    let typed   = query_contact_address(&job.contact_id, pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Contact {} no longer exists", job.contact_id))?;
+    let address = typed.full_address();
+
+    // No transaction is open, so a slow geocoder doesn't hold a connection
+    // or a lock
+    let outcome = geocoder.geocode(&address).await;
+
+    let mut transaction = pool.begin().await?;
+    let still_claimed   = match outcome {
+        Ok(result) => {
+            let claimed = mark_job_done(&mut transaction, &job).await?;
+            if claimed {
+                let mismatches = address_mismatches(&typed, &result);
+                save_geocode_result(&job.contact_id, &result, &mismatches, &mut transaction).await?;
+            }
+
+            claimed
+        }
+        Err(e) => {
+            tracing::warn!(
+                error.cause_chain = ?e,
+                error.message     = %e,
+                "Failed to geocode {}",
+                address
+            );
+            record_failure(&mut transaction, &job, job.attempts + 1, &e).await?
+        }
+    };
+
+    if still_claimed {
+        transaction.commit().await?;
+    } else {
+        tracing::info!("Job was re-queued while geocoding, so the result is dropped");
+    }
+
+    Ok(ExecutionOutcome::TaskCompleted)
+}
+
+/// Seconds to wait before retrying a job that has failed `attempts` times,
+/// doubling each time.
+pub fn backoff_secs(attempts: i32) -> i64 {
+    BASE_BACKOFF_SECS * 2_i64.pow(attempts.saturating_sub(1).clamp(0, 10) as u32)
+}
+
+type PgTransaction = Transaction<'static, Postgres>;
+
+struct ClaimedJob {
+    job_id:       Uuid,
+    contact_id:   i32,
+    attempts:     i32,
+    locked_until: NaiveDateTime,
+}
+
+/// Takes the next due job for `LEASE_SECS` and commits straight away. The
+/// result is written back in a second transaction, once the geocoder answers.
+#[tracing::instrument(skip_all)]
+async fn claim_task(
+    pool: &PgPool
+) -> Result<Option<ClaimedJob>, anyhow::Error> {
+    let job = sqlx::query_as!(
+        ClaimedJob,
+        r#"
+        UPDATE geocoding_jobs
+        SET
+            locked_until = current_timestamp + $1 * INTERVAL '1 second',
+            updated_at = current_timestamp
+        WHERE job_id = (
+            SELECT job_id
+            FROM geocoding_jobs
+            WHERE status = 'queued'
+            AND run_after <= current_timestamp
+            AND (locked_until IS NULL OR locked_until <= current_timestamp)
+            ORDER BY run_after
+            FOR UPDATE
+            SKIP LOCKED
+            LIMIT 1
+        )
+        RETURNING job_id, contact_id, attempts, locked_until AS "locked_until!"
+        "#,
+        LEASE_SECS as f64
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(job)
+}
+
+/// False if the job isn't ours any more, e.g. it was re-queued
+#[tracing::instrument(skip_all)]
+async fn mark_job_done(
+    transaction: &mut PgTransaction,
+    job:         &ClaimedJob
+) -> Result<bool, anyhow::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE geocoding_jobs
+        SET
+            status = 'done',
+            attempts = attempts + 1,
+            last_error = NULL,
+            locked_until = NULL,
+            updated_at = current_timestamp
+        WHERE job_id = $1 AND locked_until = $2
+        "#,
+        job.job_id,
+        job.locked_until
+    )
+    .execute(transaction)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Addresses the geocoder can't find won't start resolving on their own, so
+/// those fail straight away. Anything else is retried until `MAX_ATTEMPTS`.
+/// False if the job isn't ours any more.
+#[tracing::instrument(skip_all)]
+async fn record_failure(
+    transaction: &mut PgTransaction,
+    job:         &ClaimedJob,
+    attempts:    i32,
+    error:       &GeocodingError
+) -> Result<bool, anyhow::Error> {
+    let give_up = attempts >= MAX_ATTEMPTS || matches!(error, GeocodingError::NoResultsFound(_));
+    let status  = if give_up { "failed" } else { "queued" };
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE geocoding_jobs
+        SET
+            status = $3,
+            attempts = $4,
+            last_error = $5,
+            run_after = current_timestamp + $6 * INTERVAL '1 second',
+            locked_until = NULL,
+            updated_at = current_timestamp
+        WHERE job_id = $1 AND locked_until = $2
+        "#,
+        job.job_id,
+        job.locked_until,
+        status,
+        attempts,
+        error.to_string(),
+        backoff_secs(attempts) as f64
+    )
+    .execute(transaction)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geocoding_worker::backoff_secs;
+
+    #[test]
+    fn backoff_doubles_after_each_attempt() {
+        assert_eq!(60, backoff_secs(1));
+        assert_eq!(120, backoff_secs(2));
+        assert_eq!(480, backoff_secs(4));
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        assert_eq!(backoff_secs(11), backoff_secs(50));
+    }
+}
@@ -3,35 +3,12 @@ use serde::{Deserialize, Serialize};
 use urlencoding::encode;
 
 use crate::error::{GeocodingError};
-
-pub async fn get_latlng_from_address(
-    gmaps_api_client: &GoogleMapsAPIClient,
-    address:          &str
-) -> Result<Location, GeocodingError> {
-    let encoded = encode(&address);
-    let url     = format!(
-        "{}?address={}&key={}",
-        gmaps_api_client.api_url,
-        encoded,
-        gmaps_api_client.api_key.expose_secret()
-    );
-    let response: ApiResponse = reqwest::get(url)
-        .await?
-        .json()
-        .await?;
-    
-    if let Some(result) = response.results.first() {
-        Ok(result.geometry.location)
-    } else {
-        let e = format!("No results found for {}", &address);
-        Err(GeocodingError::NoResultsFound(e))
-    }
-}
+use crate::geocoder::{AddressComponents, GeocodeResult, Geocoder};
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct GoogleMapsAPIClient {
-    api_url: String,
-    api_key: Secret<String>,
+    pub api_url: String,
+    pub api_key: Secret<String>,
 }
 
 impl GoogleMapsAPIClient {
@@ -46,11 +23,35 @@ impl GoogleMapsAPIClient {
     }
 }
 
-#[allow(dead_code)]
+#[async_trait::async_trait]
+impl Geocoder for GoogleMapsAPIClient {
+    async fn geocode(&self, address: &str) -> Result<GeocodeResult, GeocodingError> {
+        let encoded = encode(address);
+        let url     = format!(
+            "{}?address={}&key={}",
+            self.api_url,
+            encoded,
+            self.api_key.expose_secret()
+        );
+        let response: ApiResponse = reqwest::get(url)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if let Some(result) = response.results.into_iter().next() {
+            Ok(result.into())
+        } else {
+            let e = format!("No results found for {} ({})", &address, response.status);
+            Err(GeocodingError::NoResultsFound(e))
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ApiResponse {
     results: Vec<ResultObject>,
-    status:  String, // TODO: Should I use this? I could return status to admin instead of "No results found"
+    status:  String,
 }
 
 #[allow(dead_code)]
@@ -61,6 +62,8 @@ struct ResultObject {
     geometry:           Geometry,
     place_id:           String,
     types:              Vec<String>,
+    #[serde(default)]
+    partial_match:      bool,
 }
 
 #[allow(dead_code)]
@@ -74,7 +77,8 @@ struct AddressComponent {
 #[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 struct Geometry {
-    bounds:        Bounds,
+    // Only sent for results that cover an area
+    bounds:        Option<Bounds>,
     location:      Location,
     location_type: String,
     viewport:      Bounds,
@@ -87,8 +91,126 @@ struct Bounds {
     southwest: Location,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Location {
     pub lat: f32,
     pub lng: f32,
-}
\ No newline at end of file
+}
+
+impl ResultObject {
+    fn component(&self, kind: &str) -> Option<&AddressComponent> {
+        self.address_components
+            .iter()
+            .find(|c| c.types.iter().any(|t| t == kind))
+    }
+}
+
+impl From<ResultObject> for GeocodeResult {
+    fn from(value: ResultObject) -> Self {
+        let country    = value.component("country");
+        let region     = value.component("administrative_area_level_1");
+        let components = AddressComponents {
+            locality:     value.component("locality")
+                .or_else(|| value.component("postal_town"))
+                .map(|c| c.long_name.clone()),
+            region:       region.map(|c| c.short_name.clone()),
+            region_name:  region.map(|c| c.long_name.clone()),
+            postal_code:  value.component("postal_code").map(|c| c.long_name.clone()),
+            country_name: country.map(|c| c.long_name.clone()),
+        };
+        let country    = country.map(|c| c.short_name.to_uppercase());
+
+        // Google doesn't give a score, so derive one from how precise the match is
+        let precision = match value.geometry.location_type.as_str() {
+            "ROOFTOP" => 1.0,
+            "RANGE_INTERPOLATED" => 0.8,
+            "GEOMETRIC_CENTER" => 0.6,
+            _ => 0.4,
+        };
+        let confidence = if value.partial_match { precision / 2.0 } else { precision };
+
+        Self {
+            location:          value.geometry.location,
+            formatted_address: value.formatted_address,
+            country,
+            place_id:          Some(value.place_id),
+            confidence,
+            components,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::Secret;
+    use wiremock::matchers::{method, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::error::GeocodingError;
+    use crate::geocoder::Geocoder;
+    use crate::gmaps_api_client::GoogleMapsAPIClient;
+
+    fn client(api_url: String) -> GoogleMapsAPIClient {
+        GoogleMapsAPIClient::new(api_url, Secret::new("my-secret-token".to_string()))
+    }
+
+    #[tokio::test]
+    async fn geocode_returns_richer_result() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(query_param("address", "Asheville, NC"))
+            .and(query_param("key", "my-secret-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": "OK",
+                "results": [{
+                    "address_components": [
+                        { "long_name": "Asheville", "short_name": "Asheville", "types": ["locality", "political"] },
+                        { "long_name": "North Carolina", "short_name": "NC", "types": ["administrative_area_level_1", "political"] },
+                        { "long_name": "United States", "short_name": "US", "types": ["country", "political"] }
+                    ],
+                    "formatted_address": "Asheville, NC, USA",
+                    "place_id":          "ChIJCW8PPKmMWYgRXTo0BsEx75Q",
+                    "types":             ["locality", "political"],
+                    "partial_match":     true,
+                    "geometry": {
+                        "location":      { "lat": 35.5951, "lng": -82.5515 },
+                        "location_type": "APPROXIMATE",
+                        "viewport": {
+                            "northeast": { "lat": 35.7, "lng": -82.4 },
+                            "southwest": { "lat": 35.4, "lng": -82.7 }
+                        }
+                    }
+                }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = client(mock_server.uri()).geocode("Asheville, NC").await.unwrap();
+
+        assert_eq!("Asheville, NC, USA", result.formatted_address);
+        assert_eq!(Some("US".to_string()), result.country);
+        assert_eq!(0.2, result.confidence);
+        assert_eq!(Some("Asheville".to_string()), result.components.locality);
+        assert_eq!(Some("NC".to_string()), result.components.region);
+        assert_eq!(None, result.components.postal_code);
+    }
+
+    #[tokio::test]
+    async fn geocode_fails_without_results() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status":  "ZERO_RESULTS",
+                "results": []
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = client(mock_server.uri()).geocode("nowhere").await;
+
+        assert!(matches!(result, Err(GeocodingError::NoResultsFound(_))));
+    }
+}
@@ -2,11 +2,17 @@ pub mod auth;
 pub mod configuration;
 pub mod domain;
 pub mod email_client;
+pub mod email_templates;
+pub mod email_worker;
 pub mod error;
+pub mod exporter;
+pub mod geocoder;
+pub mod geocoding_worker;
 pub mod gmaps_api_client;
 // pub mod idempotency;
 pub mod redis_cli;
 pub mod routes;
+pub mod routing;
 pub mod startup;
 pub mod telemetry;
 pub mod utils;
\ No newline at end of file
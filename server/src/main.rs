@@ -2,6 +2,7 @@ use std::fmt::{Debug, Display};
 use tokio::task::JoinError;
 
 use byot_server::configuration::get_configuration;
+use byot_server::{email_worker, geocoding_worker};
 use byot_server::startup::Application;
 use byot_server::telemetry::{get_subscriber, init_subscriber};
 
@@ -13,9 +14,13 @@ async fn main() -> anyhow::Result<()> {
     let configuration    = get_configuration().expect("Failed to read configuration");
     let application      = Application::build(configuration.clone()).await?;
     let application_task = tokio::spawn(application.run_until_stopped());
+    let worker_task      = tokio::spawn(geocoding_worker::run_worker_until_stopped(configuration.clone()));
+    let email_task       = tokio::spawn(email_worker::run_worker_until_stopped(configuration));
 
     tokio::select! {
-        o = application_task => report_exit("API", o)
+        o = application_task => report_exit("API", o),
+        o = worker_task => report_exit("Geocoding worker", o),
+        o = email_task => report_exit("Email worker", o),
     }
 
     Ok(())
@@ -0,0 +1,164 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::{
+    query_totp,
+    revoke_all_sessions,
+    revoke_api_tokens,
+    revoke_refresh_tokens,
+    set_two_factor_required,
+    JwtMiddleware,
+    TwoFactorRequirementData
+};
+use crate::domain::{query_user_status, update_user_status, UserParams, UserStatus, UserStatusData};
+use crate::error::AdminError;
+use crate::routes::confirm_user;
+use crate::utils::is_admin;
+
+/// Confirms a user whose confirmation emails never arrived
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn admin_confirm_user(
+    req:  HttpRequest,
+    json: web::Json<UserParams>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, AdminError> {
+    is_admin(req)?;
+
+    let status = query_user_status(&json.user_id, &pool)
+        .await
+        .context("Failed to query user")?
+        .ok_or_else(|| AdminError::ValidationError("User not found".to_string()))?;
+
+    let message = match status {
+        UserStatus::Pending   => None,
+        UserStatus::Confirmed => Some("User is already confirmed"),
+        UserStatus::Suspended => Some("User is suspended"),
+        UserStatus::Banned    => Some("User is banned"),
+    };
+
+    if let Some(message) = message {
+        return Err(AdminError::ValidationError(message.to_string()))
+    }
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let confirmed = confirm_user(&mut transaction, json.user_id)
+        .await
+        .context("Failed to confirm user")?;
+
+    // Their status changed since it was looked up
+    if !confirmed {
+        return Err(AdminError::ValidationError("User is no longer pending".to_string()))
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to confirm user")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Suspends, bans or reinstates a user. They're locked out on their next
+/// request.
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn admin_set_user_status(
+    req:  HttpRequest,
+    json: web::Json<UserStatusData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, AdminError> {
+    let admin_id = *req.extensions().get::<uuid::Uuid>().unwrap();
+    is_admin(req)?;
+
+    if json.user_id == admin_id {
+        return Err(AdminError::ValidationError("You can't change your own status".to_string()))
+    }
+
+    let updated = update_user_status(&json.user_id, json.status, &pool)
+        .await
+        .context("Failed to update user status")?;
+
+    if !updated {
+        return Err(AdminError::ValidationError("User not found".to_string()))
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Logs a user out on every device and revokes their API tokens
+#[tracing::instrument(
+    skip(req, json, pool, redis)
+)]
+pub async fn admin_revoke_sessions(
+    req:   HttpRequest,
+    json:  web::Json<UserParams>,
+    pool:  web::Data<PgPool>,
+    redis: web::Data<redis::Client>,
+    _:     JwtMiddleware
+) -> Result<HttpResponse, AdminError> {
+    is_admin(req)?;
+
+    query_user_status(&json.user_id, &pool)
+        .await
+        .context("Failed to query user")?
+        .ok_or_else(|| AdminError::ValidationError("User not found".to_string()))?;
+
+    let mut conn = redis.get_tokio_connection()
+        .await
+        .context("Could not get async Redis connection")?;
+    revoke_all_sessions(&mut conn, &json.user_id)
+        .await
+        .context("Failed to revoke sessions")?;
+    revoke_refresh_tokens(&json.user_id, &**pool)
+        .await
+        .context("Failed to revoke refresh tokens")?;
+    revoke_api_tokens(&json.user_id, &**pool)
+        .await
+        .context("Failed to revoke API tokens")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Turns the two-factor requirement for admins on or off. Admins without it
+/// can still log in but can't use the admin routes until they've set it up.
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn admin_require_two_factor(
+    req:  HttpRequest,
+    json: web::Json<TwoFactorRequirementData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, AdminError> {
+    let admin_id = *req.extensions().get::<uuid::Uuid>().unwrap();
+    is_admin(req)?;
+
+    if json.required {
+        let totp = query_totp(&admin_id, &**pool)
+            .await
+            .context("Failed to query TOTP secret")?;
+
+        // Otherwise they'd lock themselves out
+        if !totp.map_or(false, |totp| totp.enabled_at.is_some()) {
+            return Err(AdminError::ValidationError(
+                "Turn on two-factor authentication for your own account first".to_string()
+            ))
+        }
+    }
+
+    set_two_factor_required("admin", json.required, &pool)
+        .await
+        .context("Failed to update two-factor requirement")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
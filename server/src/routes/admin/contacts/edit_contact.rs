@@ -3,8 +3,11 @@ use anyhow::Context;
 use sqlx::PgPool;
 
 use crate::auth::JwtMiddleware;
-use crate::domain::contact::{
+use crate::domain::{
+    enqueue_if_address_changed,
+    query_contact_address,
     query_contact_by_id,
+    ContactViewer,
     update_contact,
     update_contact_genres,
     EditContactData, 
@@ -31,6 +34,11 @@ pub async fn admin_edit_contact(
         .await
         .context("Failed to acquire a Postgres connection from the pool")?;
 
+    let previous = query_contact_address(&contact.contact_id, &mut transaction)
+        .await
+        .context("Failed to query contact address")?
+        .ok_or(AdminError::ValidationError("Contact not found".to_string()))?;
+
     update_contact(&contact, &mut transaction)
         .await
         .context("Failed to update contact")?;
@@ -39,17 +47,19 @@ pub async fn admin_edit_contact(
         .await
         .context("Failed to update genres for contact")?;
 
+    enqueue_if_address_changed(&contact.contact_id, &previous, &mut transaction)
+        .await
+        .context("Failed to queue geocoding job")?;
+
     transaction
         .commit()
         .await
         .context("Failed to commit SQL transaction to update contact")?;
 
     // If this fails, the transaction shouldn't fail
-    let contact = query_contact_by_id(&pool, &contact.contact_id)
+    let contact = query_contact_by_id(&pool, &contact.contact_id, ContactViewer::Unrestricted)
         .await
         .context("Failed to query updated contact from the database")?;
 
-    // TODO: if verified && address changes, update latitute and longitude
-    // TODO: need someway to handle if this process errors. new column `erroring` && view for admin?
     Ok(HttpResponse::Ok().json(contact))
 }
\ No newline at end of file
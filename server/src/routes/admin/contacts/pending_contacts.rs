@@ -1,21 +1,16 @@
 use actix_web::{web, HttpRequest, HttpResponse};
 use anyhow::Context;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 
 use crate::auth::JwtMiddleware;
-use crate::domain::PendingContact;
+use crate::domain::{enqueue_geocoding_job, GeocodingReason, PendingContact};
 use crate::error::AdminError;
-use crate::gmaps_api_client::{get_latlng_from_address, GoogleMapsAPIClient, Location};
 use crate::utils::is_admin;
 
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ApproveData {
     contact_id: i32,
-    address:    String,
-    city:       String,
-    state:      String,
-    zip_code:   String
 }
 
 #[derive(serde::Serialize)]
@@ -24,42 +19,37 @@ struct JsonResponse {
 }
 
 #[tracing::instrument(
-    skip(req, json, pool, g_client),
+    skip(req, json, pool),
 )]
 pub async fn approve_contact(
-    req:      HttpRequest,
-    json:     web::Json<ApproveData>,
-    pool:     web::Data<PgPool>,
-    g_client: web::Data<GoogleMapsAPIClient>,
-    _:        JwtMiddleware,
+    req:  HttpRequest,
+    json: web::Json<ApproveData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware,
 ) -> Result<HttpResponse, AdminError> {
     is_admin(req)?;
 
-    mark_contact_as_verified(&json.contact_id, &pool)
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    mark_contact_as_verified(&json.contact_id, &mut transaction)
+        .await
+        .context("Failed to mark contact as verified")?;
+
+    // Coordinates are looked up by the geocoding worker, failures are
+    // recorded against the job rather than failing the approval
+    enqueue_geocoding_job(&json.contact_id, GeocodingReason::Approval, &mut transaction)
         .await
-        .context("Failed to delete contact")?;
-
-    let address = format!(
-        "{} {}, {} {}",
-        &json.address,
-        &json.city,
-        &json.state,
-        &json.zip_code,
-    );
-    let mut msg = format!("Location of {} successfully added", &address);
-
-    // Don't fail if location can't be found from address.
-    // Instead, let admin know there was an error.
-    match get_latlng_from_address(&g_client, &address).await {
-        Ok(lat_lng) => {
-            add_lat_lng_to_contact(&json.contact_id, lat_lng, &pool)
-                .await
-                .context("Failed to update contact lat/lng")?;
-        }
-        Err(e) => {
-            msg = format!("{}", e);
-        }
-    }
+        .context("Failed to queue geocoding job")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to approve contact")?;
+
+    let msg = "Contact approved, location will be added shortly".to_string();
 
     let json_response = JsonResponse {
         message: msg.to_string()
@@ -87,11 +77,11 @@ pub async fn get_pending_contacts(
 
 #[tracing::instrument(
     name = "Marking a contact as verified in the database",
-    skip(contact_id, pool)
+    skip(contact_id, transaction)
 )]
 pub async fn mark_contact_as_verified(
-    contact_id: &i32,
-    pool:       &PgPool
+    contact_id:  &i32,
+    transaction: &mut Transaction<'_, Postgres>
 ) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
@@ -100,7 +90,7 @@ pub async fn mark_contact_as_verified(
         WHERE contact_id = $1
         "#,
         contact_id
-    ).execute(pool)
+    ).execute(transaction)
     .await?;
 
     Ok(())
@@ -127,28 +117,4 @@ pub async fn query_pending_contacts(
     .await?;
 
     Ok(contacts)
-}
-
-#[tracing::instrument(
-    name = "Updating contact latitude and longitude in the database",
-    skip(contact_id, pool, lat_lng)
-)]
-pub async fn add_lat_lng_to_contact(
-    contact_id: &i32,
-    lat_lng:    Location,
-    pool:       &PgPool
-) -> Result<(), sqlx::Error> {
-    sqlx::query!(
-        r#"
-        UPDATE contacts
-        SET latitude = $1, longitude = $2
-        WHERE contact_id = $3
-        "#,
-        lat_lng.lat,
-        lat_lng.lng,
-        contact_id
-    ).execute(pool)
-    .await?;
-
-    Ok(())
 }
\ No newline at end of file
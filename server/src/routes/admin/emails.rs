@@ -0,0 +1,49 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::JwtMiddleware;
+use crate::domain::{query_outbox_emails, requeue_failed_email, OutboxParams, ResendEmailData};
+use crate::error::AdminError;
+use crate::utils::is_admin;
+
+#[tracing::instrument(
+    skip(req, params, pool)
+)]
+pub async fn admin_get_emails(
+    req:    HttpRequest,
+    params: web::Query<OutboxParams>,
+    pool:   web::Data<PgPool>,
+    _:      JwtMiddleware
+) -> Result<HttpResponse, AdminError> {
+    is_admin(req)?;
+
+    let emails = query_outbox_emails(params.status, &pool)
+        .await
+        .context("Failed to query outbox emails")?;
+
+    Ok(HttpResponse::Ok().json(emails))
+}
+
+/// Gives a failed email another full round of attempts
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn admin_resend_email(
+    req:  HttpRequest,
+    json: web::Json<ResendEmailData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, AdminError> {
+    is_admin(req)?;
+
+    let requeued = requeue_failed_email(&json.email_id, &pool)
+        .await
+        .context("Failed to requeue email")?;
+
+    if !requeued {
+        return Err(AdminError::ValidationError("No failed email with that id".to_string()))
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
@@ -0,0 +1,70 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::JwtMiddleware;
+use crate::domain::{
+    enqueue_missing_coordinates,
+    query_address_mismatches,
+    query_geocoding_jobs,
+    GeocodingJobParams
+};
+use crate::error::AdminError;
+use crate::utils::is_admin;
+
+#[derive(serde::Serialize)]
+struct QueuedResponse {
+    queued: u64,
+}
+
+#[tracing::instrument(
+    skip(req, params, pool)
+)]
+pub async fn admin_get_geocoding_jobs(
+    req:    HttpRequest,
+    params: web::Query<GeocodingJobParams>,
+    pool:   web::Data<PgPool>,
+    _:      JwtMiddleware
+) -> Result<HttpResponse, AdminError> {
+    is_admin(req)?;
+
+    let jobs = query_geocoding_jobs(params.status, &pool)
+        .await
+        .context("Failed to query geocoding jobs")?;
+
+    Ok(HttpResponse::Ok().json(jobs))
+}
+
+#[tracing::instrument(
+    skip(req, pool)
+)]
+pub async fn admin_geocode_missing(
+    req:  HttpRequest,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, AdminError> {
+    is_admin(req)?;
+
+    let queued = enqueue_missing_coordinates(&pool)
+        .await
+        .context("Failed to queue contacts missing coordinates")?;
+
+    Ok(HttpResponse::Ok().json(QueuedResponse { queued }))
+}
+
+#[tracing::instrument(
+    skip(req, pool)
+)]
+pub async fn admin_get_address_mismatches(
+    req:  HttpRequest,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, AdminError> {
+    is_admin(req)?;
+
+    let contacts = query_address_mismatches(&pool)
+        .await
+        .context("Failed to query contacts with mismatched addresses")?;
+
+    Ok(HttpResponse::Ok().json(contacts))
+}
@@ -1,5 +1,11 @@
+mod accounts;
 mod contacts;
+mod emails;
+mod geocoding;
 mod reviews;
 
+pub use accounts::*;
 pub use contacts::*;
+pub use emails::*;
+pub use geocoding::*;
 pub use reviews::{admin_get_recent_reviews, admin_get_reviews_by_user, admin_delete_review, admin_edit_review};
\ No newline at end of file
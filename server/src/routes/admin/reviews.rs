@@ -79,7 +79,7 @@ pub async fn admin_get_reviews_by_user(
 ) -> Result<HttpResponse, AdminError> {
     is_admin(req)?;
 
-    let reviews = query_reviews_by_user(&params.user_id, &pool)
+    let reviews = query_reviews_by_user(&params.user_id, None, &pool)
         .await
         .context("Failed to get reviews for user")?;
     
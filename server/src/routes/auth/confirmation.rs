@@ -1,15 +1,30 @@
 use actix_web::{web, HttpResponse};
 use anyhow::Context;
-use sqlx::PgPool;
+use chrono::Duration;
+use sqlx::{PgPool, Postgres, Transaction, types::chrono::NaiveDateTime};
 use uuid::Uuid;
 
+use crate::domain::{UserEmail, UserStatus};
 use crate::error::TokenError;
+use crate::routes::{queue_confirmation_email, store_confirmation_token};
+use crate::startup::ApplicationBaseUrl;
+use crate::utils::{generate_token, is_token_expired};
+
+const CONFIRMATION_EXPIRY_HOURS: i64 = 24;
+/// Confirmation links a pending user can be sent in an hour, counting the
+/// one from signing up
+const RESEND_LIMIT:              i64 = 3;
 
 #[derive(serde::Deserialize)]
 pub struct TokenParams {
     token: String,
 }
 
+#[derive(serde::Deserialize)]
+pub struct ResendConfirmationData {
+    email: String,
+}
+
 #[tracing::instrument(
     name = "Confirm a pending user",
     skip(params, pool)
@@ -17,49 +32,169 @@ pub struct TokenParams {
 pub async fn confirm(
     params: web::Query<TokenParams>,
     pool:   web::Data<PgPool>
-) -> Result<HttpResponse, TokenError> { 
-    let user_id = get_user_id_from_token(&pool, &params.token)
+) -> Result<HttpResponse, TokenError> {
+    let (user_id, created_at) = get_details_from_token(&pool, &params.token)
         .await
         .context("Failed to find a user with the provided confirmation token")?
         .ok_or(TokenError::InvalidToken)?;
 
-    confirm_user(&pool, user_id)
+    if is_token_expired(created_at, Duration::hours(CONFIRMATION_EXPIRY_HOURS)) {
+        return Err(TokenError::InvalidToken)
+    }
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let confirmed = confirm_user(&mut transaction, user_id)
         .await
         .context("Failed to update user status to `confirmed`.")?;
-    
+
+    // Suspended and banned users can't confirm their way back in
+    if !confirmed {
+        return Err(TokenError::InvalidToken)
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to confirm user")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Sends a pending user a new confirmation link. Earlier links keep working
+/// until they expire.
+#[tracing::instrument(
+    name = "Resend a confirmation email",
+    skip(json, pool, base_url)
+)]
+pub async fn resend_confirmation(
+    json:     web::Json<ResendConfirmationData>,
+    pool:     web::Data<PgPool>,
+    base_url: web::Data<ApplicationBaseUrl>
+) -> Result<HttpResponse, TokenError> {
+    let email             = UserEmail::parse(json.email.clone())
+        .map_err(TokenError::ValidationError)?;
+    let (user_id, status) = get_user_status(&pool, &email)
+        .await
+        .context("Failed to find user with provided email")?
+        .ok_or(TokenError::UnknownEmail)?;
+
+    if status != UserStatus::Pending.as_str() {
+        return Err(TokenError::ValidationError("Account is not awaiting confirmation".to_string()))
+    }
+
+    let recent = count_recent_tokens(&pool, user_id)
+        .await
+        .context("Failed to count recent confirmation tokens")?;
+
+    if recent >= RESEND_LIMIT {
+        return Err(TokenError::RateLimitError(
+            "Too many confirmation emails requested. Try again in an hour".to_string()
+        ))
+    }
+
+    let confirmation_token = generate_token();
+    let mut transaction    = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    store_confirmation_token(&mut transaction, user_id, &confirmation_token).await?;
+    queue_confirmation_email(
+        &mut transaction,
+        &email,
+        &base_url.0,
+        &confirmation_token
+    ).await
+    .context("Failed to queue confirmation email")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store confirmation token")?;
+
     Ok(HttpResponse::Ok().finish())
 }
 
+/// Marks a pending user as confirmed and removes any links they have left.
+/// Returns false if there's no pending user with that id.
+#[tracing::instrument(
+    name = "Mark user as confirmed",
+    skip(user_id, transaction)
+)]
+pub async fn confirm_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id:     Uuid
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"UPDATE users SET status = 'confirmed' WHERE user_id = $1 AND status = 'pending'"#,
+        user_id
+    ).execute(&mut *transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"DELETE FROM confirmation_tokens WHERE user_id = $1"#,
+        user_id
+    ).execute(transaction)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 #[tracing::instrument(
     name = "Get a user_id from a token",
     skip(token, pool)
 )]
-async fn get_user_id_from_token(
-    pool:  &PgPool, 
+async fn get_details_from_token(
+    pool:  &PgPool,
     token: &str
-) -> Result<Option<Uuid>, sqlx::Error> {
+) -> Result<Option<(Uuid, NaiveDateTime)>, sqlx::Error> {
     let result = sqlx::query!(
-        "SELECT user_id FROM confirmation_tokens WHERE confirmation_token = $1",
+        "SELECT user_id, created_at FROM confirmation_tokens WHERE confirmation_token = $1",
         token
     ).fetch_optional(pool)
     .await?;
 
-    Ok(result.map(|r| r.user_id))
+    Ok(result.map(|r| (r.user_id, r.created_at)))
 }
 
 #[tracing::instrument(
-    name = "Mark user as confirmed",
-    skip(user_id, pool)
+    name = "Get a user's status from their email",
+    skip(pool, email)
 )]
-async fn confirm_user(
-    pool:    &PgPool, 
+async fn get_user_status(
+    pool:  &PgPool,
+    email: &UserEmail
+) -> Result<Option<(Uuid, String)>, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"SELECT user_id, status FROM users WHERE email = $1"#,
+        email.as_ref()
+    ).fetch_optional(pool)
+    .await?;
+
+    Ok(result.map(|r| (r.user_id, r.status)))
+}
+
+#[tracing::instrument(
+    name = "Count confirmation tokens issued in the last hour",
+    skip(pool)
+)]
+async fn count_recent_tokens(
+    pool:    &PgPool,
     user_id: Uuid
-) -> Result<(), sqlx::Error> {
-    sqlx::query!(
-        r#"UPDATE users SET status = 'confirmed' WHERE user_id = $1"#,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM confirmation_tokens
+        WHERE user_id = $1 AND created_at > current_timestamp - INTERVAL '1 hour'
+        "#,
         user_id
-    ).execute(pool)
+    ).fetch_one(pool)
     .await?;
 
-    Ok(())
-}
\ No newline at end of file
+    Ok(result.count)
+}
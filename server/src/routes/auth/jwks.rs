@@ -0,0 +1,11 @@
+use actix_web::{web, HttpResponse};
+
+use crate::auth::JwtKeys;
+
+/// Public keys for every key tokens may be signed with, so other services
+/// can verify them without sharing a secret
+pub async fn jwks(jwt_keys: web::Data<JwtKeys>) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "public, max-age=300"))
+        .json(jwt_keys.jwks())
+}
@@ -1,20 +1,34 @@
-use actix_web::{
-    cookie::{time::Duration as ActixWebDuration, Cookie, SameSite},
-    web, HttpResponse
-};
-use chrono::{prelude::*, Duration};
-use jsonwebtoken::{encode, EncodingKey, Header};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use redis::aio::Connection;
 use serde_json::json;
-use secrecy::ExposeSecret;
 use sqlx::PgPool;
+use uuid::Uuid;
 
-use crate::auth::{validate_credentials, TokenClaims, Credentials};
+use crate::auth::{
+    current_session_epoch,
+    end_two_factor_challenge,
+    fail_two_factor_challenge,
+    get_two_factor_challenge,
+    issue_refresh_token,
+    query_totp,
+    session_response,
+    start_two_factor_challenge,
+    two_factor_locked,
+    validate_credentials,
+    verify_second_factor,
+    Credentials,
+    JwtKeys,
+    TokenClaims,
+    TwoFactorLoginData
+};
 use crate::configuration::JWTSettings;
+use crate::domain::query_user_status;
 use crate::domain::user::UserLogin;
 use crate::error::LoginError;
 
 #[tracing::instrument(
-    skip(json, pool, jwt_settings),
+    skip(json, pool, redis, jwt_settings, jwt_keys),
     fields(
         email=tracing::field::Empty,
         user_id=tracing::field::Empty
@@ -23,7 +37,9 @@ use crate::error::LoginError;
 pub async fn log_in(
     json:         web::Json<UserLogin>,
     pool:         web::Data<PgPool>,
+    redis:        web::Data<redis::Client>,
     jwt_settings: web::Data<JWTSettings>,
+    jwt_keys:     web::Data<JwtKeys>,
 ) -> Result<HttpResponse, LoginError> { 
     let credentials = Credentials {
         email:    json.0.email,
@@ -33,36 +49,112 @@ pub async fn log_in(
     tracing::Span::current().record("email", &tracing::field::display(&credentials.email));
 
     let user = validate_credentials(credentials, &pool).await?;
-    let now  = Utc::now();
-    let iat  = now.timestamp() as usize;
-    let week = Duration::weeks(1);
-    let exp  = (now + week).timestamp() as usize;
-
-    let claims: TokenClaims = TokenClaims {
-        sub: user.user_id.to_string(),
-        exp,
-        iat,
-        role: user.role.to_string()
-    };
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(jwt_settings.secret.expose_secret().as_ref())
-    ).unwrap();
-
-    let cookie = Cookie::build("token", token.to_owned())
-        .path("/")
-        .max_age(ActixWebDuration::new(60 * 60, 0))
-        .http_only(true)
-        .same_site(SameSite::None) // TODO: is this safe?
-        .finish();
-
-    Ok(
-        HttpResponse::Ok()
-            .cookie(cookie)
-            .json(json!({
-                "status": "success",
-                "token": token
-            }))
-    ) 
-}
\ No newline at end of file
+
+    if let Some(e) = LoginError::for_status(user.status) {
+        return Err(e)
+    }
+
+    let mut conn = redis.get_tokio_connection()
+        .await
+        .context("Could not get async Redis connection")?;
+
+    let totp = query_totp(&user.user_id, &**pool)
+        .await
+        .context("Failed to query TOTP secret")?;
+
+    // The password alone isn't enough, so there's no session yet. The client
+    // sends the challenge token back with a code to `/login/two-factor`.
+    if totp.map_or(false, |totp| totp.enabled_at.is_some()) {
+        let challenge_token = start_two_factor_challenge(&mut conn, user)
+            .await
+            .context("Failed to start two-factor challenge")?;
+
+        return Ok(HttpResponse::Ok().json(json!({
+            "status":         "two_factor_required",
+            "challengeToken": challenge_token
+        })))
+    }
+
+    start_session(&user.user_id, &user.role, &mut conn, &pool, &jwt_settings, &jwt_keys).await
+}
+
+/// The second step of logging in for users with two-factor on. Takes a code
+/// from their authenticator or one of their recovery codes.
+#[tracing::instrument(
+    skip(json, pool, redis, jwt_settings, jwt_keys),
+    fields(
+        user_id=tracing::field::Empty
+    )
+)]
+pub async fn log_in_two_factor(
+    json:         web::Json<TwoFactorLoginData>,
+    pool:         web::Data<PgPool>,
+    redis:        web::Data<redis::Client>,
+    jwt_settings: web::Data<JWTSettings>,
+    jwt_keys:     web::Data<JwtKeys>,
+) -> Result<HttpResponse, LoginError> {
+    let mut conn  = redis.get_tokio_connection()
+        .await
+        .context("Could not get async Redis connection")?;
+    let challenge = get_two_factor_challenge(&mut conn, &json.challenge_token)
+        .await
+        .context("Failed to get two-factor challenge")?
+        .ok_or(LoginError::InvalidTwoFactorChallenge)?;
+    let user_id   = challenge.user.user_id;
+
+    tracing::Span::current().record("user_id", &tracing::field::display(&user_id));
+
+    // Checked before the code so a locked-out user can tell why they can't
+    // get in. `verify_second_factor` won't accept codes meanwhile either.
+    if two_factor_locked(&user_id, &**pool).await.context("Failed to check two-factor lockout")? {
+        return Err(LoginError::TwoFactorLocked)
+    }
+
+    let verified = verify_second_factor(&user_id, &json.code, &mut conn, &pool).await?;
+
+    if !verified {
+        fail_two_factor_challenge(&mut conn, &json.challenge_token, challenge)
+            .await
+            .context("Failed to record wrong two-factor code")?;
+
+        return Err(LoginError::InvalidTwoFactorCode)
+    }
+
+    end_two_factor_challenge(&mut conn, &json.challenge_token)
+        .await
+        .context("Failed to end two-factor challenge")?;
+
+    // The account may have been suspended since the password was checked
+    let status = query_user_status(&user_id, &pool)
+        .await
+        .context("Failed to query user status")?
+        .ok_or(LoginError::InvalidTwoFactorChallenge)?;
+
+    if let Some(e) = LoginError::for_status(status) {
+        return Err(e)
+    }
+
+    start_session(&user_id, &challenge.user.role, &mut conn, &pool, &jwt_settings, &jwt_keys).await
+}
+
+/// Issues the access and refresh tokens for a user who's logged in
+async fn start_session(
+    user_id:      &Uuid,
+    role:         &str,
+    conn:         &mut Connection,
+    pool:         &PgPool,
+    jwt_settings: &JWTSettings,
+    jwt_keys:     &JwtKeys
+) -> Result<HttpResponse, LoginError> {
+    let epoch = current_session_epoch(conn, user_id)
+        .await
+        .context("Could not get session epoch from Redis")?;
+
+    let claims        = TokenClaims::new(user_id, role, None, &epoch, jwt_settings);
+    let token         = claims.encode(jwt_keys);
+    let refresh_token = issue_refresh_token(user_id, None, None, jwt_settings, pool)
+        .await
+        .context("Failed to store refresh token")?;
+
+    Ok(session_response(&token, &refresh_token, jwt_settings))
+}
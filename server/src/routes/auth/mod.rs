@@ -1,9 +1,13 @@
 mod confirmation;
+mod jwks;
 mod login;
 mod password;
+mod refresh;
 mod signup;
 
 pub use confirmation::*;
+pub use jwks::*;
 pub use login::*;
 pub use password::*;
+pub use refresh::*;
 pub use signup::*;
\ No newline at end of file
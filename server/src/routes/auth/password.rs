@@ -5,10 +5,10 @@ use secrecy::{Secret, ExposeSecret};
 use sqlx::{PgPool, Postgres, Transaction, types::chrono::NaiveDateTime};
 use uuid::Uuid;
 
-use crate::auth::{compute_password_hash};
-use crate::domain::UserEmail;
+use crate::auth::{compute_password_hash, revoke_all_sessions, revoke_api_tokens, revoke_refresh_tokens};
+use crate::domain::{enqueue_email, NewOutboxEmail, UserEmail};
 use crate::domain::input_validator::StringInput;
-use crate::email_client::EmailClient;
+use crate::email_templates::EmailTemplate;
 use crate::error::TokenError;
 use crate::startup::ApplicationBaseUrl;
 use crate::telemetry::spawn_blocking_with_tracing;
@@ -28,17 +28,16 @@ pub struct ResetPasswordData {
 }
 
 #[tracing::instrument(
-    skip(json, pool, email_client, base_url),
+    skip(json, pool, base_url),
     fields(
         email=tracing::field::Empty,
         user_id=tracing::field::Empty
     )
 )]
 pub async fn generate_reset_token(
-    json:         web::Json<ResetRequest>,
-    pool:         web::Data<PgPool>,
-    email_client: web::Data<EmailClient>,
-    base_url:     web::Data<ApplicationBaseUrl>
+    json:     web::Json<ResetRequest>,
+    pool:     web::Data<PgPool>,
+    base_url: web::Data<ApplicationBaseUrl>
 ) -> Result<HttpResponse, TokenError> {
     let email   = UserEmail::parse(json.email.clone())
         .map_err(TokenError::ValidationError)?;
@@ -47,26 +46,38 @@ pub async fn generate_reset_token(
         .context("Failed to find user with provided email")?
         .ok_or(TokenError::UnknownEmail)?;
 
-    let reset_token = generate_token();
-    store_token(&pool, &user_id, &reset_token)
+    let reset_token     = generate_token();
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    store_token(&mut transaction, &user_id, &reset_token)
         .await
         .context("Failed to insert token")?;
 
-    // This won't work in local environment. Make sure to comment out.
-    send_password_reset_email(
-        &email_client,
-        email,
+    queue_password_reset_email(
+        &mut transaction,
+        &email,
         &base_url.0,
         &reset_token
     ).await
-    .context("Failed to send password reset email")?;
+    .context("Failed to queue password reset email")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store reset token")?;
 
     Ok(HttpResponse::Ok().finish())
 }
 
+/// Resetting the password logs the user out everywhere, since whoever knew
+/// the old one may still have a session
 pub async fn reset_password(
-    json: web::Json<ResetPasswordData>,
-    pool: web::Data<PgPool>
+    json:  web::Json<ResetPasswordData>,
+    pool:  web::Data<PgPool>,
+    redis: web::Data<redis::Client>
 ) -> Result<HttpResponse, TokenError> {
     let reset_token    = StringInput::parse(json.reset_token.expose_secret().to_string());
     let token_duration = Duration::minutes(60);
@@ -100,6 +111,20 @@ pub async fn reset_password(
         .await
         .context("Failed to delete password reset token")?;
 
+    // Before committing, so the password isn't changed if this fails
+    let mut conn = redis.get_tokio_connection()
+        .await
+        .context("Could not get async Redis connection")?;
+    revoke_all_sessions(&mut conn, &user_id)
+        .await
+        .context("Failed to revoke sessions")?;
+    revoke_refresh_tokens(&user_id, &mut transaction)
+        .await
+        .context("Failed to revoke refresh tokens")?;
+    revoke_api_tokens(&user_id, &mut transaction)
+        .await
+        .context("Failed to revoke API tokens")?;
+
     transaction
         .commit()
         .await
@@ -166,43 +191,32 @@ async fn get_details_from_reset_token(
 }
 
 #[tracing::instrument(
-    name = "Send a password reset email",
-    skip(email_client, email, base_url, reset_token)
+    name = "Queue a password reset email",
+    skip(transaction, email, base_url, reset_token)
 )]
-async fn send_password_reset_email(
-    email_client: &EmailClient,
-    email:        UserEmail,
-    base_url:     &str,
-    reset_token:  &str
-) -> Result<(), reqwest::Error> {
+async fn queue_password_reset_email(
+    transaction: &mut Transaction<'_, Postgres>,
+    email:       &UserEmail,
+    base_url:    &str,
+    reset_token: &str
+) -> Result<(), anyhow::Error> {
     let reset_link = format!(
         "{}/reset-password?reset_token={}",
         base_url, reset_token
     );
+    let rendered   = EmailTemplate::ResetPassword.render(&[("reset_link", &reset_link)])?;
 
-    let plain_body = &format!(
-        "Your password reset request has been received. Visit {} to reset your password",
-        reset_link
-    );
-    let html_body = &format!(
-        "Your password reset request has been received. <br />Click <a href=\"{}\">here</a> to reset your password",
-        reset_link
-    );
+    enqueue_email(&NewOutboxEmail::from_template(email, &rendered), transaction).await?;
 
-    email_client.send_email(
-        &email,
-        "Password reset",
-        &plain_body,
-        &html_body
-    ).await
+    Ok(())
 }
 
 #[tracing::instrument(
     name = "Store password reset token in the database",
-    skip(reset_token, pool)
+    skip(reset_token, transaction)
 )]
 async fn store_token(
-    pool:        &PgPool,
+    transaction: &mut Transaction<'_, Postgres>,
     user_id:     &Uuid,
     reset_token: &String
 ) -> Result<(), TokenError> {
@@ -213,7 +227,7 @@ async fn store_token(
         "#,
         user_id,
         reset_token
-    ).execute(pool)
+    ).execute(transaction)
     .await
     .map_err(TokenError::DatabaseError)?;
 
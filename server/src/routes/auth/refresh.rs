@@ -0,0 +1,127 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use anyhow::Context;
+use chrono::Utc;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::auth::{
+    current_session_epoch,
+    get_refresh_token_for_update,
+    issue_refresh_token,
+    mark_refresh_token_used,
+    revoke_refresh_token_family,
+    session_response,
+    JwtKeys,
+    TokenClaims,
+    REFRESH_TOKEN_COOKIE
+};
+use crate::configuration::JWTSettings;
+use crate::domain::UserStatus;
+use crate::error::LoginError;
+
+/// Swaps the refresh token for a new one and a new access token. Each
+/// refresh token only works once. If a used one turns up again someone else
+/// has a copy, so the whole session is ended.
+#[tracing::instrument(
+    skip(req, pool, redis, jwt_settings, jwt_keys),
+    fields(
+        user_id=tracing::field::Empty
+    )
+)]
+pub async fn refresh_session(
+    req:          HttpRequest,
+    pool:         web::Data<PgPool>,
+    redis:        web::Data<redis::Client>,
+    jwt_settings: web::Data<JWTSettings>,
+    jwt_keys:     web::Data<JwtKeys>,
+) -> Result<HttpResponse, LoginError> {
+    let token = req.cookie(REFRESH_TOKEN_COOKIE)
+        .map(|c| c.value().to_string())
+        .ok_or(LoginError::InvalidRefreshToken)?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let stored = get_refresh_token_for_update(&token, &mut transaction)
+        .await
+        .context("Failed to query refresh token")?
+        .ok_or(LoginError::InvalidRefreshToken)?;
+
+    tracing::Span::current().record("user_id", &tracing::field::display(&stored.user_id));
+
+    if stored.used_at.is_some() && stored.revoked_at.is_none() {
+        tracing::warn!("A used refresh token was presented again, ending its session");
+
+        revoke_refresh_token_family(&stored.family_id, &mut transaction)
+            .await
+            .context("Failed to revoke refresh token family")?;
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to revoke refresh token family")?;
+
+        return Err(LoginError::InvalidRefreshToken)
+    }
+
+    if stored.revoked_at.is_some() || stored.expires_at <= Utc::now().naive_utc() {
+        return Err(LoginError::InvalidRefreshToken)
+    }
+
+    let (role, status) = get_role_and_status(&stored.user_id, &mut transaction)
+        .await
+        .context("Failed to query user")?;
+
+    if let Some(e) = LoginError::for_status(status) {
+        return Err(e)
+    }
+
+    mark_refresh_token_used(&stored.token_id, &mut transaction)
+        .await
+        .context("Failed to mark refresh token as used")?;
+    let refresh_token = issue_refresh_token(
+        &stored.user_id,
+        Some(stored.family_id),
+        stored.org_id,
+        &jwt_settings,
+        &mut transaction
+    ).await
+    .context("Failed to store refresh token")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to rotate refresh token")?;
+
+    let mut conn = redis.get_tokio_connection()
+        .await
+        .context("Could not get async Redis connection")?;
+    let epoch    = current_session_epoch(&mut conn, &stored.user_id)
+        .await
+        .context("Could not get session epoch from Redis")?;
+
+    let claims = TokenClaims::new(&stored.user_id, &role, stored.org_id, &epoch, &jwt_settings);
+    let token  = claims.encode(&jwt_keys);
+
+    Ok(session_response(&token, &refresh_token, &jwt_settings))
+}
+
+#[tracing::instrument(
+    name = "Get user role and status",
+    skip(transaction)
+)]
+async fn get_role_and_status(
+    user_id:     &Uuid,
+    transaction: &mut Transaction<'_, Postgres>
+) -> Result<(String, UserStatus), anyhow::Error> {
+    let row = sqlx::query!(
+        r#"SELECT role, status FROM users WHERE user_id = $1"#,
+        user_id
+    ).fetch_one(transaction)
+    .await?;
+
+    let status = UserStatus::try_from(row.status).map_err(anyhow::Error::msg)?;
+
+    Ok((row.role, status))
+}
@@ -5,8 +5,8 @@ use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
 use crate::auth::compute_password_hash;
-use crate::domain::UserEmail;
-use crate::email_client::EmailClient;
+use crate::domain::{enqueue_email, NewOutboxEmail, UserEmail};
+use crate::email_templates::EmailTemplate;
 use crate::error::{RegisterError, TokenError};
 use crate::startup::ApplicationBaseUrl;
 use crate::telemetry::spawn_blocking_with_tracing;
@@ -19,17 +19,16 @@ pub struct JsonData {
 }
 
 #[tracing::instrument(
-    skip(json, pool, email_client, base_url),
+    skip(json, pool, base_url),
     fields(
         email=tracing::field::Empty,
         user_id=tracing::field::Empty
     )
 )]
 pub async fn sign_up(
-    json:         web::Json<JsonData>,
-    pool:         web::Data<PgPool>,
-    email_client: web::Data<EmailClient>,
-    base_url:     web::Data<ApplicationBaseUrl>,
+    json:     web::Json<JsonData>,
+    pool:     web::Data<PgPool>,
+    base_url: web::Data<ApplicationBaseUrl>,
 ) -> Result<HttpResponse, RegisterError> {
     let email = UserEmail::parse(json.email.clone())
         .map_err(RegisterError::ValidationError)?;
@@ -49,22 +48,21 @@ pub async fn sign_up(
 
     let confirmation_token = generate_token();
 
-    store_token(&mut transaction, user_id, &confirmation_token)
+    store_confirmation_token(&mut transaction, user_id, &confirmation_token)
         .await
         .context("Failed to store the confirmation token")?;
 
-    transaction.commit()
-        .await
-        .context("Failed to commit SQL transaction to store a new subscriber")?;
-
-    // Will error locally w/o email configured
-    send_confirmation_email(
-        &email_client,
-        email,
+    queue_confirmation_email(
+        &mut transaction,
+        &email,
         &base_url.0,
         &confirmation_token
     ).await
-    .context("Failed to send confirmation email")?;
+    .context("Failed to queue confirmation email")?;
+
+    transaction.commit()
+        .await
+        .context("Failed to commit SQL transaction to store a new subscriber")?;
 
     Ok(HttpResponse::Ok().finish())
 }
@@ -98,7 +96,7 @@ async fn insert_user(
     name = "Store confirmation token in the database",
     skip(confirmation_token, transaction)
 )]
-async fn store_token(
+pub async fn store_confirmation_token(
     transaction:        &mut Transaction<'_, Postgres>,
     user_id:            Uuid,
     confirmation_token: &str
@@ -118,30 +116,21 @@ async fn store_token(
 }
 
 #[tracing::instrument(
-    name = "Send a confirmation email to a new user",
-    skip(email_client, email, base_url, token)
+    name = "Queue a confirmation email to a new user",
+    skip(transaction, email, base_url, token)
 )]
-async fn send_confirmation_email(
-    email_client: &EmailClient,
-    email:        UserEmail,
-    base_url:     &str,
-    token:        &str,
-) -> Result<(), reqwest::Error> {
+pub async fn queue_confirmation_email(
+    transaction: &mut Transaction<'_, Postgres>,
+    email:       &UserEmail,
+    base_url:    &str,
+    token:       &str,
+) -> Result<(), anyhow::Error> {
     let confirmation_link = format!("{}/confirm?token={}", base_url, token);
+    let rendered          = EmailTemplate::ConfirmAccount.render(&[
+        ("confirmation_link", &confirmation_link),
+    ])?;
 
-    let plain_body = &format!(
-        "Welcome to Book Your Own Tour. Visit {} to confirm your account",
-        confirmation_link
-    );
-    let html_body = &format!(
-        "Welcome to Book Your Own Tour. <br />Click <a href=\"{}\">here</a> to confirm your account",
-        confirmation_link
-    );
+    enqueue_email(&NewOutboxEmail::from_template(email, &rendered), transaction).await?;
 
-    email_client.send_email(
-        &email,
-        "Confirm your account",
-        &plain_body,
-        &html_body
-    ).await
-}
\ No newline at end of file
+    Ok(())
+}
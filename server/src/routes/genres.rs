@@ -39,7 +39,7 @@ pub async fn get_genres(
     name = "Querying genres from DB",
     skip(pool)
 )]
-async fn query_genres(pool: &PgPool) -> Result<Vec<Genre>, sqlx::Error> {
+pub async fn query_genres(pool: &PgPool) -> Result<Vec<Genre>, sqlx::Error> {
     let genres = sqlx::query_as!(
         Genre,
         r#"
@@ -1,10 +1,19 @@
 use actix_web::{web, HttpResponse};
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 
-use crate::domain::StringInput;
+use crate::domain::{
+    query_contacts_in_bounds,
+    query_contacts_near,
+    BoundingBox,
+    NearbyContacts,
+    NearbyParams,
+    StringInput
+};
 use crate::error::GeocodingError;
-use crate::gmaps_api_client::{get_latlng_from_address, GoogleMapsAPIClient, Location};
+use crate::geocoder::Geocoder;
+use crate::gmaps_api_client::Location;
 use crate::redis_cli::{get_data_as_json, store_data_as_json};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -13,14 +22,66 @@ pub struct CityData {
 }
 
 #[tracing::instrument(
-    skip(json, redis, g_client)
+    skip(json, redis, geocoder)
 )]
 pub async fn find_coordinates_for_city(
     json:     web::Json<CityData>,
     redis:    web::Data<redis::Client>,
-    g_client: web::Data<GoogleMapsAPIClient>,
+    geocoder: web::Data<dyn Geocoder>,
 ) -> Result<HttpResponse, GeocodingError> {
-    let city = StringInput::parse(json.city.clone());
+    let lat_lng = locate_city(&json.city, &redis, &**geocoder).await?;
+
+    Ok(HttpResponse::Ok().json(lat_lng))
+}
+
+#[tracing::instrument(
+    skip(params, pool, redis, geocoder)
+)]
+pub async fn contacts_nearby(
+    params:   web::Query<NearbyParams>,
+    pool:     web::Data<PgPool>,
+    redis:    web::Data<redis::Client>,
+    geocoder: web::Data<dyn Geocoder>,
+) -> Result<HttpResponse, GeocodingError> {
+    params.validate().map_err(GeocodingError::ValidationError)?;
+
+    let centre = match (params.lat, params.lng, &params.city) {
+        (Some(lat), Some(lng), _) => Location { lat, lng },
+        (_, _, Some(city)) => locate_city(city, &redis, &**geocoder).await?,
+        _ => unreachable!("validated above")
+    };
+
+    let contacts = query_contacts_near(&pool, &centre, params.radius_km)
+        .await
+        .context("Failed to query nearby contacts")?;
+
+    Ok(HttpResponse::Ok().json(NearbyContacts { centre, contacts }))
+}
+
+#[tracing::instrument(
+    skip(params, pool)
+)]
+pub async fn contacts_in_bounds(
+    params: web::Query<BoundingBox>,
+    pool:   web::Data<PgPool>,
+) -> Result<HttpResponse, GeocodingError> {
+    params.validate().map_err(GeocodingError::ValidationError)?;
+
+    let centre   = params.centre();
+    let contacts = query_contacts_in_bounds(&pool, &params, &centre)
+        .await
+        .context("Failed to query contacts in bounds")?;
+
+    Ok(HttpResponse::Ok().json(NearbyContacts { centre, contacts }))
+}
+
+/// Looks a city up with the configured geocoder, caching the answer in Redis.
+pub(crate) async fn locate_city(
+    city:     &str,
+    redis:    &redis::Client,
+    geocoder: &dyn Geocoder,
+) -> Result<Location, GeocodingError> {
+    let city = StringInput::parse(city.to_string());
     let city = city.to_lowercase();
     let mut conn = redis.get_tokio_connection()
         .await
@@ -30,16 +91,17 @@ pub async fn find_coordinates_for_city(
         .context("Could not get cached city from Redis")?;
 
     if let Some(cached_location) = cached_location {
-        return Ok(HttpResponse::Ok().json(cached_location))
+        return Ok(cached_location)
     }
 
-    let lat_lng = get_latlng_from_address(&g_client, &city)
+    let lat_lng = geocoder.geocode(&city)
         .await
-        .context("Could not get coordinates from Google API")?;
+        .context("Could not get coordinates from geocoder")?
+        .location;
 
     store_data_as_json(&mut conn, &city, &lat_lng)
         .await
         .context("Could not store city in Redis")?;
 
-    Ok(HttpResponse::Ok().json(lat_lng))
-}
\ No newline at end of file
+    Ok(lat_lng)
+}
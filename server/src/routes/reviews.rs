@@ -36,7 +36,7 @@ async fn query_reviews_for_contact(
     let result = sqlx::query_as!(
         Review,
         r#"
-        SELECT review_id, contact_id, user_id, title, body, rating
+        SELECT review_id, contact_id, user_id, org_id, title, body, rating
         FROM reviews
         WHERE contact_id = $1
         "#,
@@ -0,0 +1,84 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::{
+    insert_api_token,
+    query_api_tokens,
+    revoke_api_token,
+    ApiTokenParams,
+    ApiTokenScope,
+    JwtMiddleware,
+    NewApiTokenData
+};
+use crate::error::ContentError;
+
+const MAX_NAME_LENGTH: usize = 100;
+
+/// Makes a token for scripts to use instead of a session. It acts for the
+/// organisation the user is acting for now, if any. Only a session can make
+/// one, so a leaked token can't be used to mint more.
+#[tracing::instrument(
+    skip(json, pool, jwt)
+)]
+pub async fn add_api_token(
+    json: web::Json<NewApiTokenData>,
+    pool: web::Data<PgPool>,
+    jwt:  JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let name       = json.name.trim();
+    let mut scopes = json.scopes.clone();
+
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(ContentError::ValidationError(
+            format!("Token name must be between 1 and {} characters", MAX_NAME_LENGTH)
+        ))
+    }
+
+    scopes.sort_by_key(ApiTokenScope::as_str);
+    scopes.dedup();
+
+    if scopes.is_empty() {
+        return Err(ContentError::ValidationError("Choose at least one scope".to_string()))
+    }
+
+    let org_id = jwt.organisation.map(|org| org.org_id);
+    let token  = insert_api_token(&jwt.user_id, name, &scopes, org_id, &**pool)
+        .await
+        .context("Failed to store API token")?;
+
+    Ok(HttpResponse::Ok().json(token))
+}
+
+#[tracing::instrument(
+    skip(pool, jwt)
+)]
+pub async fn get_api_tokens(
+    pool: web::Data<PgPool>,
+    jwt:  JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let tokens = query_api_tokens(&jwt.user_id, &pool)
+        .await
+        .context("Failed to query API tokens")?;
+
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+#[tracing::instrument(
+    skip(json, pool, jwt)
+)]
+pub async fn user_revoke_api_token(
+    json: web::Json<ApiTokenParams>,
+    pool: web::Data<PgPool>,
+    jwt:  JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let revoked = revoke_api_token(&json.token_id, &jwt.user_id, &pool)
+        .await
+        .context("Failed to revoke API token")?;
+
+    if !revoked {
+        return Err(ContentError::ValidationError("API token not found".to_string()))
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
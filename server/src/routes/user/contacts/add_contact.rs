@@ -2,15 +2,17 @@ use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use anyhow::Context;
 use sqlx::PgPool;
 
-use crate::auth::JwtMiddleware;
+use crate::auth::{scope, ApiAuth};
 use crate::domain::{
     add_contact_genre_relation,
     insert_contact,
     query_contact_by_id,
+    ContactViewer,
     NewContact,
     NewContactData
 };
 use crate::error::ContentError;
+use crate::utils::new_data_org;
 
 #[tracing::instrument(
     skip(req, json, pool),
@@ -19,10 +21,11 @@ pub async fn add_contact(
     req:  HttpRequest,
     json: web::Json<NewContactData>,
     pool: web::Data<PgPool>,
-    _:    JwtMiddleware
+    _:    ApiAuth<scope::ContactsWrite>
 ) -> Result<HttpResponse, ContentError> {
     let ext     = req.extensions();
     let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let org_id  = new_data_org(&req)?;
     let contact: NewContact = json.0.try_into().map_err(ContentError::ValidationError)?;
 
     let mut transaction = pool
@@ -30,7 +33,7 @@ pub async fn add_contact(
         .await
         .context("Failed to acquire a Postgres connection from the pool")?;
 
-    let contact_id = insert_contact(&contact, &mut transaction, user_id)
+    let contact_id = insert_contact(&contact, &mut transaction, user_id, org_id)
         .await
         .context("Failed to insert new contact into database")?;
 
@@ -44,7 +47,7 @@ pub async fn add_contact(
         .context("Failed to commit SQL transaction to add new Contact")?;
 
     // If this fails, the transaction shouldn't fail
-    let contact = query_contact_by_id(&pool, &contact_id)
+    let contact = query_contact_by_id(&pool, &contact_id, ContactViewer::User(user_id, org_id))
         .await
         .context("Failed to query newly added contact from database")?;
 
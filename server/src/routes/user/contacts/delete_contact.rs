@@ -3,14 +3,17 @@ use anyhow::Context;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::auth::JwtMiddleware;
+use crate::auth::{scope, ApiAuth};
 use crate::domain::contact::delete_contact;
+use crate::domain::{query_contact_permission, ContactPermission};
 use crate::error::ContentError;
-use crate::utils::user_matches;
+use crate::utils::active_org;
 
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DeleteData {
+    // Still sent by clients, but who can delete is decided by the contact
+    #[allow(dead_code)]
     user_id:    Uuid,
     contact_id: i32,
 }
@@ -22,15 +25,22 @@ pub async fn user_delete_contact(
     req:  HttpRequest,
     pool: web::Data<PgPool>,
     json: web::Json<DeleteData>,
-    _:    JwtMiddleware
+    _:    ApiAuth<scope::ContactsWrite>
 ) -> Result<HttpResponse, ContentError> {
     let ext     = req.extensions();
     let user_id = ext.get::<uuid::Uuid>().unwrap();
 
-    user_matches(user_id, &json.user_id)?;
+    let permission = query_contact_permission(&json.contact_id, user_id, active_org(&req).as_ref(), &pool)
+        .await
+        .context("Failed to query permission for contact")?;
+
+    if permission != Some(ContactPermission::Owner) {
+        return Err(ContentError::AuthorizationError)
+    }
+
     delete_contact(&json.contact_id, &pool)
         .await
         .context("Failed to delete contact")?;
     
     Ok(HttpResponse::Ok().finish())
-}
\ No newline at end of file
+}
@@ -2,16 +2,21 @@ use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use anyhow::Context;
 use sqlx::PgPool;
 
-use crate::auth::JwtMiddleware;
-use crate::domain::contact::{
+use crate::auth::{scope, ApiAuth};
+use crate::domain::{
+    enqueue_if_address_changed,
+    query_contact_address,
     query_contact_by_id,
+    query_contact_permission,
     update_contact,
     update_contact_genres,
+    ContactPermission,
+    ContactViewer,
     EditContactData, 
     EditedContact
 };
 use crate::error::ContentError;
-use crate::utils::user_matches;
+use crate::utils::active_org;
 
 #[tracing::instrument(
     skip(req, pool, json)
@@ -20,20 +25,35 @@ pub async fn user_edit_contact(
     req:  HttpRequest,
     pool: web::Data<PgPool>,
     json: web::Json<EditContactData>,
-    _:    JwtMiddleware
+    _:    ApiAuth<scope::ContactsWrite>
 ) -> Result<HttpResponse, ContentError> {
     let ext     = req.extensions();
     let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let org     = active_org(&req);
 
-    user_matches(user_id, &json.user_id)?;
+    let permission = query_contact_permission(&json.contact_id, user_id, org.as_ref(), &pool)
+        .await
+        .context("Failed to query permission for contact")?
+        .filter(|p| p.can_edit())
+        .ok_or(ContentError::AuthorizationError)?;
     
-    let contact: EditedContact = json.0.try_into().map_err(ContentError::ValidationError)?;
+    let mut contact: EditedContact = json.0.try_into().map_err(ContentError::ValidationError)?;
+
+    // Only the owner decides who can see a contact
+    if permission != ContactPermission::Owner {
+        contact.is_private = true;
+    }
 
     let mut transaction = pool
         .begin()
         .await
         .context("Failed to acquire a Postgres connection from the pool")?;
 
+    let previous = query_contact_address(&contact.contact_id, &mut transaction)
+        .await
+        .context("Failed to query contact address")?
+        .ok_or(ContentError::ValidationError("Contact not found".to_string()))?;
+
     update_contact(&contact, &mut transaction)
         .await
         .context("Failed to update contact")?;
@@ -41,6 +61,10 @@ pub async fn user_edit_contact(
     update_contact_genres(&contact, &mut transaction)
         .await
         .context("Failed to update genres for contact")?;
+
+    enqueue_if_address_changed(&contact.contact_id, &previous, &mut transaction)
+        .await
+        .context("Failed to queue geocoding job")?;
     
     transaction
         .commit()
@@ -48,11 +72,9 @@ pub async fn user_edit_contact(
         .context("Failed to commit SQL transaction to update contact")?;
     
     // If this fails, the transaction shouldn't fail
-    let contact = query_contact_by_id(&pool, &contact.contact_id)
+    let contact = query_contact_by_id(&pool, &contact.contact_id, ContactViewer::User(user_id, org.map(|o| o.org_id)))
         .await
         .context("Failed to query updated contact from the database")?;
 
-    // TODO: if verified && address changes, update latitute and longitude
-    // TODO: need someway to handle if this process errors. new column `erroring` && view for admin?
     Ok(HttpResponse::Ok().json(contact))
 }
\ No newline at end of file
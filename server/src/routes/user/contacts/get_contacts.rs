@@ -3,9 +3,10 @@ use anyhow::Context;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::auth::JwtMiddleware;
+use crate::auth::ApiAuth;
 use crate::domain::{format_contact_response, ContactResponse, ContactRow};
 use crate::error::ContentError;
+use crate::utils::active_org;
 
 #[tracing::instrument(
     skip(req, pool)
@@ -13,12 +14,13 @@ use crate::error::ContentError;
 pub async fn user_get_contacts(
     req:  HttpRequest,
     pool: web::Data<PgPool>,
-    _:    JwtMiddleware
+    _:    ApiAuth
 ) -> Result<HttpResponse, ContentError> {
     let ext      = req.extensions();
     let user_id  = ext.get::<uuid::Uuid>().unwrap();
+    let org_id   = active_org(&req).map(|org| org.org_id);
     let private  = false;
-    let contacts = query_user_contacts(user_id, private, &pool)
+    let contacts = query_user_contacts(user_id, org_id, private, &pool)
         .await
         .context("Failed to get private contacts from database")?;
 
@@ -31,12 +33,13 @@ pub async fn user_get_contacts(
 pub async fn private_contacts(
     req:  HttpRequest,
     pool: web::Data<PgPool>,
-    _:    JwtMiddleware,
+    _:    ApiAuth,
 ) -> Result<HttpResponse, ContentError> {
     let ext      = req.extensions();
     let user_id  = ext.get::<uuid::Uuid>().unwrap();
+    let org_id   = active_org(&req).map(|org| org.org_id);
     let private  = true;
-    let contacts = query_user_contacts(user_id, private, &pool)
+    let contacts = query_user_contacts(user_id, org_id, private, &pool)
         .await
         .context("Failed to get private contacts from database")?;
     
@@ -45,10 +48,11 @@ pub async fn private_contacts(
 
 #[tracing::instrument(
     name = "Querying User's contacts from DB",
-    skip(pool, user_id, private)
+    skip(pool, user_id, org_id, private)
 )]
 async fn query_user_contacts(
     user_id:  &Uuid,
+    org_id:   Option<Uuid>,
     private:  bool,
     pool:     &PgPool
 ) -> Result<Vec<ContactResponse>, sqlx::Error> {
@@ -58,19 +62,26 @@ async fn query_user_contacts(
         SELECT c.contact_id, c.display_name, c.address, c.city, c.state, 
                c.zip_code, c.capacity, c.latitude, c.longitude, c.email, 
                c.contact_form, c.age_range, c.country, c.is_private, c.contact_type,
-               c.user_id,
+               c.user_id, c.org_id,
                ROUND(AVG(r.rating), 2)::real AS average_rating,
                g.genre_name, g.genre_id
         FROM contacts c
         LEFT JOIN reviews r ON c.contact_id = r.contact_id
         LEFT JOIN contacts_genres ON c.contact_id = contacts_genres.contact_id
         LEFT JOIN genres g on g.genre_id = contacts_genres.genre_id
-        WHERE c.is_private = true OR c.is_private = $1
-        AND c.user_id = $2
+        WHERE (
+            ((c.org_id IS NULL AND c.user_id = $2) OR c.org_id = $3)
+            AND (c.is_private = true OR c.is_private = $1)
+        )
+        OR EXISTS (
+            SELECT 1 FROM contact_shares s
+            WHERE s.contact_id = c.contact_id AND s.user_id = $2
+        )
         GROUP BY c.contact_id, g.genre_name, g.genre_id
-        "#, // `is_private` is intentionally redundant to allow for `true` or all
+        "#, // `is_private` is intentionally redundant to allow for `true` or all. Shared contacts are always private
         private,
-        user_id
+        user_id,
+        org_id
     ).fetch_all(pool)
     .await?;
 
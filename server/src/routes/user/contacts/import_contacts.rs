@@ -0,0 +1,113 @@
+use actix_multipart::Multipart;
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use futures_util::StreamExt;
+use sqlx::PgPool;
+
+use crate::auth::{scope, ApiAuth};
+use crate::domain::{
+    add_contact_genre_relation,
+    insert_contact,
+    parse_contact_csv,
+    ImportMode,
+    ImportParams,
+    ImportReport
+};
+use crate::error::ContentError;
+use crate::routes::query_genres;
+use crate::utils::new_data_org;
+
+const MAX_IMPORT_BYTES: usize = 5 * 1024 * 1024;
+
+#[tracing::instrument(
+    skip(req, params, payload, pool)
+)]
+pub async fn import_contacts(
+    req:     HttpRequest,
+    params:  web::Query<ImportParams>,
+    payload: Multipart,
+    pool:    web::Data<PgPool>,
+    _:       ApiAuth<scope::ContactsWrite>
+) -> Result<HttpResponse, ContentError> {
+    let user_id = *req.extensions().get::<uuid::Uuid>().unwrap();
+    let org_id  = new_data_org(&req)?;
+    let data    = read_csv_field(payload).await?;
+    let genres  = query_genres(&pool)
+        .await
+        .context("Failed to query list of genres")?;
+
+    let rows = parse_contact_csv(&data, &genres).map_err(ContentError::ValidationError)?;
+
+    let total_rows = rows.len();
+    let mut errors = Vec::new();
+    let mut valid  = Vec::new();
+
+    for row in rows {
+        match row {
+            Ok(contact) => valid.push(contact),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    let mut imported = 0;
+
+    if params.mode == ImportMode::Commit && !valid.is_empty() {
+        let mut transaction = pool
+            .begin()
+            .await
+            .context("Failed to acquire a Postgres connection from the pool")?;
+
+        for contact in valid.iter() {
+            let contact_id = insert_contact(contact, &mut transaction, &user_id, org_id)
+                .await
+                .context("Failed to insert imported contact into database")?;
+
+            add_contact_genre_relation(&contact_id, contact.genres.clone(), &mut transaction)
+                .await
+                .context("Failed to insert contacts_genres relation")?;
+
+            imported += 1;
+        }
+
+        transaction
+            .commit()
+            .await
+            .context("Failed to commit SQL transaction to import contacts")?;
+    }
+
+    let report = ImportReport {
+        mode:       params.mode,
+        total_rows,
+        valid_rows: valid.len(),
+        imported,
+        errors
+    };
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+async fn read_csv_field(mut payload: Multipart) -> Result<Vec<u8>, ContentError> {
+    while let Some(field) = payload.next().await {
+        let mut field = field.map_err(|e| ContentError::ValidationError(e.to_string()))?;
+
+        if field.content_disposition().get_name() != Some("file") {
+            continue;
+        }
+
+        let mut data = Vec::new();
+
+        while let Some(chunk) = field.next().await {
+            let chunk = chunk.map_err(|e| ContentError::ValidationError(e.to_string()))?;
+
+            if data.len() + chunk.len() > MAX_IMPORT_BYTES {
+                return Err(ContentError::ValidationError("CSV file is too large".to_string()))
+            }
+
+            data.extend_from_slice(&chunk);
+        }
+
+        return Ok(data)
+    }
+
+    Err(ContentError::ValidationError("Missing `file` field".to_string()))
+}
@@ -2,8 +2,12 @@ mod add_contact;
 mod delete_contact;
 mod edit_contact;
 mod get_contacts;
+mod import_contacts;
+mod share_contact;
 
 pub use add_contact::*;
 pub use delete_contact::*;
 pub use edit_contact::*;
-pub use get_contacts::*;
\ No newline at end of file
+pub use get_contacts::*;
+pub use import_contacts::*;
+pub use share_contact::*;
@@ -0,0 +1,148 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::{scope, ApiAuth};
+use crate::domain::{
+    delete_contact_share,
+    query_contact_by_id,
+    query_contact_permission,
+    query_contact_shares,
+    query_shared_contacts,
+    query_user_id_by_email,
+    upsert_contact_share,
+    ContactAccess,
+    ContactPermission,
+    ContactResponse,
+    ContactShareParams,
+    ContactViewer,
+    ShareContactData,
+    UnshareContactData,
+    UserEmail
+};
+use crate::error::ContentError;
+use crate::utils::active_org;
+
+/// Grants another user access to one of your private contacts. Sharing
+/// again with the same user changes their access level.
+#[tracing::instrument(
+    skip(req, pool, json)
+)]
+pub async fn share_contact(
+    req:  HttpRequest,
+    pool: web::Data<PgPool>,
+    json: web::Json<ShareContactData>,
+    _:    ApiAuth<scope::ContactsWrite>
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let contact = owned_contact(&req, &json.contact_id, user_id, &pool).await?;
+
+    if !contact.is_private {
+        return Err(ContentError::ValidationError(
+            "Only private contacts can be shared".to_string()
+        ));
+    }
+
+    let grantee = grantee_id(&json.email, &pool).await?;
+
+    if &grantee == user_id {
+        return Err(ContentError::ValidationError(
+            "You can't share a contact with yourself".to_string()
+        ));
+    }
+
+    let access = json.access.unwrap_or(ContactAccess::Read);
+
+    upsert_contact_share(&contact.contact_id, &grantee, user_id, access, &pool)
+        .await
+        .context("Failed to share contact")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    skip(req, pool, json)
+)]
+pub async fn unshare_contact(
+    req:  HttpRequest,
+    pool: web::Data<PgPool>,
+    json: web::Json<UnshareContactData>,
+    _:    ApiAuth<scope::ContactsWrite>
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let contact = owned_contact(&req, &json.contact_id, user_id, &pool).await?;
+    let grantee = grantee_id(&json.email, &pool).await?;
+
+    delete_contact_share(&contact.contact_id, &grantee, &pool)
+        .await
+        .context("Failed to remove contact share")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    skip(req, pool)
+)]
+pub async fn get_contact_shares(
+    req:    HttpRequest,
+    pool:   web::Data<PgPool>,
+    params: web::Query<ContactShareParams>,
+    _:      ApiAuth
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let contact = owned_contact(&req, &params.contact_id, user_id, &pool).await?;
+    let shares  = query_contact_shares(&contact.contact_id, &pool)
+        .await
+        .context("Failed to query contact shares from database")?;
+
+    Ok(HttpResponse::Ok().json(shares))
+}
+
+#[tracing::instrument(
+    skip(req, pool)
+)]
+pub async fn get_shared_contacts(
+    req:  HttpRequest,
+    pool: web::Data<PgPool>,
+    _:    ApiAuth
+) -> Result<HttpResponse, ContentError> {
+    let ext      = req.extensions();
+    let user_id  = ext.get::<uuid::Uuid>().unwrap();
+    let contacts = query_shared_contacts(user_id, &pool)
+        .await
+        .context("Failed to query shared contacts from database")?;
+
+    Ok(HttpResponse::Ok().json(contacts))
+}
+
+async fn owned_contact(
+    req:        &HttpRequest,
+    contact_id: &i32,
+    user_id:    &uuid::Uuid,
+    pool:       &PgPool
+) -> Result<ContactResponse, ContentError> {
+    let permission = query_contact_permission(contact_id, user_id, active_org(req).as_ref(), pool)
+        .await
+        .context("Failed to query permission for contact")?;
+
+    if permission != Some(ContactPermission::Owner) {
+        return Err(ContentError::AuthorizationError)
+    }
+
+    query_contact_by_id(pool, contact_id, ContactViewer::Unrestricted)
+        .await
+        .context("Failed to query contact from database")?
+        .ok_or(ContentError::ValidationError("Contact not found".to_string()))
+}
+
+async fn grantee_id(email: &str, pool: &PgPool) -> Result<uuid::Uuid, ContentError> {
+    let email = UserEmail::parse(email.to_string()).map_err(ContentError::ValidationError)?;
+
+    query_user_id_by_email(&email, pool)
+        .await
+        .context("Failed to look up user by email")?
+        .ok_or(ContentError::ValidationError("No user with that email".to_string()))
+}
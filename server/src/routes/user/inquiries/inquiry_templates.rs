@@ -0,0 +1,94 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::JwtMiddleware;
+use crate::domain::{
+    delete_inquiry_template,
+    edit_inquiry_template,
+    insert_inquiry_template,
+    query_inquiry_templates,
+    EditInquiryTemplateData,
+    InquiryTemplateData,
+    InquiryTemplateDeleteData,
+    NewInquiryTemplate
+};
+use crate::error::ContentError;
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn add_inquiry_template(
+    req:  HttpRequest,
+    json: web::Json<InquiryTemplateData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext      = req.extensions();
+    let user_id  = ext.get::<uuid::Uuid>().unwrap();
+    let template: NewInquiryTemplate = json.0.try_into().map_err(ContentError::ValidationError)?;
+    let template = insert_inquiry_template(&template, user_id, &pool)
+        .await
+        .context("Failed to insert inquiry template into database")?;
+
+    Ok(HttpResponse::Ok().json(template))
+}
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn user_edit_inquiry_template(
+    req:  HttpRequest,
+    json: web::Json<EditInquiryTemplateData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext         = req.extensions();
+    let user_id     = ext.get::<uuid::Uuid>().unwrap();
+    let json        = json.0;
+    let template_id = json.template_id;
+    let template: NewInquiryTemplate = json.template.try_into().map_err(ContentError::ValidationError)?;
+
+    let template = edit_inquiry_template(&template_id, &template, user_id, &pool)
+        .await
+        .context("Failed to update inquiry template")?
+        .ok_or(ContentError::ValidationError("Template not found".to_string()))?;
+
+    Ok(HttpResponse::Ok().json(template))
+}
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn user_delete_inquiry_template(
+    req:  HttpRequest,
+    json: web::Json<InquiryTemplateDeleteData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+
+    delete_inquiry_template(&json.template_id, user_id, &pool)
+        .await
+        .context("Failed to delete inquiry template")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    skip(req, pool)
+)]
+pub async fn get_inquiry_templates(
+    req:  HttpRequest,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext       = req.extensions();
+    let user_id   = ext.get::<uuid::Uuid>().unwrap();
+    let templates = query_inquiry_templates(user_id, &pool)
+        .await
+        .context("Failed to query inquiry templates from database")?;
+
+    Ok(HttpResponse::Ok().json(templates))
+}
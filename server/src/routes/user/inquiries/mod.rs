@@ -0,0 +1,5 @@
+mod inquiry_templates;
+mod send_inquiry;
+
+pub use inquiry_templates::*;
+pub use send_inquiry::*;
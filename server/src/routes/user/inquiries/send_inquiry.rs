@@ -0,0 +1,148 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::auth::JwtMiddleware;
+use crate::domain::{
+    count_recent_inquiries,
+    enqueue_email,
+    format_requested_dates,
+    insert_outreach,
+    insert_sent_inquiry,
+    query_contact_by_id,
+    query_inquiry_template,
+    query_sent_inquiries,
+    ContactViewer,
+    InquiryValues,
+    NewOutboxEmail,
+    NewOutreach,
+    NewSentInquiry,
+    OptionalStringInput,
+    OutreachChannel,
+    OutreachOutcome,
+    SendInquiryData,
+    UserEmail
+};
+use crate::error::ContentError;
+use crate::routes::get_email;
+use crate::startup::DailyInquiryLimit;
+use crate::utils::active_org;
+
+/// Renders one of the user's templates for a contact and queues it to be
+/// emailed from the platform, with replies going to the user. Sends are
+/// logged to the contact's outreach history in the same transaction.
+#[tracing::instrument(
+    skip(req, json, pool, limit)
+)]
+pub async fn send_inquiry(
+    req:   HttpRequest,
+    json:  web::Json<SendInquiryData>,
+    pool:  web::Data<PgPool>,
+    limit: web::Data<DailyInquiryLimit>,
+    _:     JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let org_id  = active_org(&req).map(|org| org.org_id);
+
+    let template = query_inquiry_template(&json.template_id, user_id, &pool)
+        .await
+        .context("Failed to query inquiry template from database")?
+        .ok_or(ContentError::ValidationError("Template not found".to_string()))?;
+
+    let contact = query_contact_by_id(&pool, &json.contact_id, ContactViewer::User(user_id, org_id))
+        .await
+        .context("Failed to query contact from database")?
+        .ok_or(ContentError::ValidationError("Contact not found".to_string()))?;
+
+    let recipient = contact.email
+        .ok_or(ContentError::ValidationError("Contact has no email address".to_string()))
+        .and_then(|email| UserEmail::parse(email).map_err(ContentError::ValidationError))?;
+
+    let values = InquiryValues {
+        venue_name: contact.display_name,
+        city:       contact.city,
+        dates:      format_requested_dates(&json.requested_from, &json.requested_to)
+            .map_err(ContentError::ValidationError)?,
+        band_link:  OptionalStringInput::parse(json.band_link.clone()),
+    };
+    let rendered = template.render(&values).map_err(ContentError::ValidationError)?;
+    let reply_to = get_email(*user_id, &pool).await?;
+    let reply_to = UserEmail::parse(reply_to).map_err(ContentError::ValidationError)?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let sent = count_recent_inquiries(user_id, &mut transaction)
+        .await
+        .context("Failed to count recent inquiries")?;
+
+    if sent >= limit.0 {
+        return Err(ContentError::RateLimitError(format!(
+            "You can send {} inquiries a day. Try again tomorrow",
+            limit.0
+        )))
+    }
+
+    let outreach = NewOutreach {
+        contact_id:     json.contact_id,
+        outreach_date:  Utc::now().date_naive(),
+        channel:        OutreachChannel::Email,
+        requested_from: json.requested_from,
+        requested_to:   json.requested_to,
+        outcome:        OutreachOutcome::NoReply,
+        notes:          Some(format!("Sent \"{}\" through the platform", template.template_name)),
+    };
+    let outreach = insert_outreach(&outreach, user_id, &mut transaction)
+        .await
+        .context("Failed to log inquiry to outreach")?;
+    let inquiry  = NewSentInquiry {
+        contact_id:  json.contact_id,
+        template_id: template.template_id,
+        outreach_id: outreach.outreach_id,
+        recipient:   recipient.as_ref(),
+        subject:     &rendered.subject,
+        body:        &rendered.text_body,
+    };
+    let inquiry  = insert_sent_inquiry(&inquiry, user_id, &mut transaction)
+        .await
+        .context("Failed to log sent inquiry")?;
+    let email    = NewOutboxEmail {
+        recipient: &recipient,
+        reply_to:  Some(&reply_to),
+        subject:   &rendered.subject,
+        html_body: &rendered.html_body,
+        text_body: &rendered.text_body,
+    };
+
+    enqueue_email(&email, &mut transaction)
+        .await
+        .context("Failed to queue inquiry email")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to send inquiry")?;
+
+    Ok(HttpResponse::Ok().json(inquiry))
+}
+
+#[tracing::instrument(
+    skip(req, pool)
+)]
+pub async fn get_sent_inquiries(
+    req:  HttpRequest,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext       = req.extensions();
+    let user_id   = ext.get::<uuid::Uuid>().unwrap();
+    let inquiries = query_sent_inquiries(user_id, &pool)
+        .await
+        .context("Failed to query sent inquiries from database")?;
+
+    Ok(HttpResponse::Ok().json(inquiries))
+}
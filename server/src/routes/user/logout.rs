@@ -1,20 +1,66 @@
-use actix_web::{HttpRequest, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 use actix_web::cookie::{time::Duration, Cookie};
+use sqlx::PgPool;
 
-use crate::auth::JwtMiddleware;
+use crate::auth::{
+    revoke_all_sessions,
+    revoke_api_tokens,
+    revoke_refresh_token,
+    revoke_refresh_tokens,
+    revoke_token,
+    JwtMiddleware,
+    REFRESH_TOKEN_COOKIE
+};
+use crate::error::ContentError;
 
-#[tracing::instrument()]
+#[tracing::instrument(
+    skip(req, pool, redis, jwt)
+)]
 pub async fn log_out(
-    _req:  HttpRequest,
-    _:    JwtMiddleware
-) -> HttpResponse {
-    let cookie = Cookie::build("token", "")
+    req:   HttpRequest,
+    pool:  web::Data<PgPool>,
+    redis: web::Data<redis::Client>,
+    jwt:   JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let mut conn = redis.get_tokio_connection().await?;
+    revoke_token(&mut conn, &jwt.claims).await?;
+
+    if let Some(cookie) = req.cookie(REFRESH_TOKEN_COOKIE) {
+        revoke_refresh_token(cookie.value(), &**pool).await?;
+    }
+
+    Ok(logged_out_response())
+}
+
+/// Logs the user out on every device, not just this one, and revokes their
+/// API tokens
+#[tracing::instrument(
+    skip(pool, redis, jwt)
+)]
+pub async fn log_out_everywhere(
+    pool:  web::Data<PgPool>,
+    redis: web::Data<redis::Client>,
+    jwt:   JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let mut conn = redis.get_tokio_connection().await?;
+    revoke_all_sessions(&mut conn, &jwt.user_id).await?;
+    revoke_refresh_tokens(&jwt.user_id, &**pool).await?;
+    revoke_api_tokens(&jwt.user_id, &**pool).await?;
+
+    Ok(logged_out_response())
+}
+
+fn logged_out_response() -> HttpResponse {
+    HttpResponse::Ok()
+        .cookie(expired_cookie("token"))
+        .cookie(expired_cookie(REFRESH_TOKEN_COOKIE))
+        .finish()
+}
+
+fn expired_cookie(name: &str) -> Cookie<'static> {
+    Cookie::build(name.to_owned(), "")
         .path("/")
         .max_age(Duration::new(-1, 0))
         .http_only(true)
-        .finish();
-    
-    HttpResponse::Ok()
-        .cookie(cookie)
         .finish()
 }
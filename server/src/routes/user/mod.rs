@@ -1,9 +1,21 @@
+mod api_tokens;
 mod contacts;
+mod inquiries;
 mod logout;
+mod organisations;
+mod outreach;
 mod password;
 mod reviews;
+mod tours;
+mod two_factor;
 
+pub use api_tokens::*;
 pub use contacts::*;
-pub use logout::log_out;
+pub use inquiries::*;
+pub use logout::{log_out, log_out_everywhere};
+pub use organisations::*;
+pub use outreach::*;
 pub use password::*;
-pub use reviews::*;
\ No newline at end of file
+pub use reviews::*;
+pub use tours::*;
+pub use two_factor::*;
\ No newline at end of file
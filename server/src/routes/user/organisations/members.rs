@@ -0,0 +1,191 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use chrono::Duration;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::auth::JwtMiddleware;
+use crate::domain::{
+    delete_invite,
+    delete_member,
+    enqueue_email,
+    insert_invite,
+    query_invite_for_user,
+    query_members,
+    query_organisation_name,
+    upsert_member,
+    ActiveOrganisation,
+    InviteMemberData,
+    InviteParams,
+    MemberData,
+    NewOutboxEmail,
+    OrgRole,
+    UserEmail
+};
+use crate::email_templates::EmailTemplate;
+use crate::error::ContentError;
+use crate::startup::ApplicationBaseUrl;
+use crate::utils::{active_org, generate_token, is_token_expired};
+
+const INVITE_EXPIRY_DAYS: i64 = 7;
+
+#[tracing::instrument(
+    skip(req, pool)
+)]
+pub async fn get_members(
+    req:  HttpRequest,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let org     = require_org(&req)?;
+    let members = query_members(&org.org_id, &pool)
+        .await
+        .context("Failed to query organisation members from database")?;
+
+    Ok(HttpResponse::Ok().json(members))
+}
+
+/// Emails a link to join the active organisation. Whoever follows it has to
+/// be logged in with the invited email.
+#[tracing::instrument(
+    skip(req, json, pool, base_url)
+)]
+pub async fn invite_member(
+    req:      HttpRequest,
+    json:     web::Json<InviteMemberData>,
+    pool:     web::Data<PgPool>,
+    base_url: web::Data<ApplicationBaseUrl>,
+    _:        JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let org     = require_owner(&req)?;
+    let email   = UserEmail::parse(json.email.clone()).map_err(ContentError::ValidationError)?;
+    let role    = json.role.unwrap_or(OrgRole::Member);
+    let token   = generate_token();
+
+    let org_name = query_organisation_name(&org.org_id, &pool)
+        .await
+        .context("Failed to query organisation from database")?
+        .ok_or(ContentError::ValidationError("Organisation not found".to_string()))?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    insert_invite(&org.org_id, email.as_ref(), role, user_id, &token, &mut transaction)
+        .await
+        .context("Failed to store invite")?;
+    queue_invite_email(&mut transaction, &email, &org_name, &base_url.0, &token)
+        .await
+        .context("Failed to queue invite email")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store invite")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    skip(req, params, pool)
+)]
+pub async fn accept_invite(
+    req:    HttpRequest,
+    params: web::Query<InviteParams>,
+    pool:   web::Data<PgPool>,
+    _:      JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let invite  = query_invite_for_user(&params.token, user_id, &pool)
+        .await
+        .context("Failed to query invite from database")?
+        .ok_or(ContentError::ValidationError("Invite not found".to_string()))?;
+
+    if is_token_expired(invite.created_at, Duration::days(INVITE_EXPIRY_DAYS)) {
+        return Err(ContentError::ValidationError("Invite has expired".to_string()))
+    }
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    upsert_member(&invite.org_id, user_id, invite.role, &mut transaction)
+        .await
+        .context("Failed to add member to organisation")?;
+    delete_invite(&params.token, &mut transaction)
+        .await
+        .context("Failed to remove accepted invite")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to accept invite")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn remove_member(
+    req:  HttpRequest,
+    json: web::Json<MemberData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let org     = require_owner(&req)?;
+
+    // Stops an organisation from being left without an owner
+    if &json.user_id == user_id {
+        return Err(ContentError::ValidationError("Owners can't remove themselves".to_string()))
+    }
+
+    delete_member(&org.org_id, &json.user_id, &pool)
+        .await
+        .context("Failed to remove organisation member")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+fn require_org(req: &HttpRequest) -> Result<ActiveOrganisation, ContentError> {
+    active_org(req)
+        .ok_or(ContentError::ValidationError("Switch to an organisation first".to_string()))
+}
+
+fn require_owner(req: &HttpRequest) -> Result<ActiveOrganisation, ContentError> {
+    let org = require_org(req)?;
+
+    if org.role.can_manage_members() {
+        Ok(org)
+    } else {
+        Err(ContentError::AuthorizationError)
+    }
+}
+
+#[tracing::instrument(
+    name = "Queue an organisation invite email",
+    skip(transaction, email, base_url, token)
+)]
+async fn queue_invite_email(
+    transaction: &mut Transaction<'_, Postgres>,
+    email:       &UserEmail,
+    org_name:    &str,
+    base_url:    &str,
+    token:       &str,
+) -> Result<(), anyhow::Error> {
+    let invite_link = format!("{}/user/organisations/accept-invite?token={}", base_url, token);
+    let rendered    = EmailTemplate::OrganisationInvite.render(&[
+        ("org_name", org_name),
+        ("invite_link", &invite_link),
+    ])?;
+
+    enqueue_email(&NewOutboxEmail::from_template(email, &rendered), transaction).await?;
+
+    Ok(())
+}
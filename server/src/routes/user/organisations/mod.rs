@@ -0,0 +1,5 @@
+mod members;
+mod user_organisations;
+
+pub use members::*;
+pub use user_organisations::*;
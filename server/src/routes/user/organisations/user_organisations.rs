@@ -0,0 +1,115 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::auth::{
+    set_refresh_token_org,
+    token_cookie,
+    JwtKeys,
+    JwtMiddleware,
+    TokenClaims,
+    REFRESH_TOKEN_COOKIE
+};
+use crate::configuration::JWTSettings;
+use crate::domain::{
+    insert_organisation,
+    query_membership,
+    query_user_organisations,
+    NewOrganisation,
+    NewOrganisationData,
+    SwitchOrganisationData
+};
+use crate::error::ContentError;
+
+/// Creates an organisation with the user as its owner
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn add_organisation(
+    req:  HttpRequest,
+    json: web::Json<NewOrganisationData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let org: NewOrganisation = json.0.try_into().map_err(ContentError::ValidationError)?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let org = insert_organisation(&org, user_id, &mut transaction)
+        .await
+        .context("Failed to insert new organisation into database")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to add new organisation")?;
+
+    Ok(HttpResponse::Ok().json(org))
+}
+
+#[tracing::instrument(
+    skip(req, pool)
+)]
+pub async fn get_organisations(
+    req:  HttpRequest,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let orgs    = query_user_organisations(user_id, &pool)
+        .await
+        .context("Failed to query organisations from database")?;
+
+    Ok(HttpResponse::Ok().json(orgs))
+}
+
+/// Issues a new token acting for one of the user's organisations, or for
+/// themselves again without an `orgId`
+#[tracing::instrument(
+    skip(req, json, pool, jwt_settings, jwt_keys, jwt)
+)]
+pub async fn switch_organisation(
+    req:          HttpRequest,
+    json:         web::Json<SwitchOrganisationData>,
+    pool:         web::Data<PgPool>,
+    jwt_settings: web::Data<JWTSettings>,
+    jwt_keys:     web::Data<JwtKeys>,
+    jwt:          JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let role    = ext.get::<String>().unwrap();
+
+    if let Some(org_id) = &json.org_id {
+        query_membership(org_id, user_id, &pool)
+            .await
+            .context("Failed to query organisation membership")?
+            .ok_or(ContentError::AuthorizationError)?;
+    }
+
+    if let Some(cookie) = req.cookie(REFRESH_TOKEN_COOKIE) {
+        set_refresh_token_org(cookie.value(), user_id, json.org_id, &**pool)
+            .await
+            .context("Failed to update refresh token organisation")?;
+    }
+
+    let claims = TokenClaims::new(user_id, role, json.org_id, &jwt.claims.epoch, &jwt_settings);
+    let token  = claims.encode(&jwt_keys);
+    let cookie = token_cookie(&token, &jwt_settings);
+
+    Ok(
+        HttpResponse::Ok()
+            .cookie(cookie)
+            .json(json!({
+                "status": "success",
+                "token": token
+            }))
+    )
+}
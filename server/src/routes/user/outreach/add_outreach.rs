@@ -0,0 +1,51 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::JwtMiddleware;
+use crate::domain::{
+    insert_outreach,
+    query_contact_by_id,
+    ContactViewer,
+    NewOutreach,
+    NewOutreachData
+};
+use crate::error::ContentError;
+use crate::utils::active_org;
+
+/// Logs an inquiry sent to a contact the user can see
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn add_outreach(
+    req:  HttpRequest,
+    json: web::Json<NewOutreachData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext      = req.extensions();
+    let user_id  = ext.get::<uuid::Uuid>().unwrap();
+    let org_id   = active_org(&req).map(|org| org.org_id);
+    let outreach: NewOutreach = json.0.try_into().map_err(ContentError::ValidationError)?;
+
+    query_contact_by_id(&pool, &outreach.contact_id, ContactViewer::User(user_id, org_id))
+        .await
+        .context("Failed to query contact from database")?
+        .ok_or(ContentError::ValidationError("Contact not found".to_string()))?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let outreach = insert_outreach(&outreach, user_id, &mut transaction)
+        .await
+        .context("Failed to insert outreach into database")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to add outreach")?;
+
+    Ok(HttpResponse::Ok().json(outreach))
+}
@@ -0,0 +1,30 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::JwtMiddleware;
+use crate::domain::{delete_outreach, OutreachDeleteData};
+use crate::error::ContentError;
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn user_delete_outreach(
+    req:  HttpRequest,
+    json: web::Json<OutreachDeleteData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+
+    let deleted = delete_outreach(&json.outreach_id, user_id, &pool)
+        .await
+        .context("Failed to delete outreach")?;
+
+    if !deleted {
+        return Err(ContentError::ValidationError("Outreach not found".to_string()))
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
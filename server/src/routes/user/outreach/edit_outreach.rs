@@ -0,0 +1,29 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::JwtMiddleware;
+use crate::domain::{edit_outreach, EditOutreach, EditOutreachData};
+use crate::error::ContentError;
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn user_edit_outreach(
+    req:  HttpRequest,
+    json: web::Json<EditOutreachData>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext      = req.extensions();
+    let user_id  = ext.get::<uuid::Uuid>().unwrap();
+    let outreach: EditOutreach = json.0.try_into().map_err(ContentError::ValidationError)?;
+
+    // Outreach is only ever visible to whoever logged it
+    let outreach = edit_outreach(&outreach, user_id, &pool)
+        .await
+        .context("Failed to update outreach")?
+        .ok_or(ContentError::ValidationError("Outreach not found".to_string()))?;
+
+    Ok(HttpResponse::Ok().json(outreach))
+}
@@ -0,0 +1,57 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::JwtMiddleware;
+use crate::domain::{
+    query_follow_ups_due,
+    query_outreach_for_contact,
+    FollowUpParams,
+    OutreachParams
+};
+use crate::error::ContentError;
+
+const DEFAULT_FOLLOW_UP_DAYS: i32 = 7;
+
+#[tracing::instrument(
+    skip(req, pool)
+)]
+pub async fn get_outreach(
+    req:    HttpRequest,
+    params: web::Query<OutreachParams>,
+    pool:   web::Data<PgPool>,
+    _:      JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext      = req.extensions();
+    let user_id  = ext.get::<uuid::Uuid>().unwrap();
+    let outreach = query_outreach_for_contact(&params.contact_id, user_id, &pool)
+        .await
+        .context("Failed to query outreach from database")?;
+
+    Ok(HttpResponse::Ok().json(outreach))
+}
+
+/// Inquiries with no reply after `days` (a week by default)
+#[tracing::instrument(
+    skip(req, pool)
+)]
+pub async fn get_follow_ups(
+    req:    HttpRequest,
+    params: web::Query<FollowUpParams>,
+    pool:   web::Data<PgPool>,
+    _:      JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let days    = params.days.unwrap_or(DEFAULT_FOLLOW_UP_DAYS);
+
+    if days < 0 {
+        return Err(ContentError::ValidationError("Days cannot be negative".to_string()))
+    }
+
+    let follow_ups = query_follow_ups_due(user_id, days, &pool)
+        .await
+        .context("Failed to query follow-ups from database")?;
+
+    Ok(HttpResponse::Ok().json(follow_ups))
+}
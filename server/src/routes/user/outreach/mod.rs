@@ -0,0 +1,9 @@
+mod add_outreach;
+mod delete_outreach;
+mod edit_outreach;
+mod get_outreach;
+
+pub use add_outreach::*;
+pub use delete_outreach::*;
+pub use edit_outreach::*;
+pub use get_outreach::*;
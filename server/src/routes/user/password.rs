@@ -4,7 +4,20 @@ use secrecy::{ExposeSecret, Secret};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::auth::{validate_credentials, AuthError, Credentials, JwtMiddleware};
+use crate::auth::{
+    issue_refresh_token,
+    revoke_all_sessions,
+    revoke_api_tokens,
+    revoke_refresh_tokens,
+    session_response,
+    validate_credentials,
+    AuthError,
+    Credentials,
+    JwtKeys,
+    JwtMiddleware,
+    TokenClaims
+};
+use crate::configuration::JWTSettings;
 
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -14,14 +27,19 @@ pub struct PasswordResetData {
     new_password_check: Secret<String>,
 }
 
+/// Changing the password logs the user out everywhere else and revokes their
+/// API tokens. This device gets a new token.
 #[tracing::instrument(
-    skip(req, json, pool)
+    skip(req, json, pool, redis, jwt_settings, jwt_keys, jwt)
 )]
 pub async fn change_password(
-    req:  HttpRequest,
-    json: web::Json<PasswordResetData>,
-    pool: web::Data<PgPool>,
-    _:    JwtMiddleware
+    req:          HttpRequest,
+    json:         web::Json<PasswordResetData>,
+    pool:         web::Data<PgPool>,
+    redis:        web::Data<redis::Client>,
+    jwt_settings: web::Data<JWTSettings>,
+    jwt_keys:     web::Data<JwtKeys>,
+    jwt:          JwtMiddleware
 ) -> Result<HttpResponse, AuthError> {
     let ext     = req.extensions();
     let user_id = ext.get::<uuid::Uuid>().unwrap();
@@ -43,15 +61,35 @@ pub async fn change_password(
     crate::auth::change_password(*user_id, json.0.new_password, &pool)
         .await
         .context("Failed to update password")?;
-    
-    Ok(HttpResponse::Ok().finish())
+
+    let mut conn = redis.get_tokio_connection()
+        .await
+        .context("Could not get async Redis connection")?;
+    let epoch    = revoke_all_sessions(&mut conn, user_id)
+        .await
+        .context("Failed to revoke sessions")?;
+
+    revoke_refresh_tokens(user_id, &**pool)
+        .await
+        .context("Failed to revoke refresh tokens")?;
+    revoke_api_tokens(user_id, &**pool)
+        .await
+        .context("Failed to revoke API tokens")?;
+
+    let claims        = TokenClaims::new(user_id, &jwt.role, jwt.claims.org, &epoch, &jwt_settings);
+    let token         = claims.encode(&jwt_keys);
+    let refresh_token = issue_refresh_token(user_id, None, jwt.claims.org, &jwt_settings, &**pool)
+        .await
+        .context("Failed to store refresh token")?;
+
+    Ok(session_response(&token, &refresh_token, &jwt_settings))
 }
 
 #[tracing::instrument(
     name = "Get email",
     skip(pool)
 )]
-async fn get_email(user_id: Uuid, pool: &PgPool) -> Result<String, anyhow::Error> {
+pub async fn get_email(user_id: Uuid, pool: &PgPool) -> Result<String, anyhow::Error> {
     let row = sqlx::query!(r#"SELECT email FROM users WHERE user_id = $1"#, user_id)
         .fetch_one(pool)
         .await
@@ -5,6 +5,7 @@ use sqlx::{PgPool, Postgres, Transaction};
 use crate::auth::JwtMiddleware;
 use crate::domain::{insert_review, CreateReviewData, NewReview};
 use crate::error::ContentError;
+use crate::utils::new_data_org;
 
 #[tracing::instrument(
     skip(req, json, pool)
@@ -17,6 +18,7 @@ pub async fn review_contact(
 ) -> Result<HttpResponse, ContentError> {
     let ext     = req.extensions();
     let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let org_id  = new_data_org(&req)?;
     let review: NewReview = json.0.try_into().map_err(ContentError::ValidationError)?;
 
     let mut transaction = pool
@@ -28,7 +30,7 @@ pub async fn review_contact(
         .await
         .context("Failed to find Contact with provided contact_id")?;
 
-    let review = insert_review(review, user_id, &mut transaction)
+    let review = insert_review(review, user_id, org_id, &mut transaction)
         .await
         .context("Failed to insert new review into database")?;
     
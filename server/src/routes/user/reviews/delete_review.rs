@@ -1,10 +1,10 @@
-use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 use anyhow::Context;
 use sqlx::PgPool;
 
 use crate::auth::JwtMiddleware;
 use crate::domain::{
-    delete_review, ReviewDeleteData
+    delete_review, query_review_by_id, ReviewDeleteData
 };
 use crate::error::ContentError;
 use crate::utils::user_matches;
@@ -18,11 +18,13 @@ pub async fn user_delete_review(
     pool: web::Data<PgPool>,
     _:    JwtMiddleware,
 ) -> Result<HttpResponse, ContentError> {
-    let ext     = req.extensions();
-    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let review = query_review_by_id(&json.review_id, &pool)
+        .await
+        .context("Failed to query review from database")?
+        .ok_or(ContentError::ValidationError("Review not found".to_string()))?;
 
-    user_matches(user_id, &json.user_id)?;
-    delete_review(&json.review_id, user_id, &pool)
+    user_matches(&req, &review.user_id, &review.org_id)?;
+    delete_review(&review.review_id, &review.user_id, &pool)
         .await
         .context("Failed to delete review")?;
     
@@ -1,10 +1,10 @@
-use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 use anyhow::Context;
 use sqlx::PgPool;
 
 use crate::auth::JwtMiddleware;
 use crate::domain::{
-    edit_review, Review, ReviewEditData
+    edit_review, query_review_by_id, Review, ReviewEditData
 };
 use crate::error::ContentError;
 use crate::utils::user_matches;
@@ -18,12 +18,13 @@ pub async fn user_edit_review(
     pool: web::Data<PgPool>,
     _:    JwtMiddleware,
 ) -> Result<HttpResponse, ContentError> {
-    let ext     = req.extensions();
-    let user_id = ext.get::<uuid::Uuid>().unwrap();
     let review: Review = json.0.try_into().map_err(ContentError::ValidationError)?;
+    let existing       = query_review_by_id(&review.review_id, &pool)
+        .await
+        .context("Failed to query review from database")?
+        .ok_or(ContentError::ValidationError("Review not found".to_string()))?;
 
-    user_matches(user_id, &review.user_id)
-        .context("User IDs don't match when editing review")?;
+    user_matches(&req, &existing.user_id, &existing.org_id)?;
     let review = edit_review(review, &pool)
         .await
         .context("Failed to update review")?;
@@ -5,6 +5,7 @@ use sqlx::PgPool;
 use crate::auth::JwtMiddleware;
 use crate::domain::query_reviews_by_user;
 use crate::error::ContentError;
+use crate::utils::active_org;
 
 #[tracing::instrument(
     skip(req, pool)
@@ -16,8 +17,9 @@ pub async fn user_get_reviews(
 ) -> Result<HttpResponse, ContentError> {
     let ext     = req.extensions();
     let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let org_id  = active_org(&req).map(|org| org.org_id);
 
-    let reviews = query_reviews_by_user(user_id, &pool)
+    let reviews = query_reviews_by_user(user_id, org_id, &pool)
         .await
         .context("Failed to get reviews for user")?;
     
@@ -0,0 +1,29 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::{scope, ApiAuth};
+use crate::domain::{insert_tour, NewTour, NewTourData};
+use crate::error::ContentError;
+use crate::utils::new_data_org;
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn add_tour(
+    req:  HttpRequest,
+    json: web::Json<NewTourData>,
+    pool: web::Data<PgPool>,
+    _:    ApiAuth<scope::ToursWrite>
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let org_id  = new_data_org(&req)?;
+    let tour: NewTour = json.0.try_into().map_err(ContentError::ValidationError)?;
+
+    let tour = insert_tour(&tour, user_id, org_id, &pool)
+        .await
+        .context("Failed to insert new tour into database")?;
+
+    Ok(HttpResponse::Ok().json(tour))
+}
@@ -0,0 +1,95 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::JwtMiddleware;
+use crate::domain::{
+    delete_calendar_tokens,
+    insert_calendar_token,
+    query_tour_by_id,
+    CalendarSubscription,
+    TourParams
+};
+use crate::error::ContentError;
+use crate::startup::ApplicationBaseUrl;
+use crate::utils::{generate_token, user_matches};
+
+/// Creates the subscription URL for a tour, revoking any earlier one so a
+/// leaked link can be shut off by making a new one.
+#[tracing::instrument(
+    skip(req, json, pool, base_url)
+)]
+pub async fn create_calendar_token(
+    req:      HttpRequest,
+    json:     web::Json<TourParams>,
+    pool:     web::Data<PgPool>,
+    base_url: web::Data<ApplicationBaseUrl>,
+    _:        JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let tour    = query_tour_by_id(&json.tour_id, &pool)
+        .await
+        .context("Failed to query tour from database")?
+        .ok_or(ContentError::ValidationError("Tour not found".to_string()))?;
+
+    user_matches(&req, &tour.user_id, &tour.org_id)?;
+
+    let token           = generate_token();
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    delete_calendar_tokens(&tour.tour_id, &mut transaction)
+        .await
+        .context("Failed to revoke old calendar tokens")?;
+    insert_calendar_token(&tour.tour_id, user_id, &token, &mut transaction)
+        .await
+        .context("Failed to store calendar token")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to store calendar token")?;
+
+    let subscription = CalendarSubscription {
+        url:   format!("{}/tour-calendar?token={}", base_url.0, token),
+        token,
+    };
+
+    Ok(HttpResponse::Ok().json(subscription))
+}
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn revoke_calendar_token(
+    req:  HttpRequest,
+    json: web::Json<TourParams>,
+    pool: web::Data<PgPool>,
+    _:    JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let tour = query_tour_by_id(&json.tour_id, &pool)
+        .await
+        .context("Failed to query tour from database")?
+        .ok_or(ContentError::ValidationError("Tour not found".to_string()))?;
+
+    user_matches(&req, &tour.user_id, &tour.org_id)?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    delete_calendar_tokens(&tour.tour_id, &mut transaction)
+        .await
+        .context("Failed to revoke calendar tokens")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to revoke calendar tokens")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
@@ -0,0 +1,30 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::{scope, ApiAuth};
+use crate::domain::{delete_tour, query_tour_by_id, TourDeleteData};
+use crate::error::ContentError;
+use crate::utils::user_matches;
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn user_delete_tour(
+    req:  HttpRequest,
+    json: web::Json<TourDeleteData>,
+    pool: web::Data<PgPool>,
+    _:    ApiAuth<scope::ToursWrite>
+) -> Result<HttpResponse, ContentError> {
+    let tour = query_tour_by_id(&json.tour_id, &pool)
+        .await
+        .context("Failed to query tour from database")?
+        .ok_or(ContentError::ValidationError("Tour not found".to_string()))?;
+
+    user_matches(&req, &tour.user_id, &tour.org_id)?;
+    delete_tour(&tour.tour_id, &pool)
+        .await
+        .context("Failed to delete tour")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
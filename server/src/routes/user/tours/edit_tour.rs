@@ -0,0 +1,50 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::{scope, ApiAuth};
+use crate::domain::{
+    count_dates_outside_tour,
+    query_tour_by_id,
+    update_tour,
+    EditTourData,
+    Tour
+};
+use crate::error::ContentError;
+use crate::utils::user_matches;
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn user_edit_tour(
+    req:  HttpRequest,
+    json: web::Json<EditTourData>,
+    pool: web::Data<PgPool>,
+    _:    ApiAuth<scope::ToursWrite>
+) -> Result<HttpResponse, ContentError> {
+    let tour: Tour = json.0.try_into().map_err(ContentError::ValidationError)?;
+
+    let existing = query_tour_by_id(&tour.tour_id, &pool)
+        .await
+        .context("Failed to query tour from database")?
+        .ok_or(ContentError::ValidationError("Tour not found".to_string()))?;
+
+    user_matches(&req, &existing.user_id, &existing.org_id)?;
+
+    // Shrinking a tour shouldn't silently orphan dates that have been booked
+    let outside = count_dates_outside_tour(&tour, &pool)
+        .await
+        .context("Failed to check tour dates against new range")?;
+
+    if outside > 0 {
+        return Err(ContentError::ValidationError(
+            "Move or delete dates outside of the new range first".to_string()
+        ))
+    }
+
+    let tour = update_tour(&tour, &pool)
+        .await
+        .context("Failed to update tour")?;
+
+    Ok(HttpResponse::Ok().json(tour))
+}
@@ -0,0 +1,166 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web::http::header::{Charset, ContentDisposition, DispositionParam, DispositionType, ExtendedValue};
+use actix_web::web::Bytes;
+use anyhow::Context;
+use futures_util::StreamExt;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::auth::ApiAuth;
+use crate::domain::{
+    query_tour_by_id,
+    query_tour_id_by_calendar_token,
+    stream_exported_dates,
+    CalendarExportParams,
+    CalendarFeedParams,
+    StringInput,
+    TourParams
+};
+use crate::error::ContentError;
+use crate::exporter::{IcsExporter, SongkickExporter, TourExporter};
+use crate::utils::user_can_view;
+
+#[tracing::instrument(
+    skip(req, params, pool)
+)]
+pub async fn export_songkick(
+    req:    HttpRequest,
+    params: web::Query<TourParams>,
+    pool:   web::Data<PgPool>,
+    _:      ApiAuth
+) -> Result<HttpResponse, ContentError> {
+    let tour = query_tour_by_id(&params.tour_id, &pool)
+        .await
+        .context("Failed to query tour from database")?
+        .ok_or(ContentError::ValidationError("Tour not found".to_string()))?;
+
+    user_can_view(&req, &tour.user_id, &tour.org_id)?;
+
+    let filename = format!("{}-songkick", tour.tour_name);
+
+    Ok(export_response(SongkickExporter, &filename, tour.tour_id, false, pool))
+}
+
+#[tracing::instrument(
+    skip(req, params, pool)
+)]
+pub async fn export_ics(
+    req:    HttpRequest,
+    params: web::Query<CalendarExportParams>,
+    pool:   web::Data<PgPool>,
+    _:      ApiAuth
+) -> Result<HttpResponse, ContentError> {
+    let tour = query_tour_by_id(&params.tour_id, &pool)
+        .await
+        .context("Failed to query tour from database")?
+        .ok_or(ContentError::ValidationError("Tour not found".to_string()))?;
+
+    user_can_view(&req, &tour.user_id, &tour.org_id)?;
+
+    let include_pending = params.include_pending.unwrap_or(false);
+    let exporter        = IcsExporter::new(tour.tour_name.clone());
+
+    Ok(export_response(exporter, &tour.tour_name, tour.tour_id, include_pending, pool))
+}
+
+/// Subscription feed for calendar apps, which can't send the JWT cookie. The
+/// token in the URL stands in for it.
+#[tracing::instrument(
+    skip(params, pool)
+)]
+pub async fn tour_calendar_feed(
+    params: web::Query<CalendarFeedParams>,
+    pool:   web::Data<PgPool>
+) -> Result<HttpResponse, ContentError> {
+    let token   = StringInput::parse(params.token.clone());
+    let tour_id = query_tour_id_by_calendar_token(&token, &pool)
+        .await
+        .context("Failed to look up calendar token")?
+        .ok_or(ContentError::AuthorizationError)?;
+    let tour    = query_tour_by_id(&tour_id, &pool)
+        .await
+        .context("Failed to query tour from database")?
+        .ok_or(ContentError::ValidationError("Tour not found".to_string()))?;
+
+    let include_pending = params.include_pending.unwrap_or(false);
+    let exporter        = IcsExporter::new(tour.tour_name.clone());
+
+    Ok(export_response(exporter, &tour.tour_name, tour.tour_id, include_pending, pool))
+}
+
+/// Rows rendered ahead of the client. Past this the export waits for it to
+/// catch up rather than buffering the tour.
+const EXPORT_BUFFER_ROWS: usize = 16;
+
+/// Streams the file as the dates come back from the database. By the time
+/// a row fails the headers are gone, so the download is just cut short.
+fn export_response<E: TourExporter + 'static>(
+    exporter:        E,
+    filename:        &str,
+    tour_id:         Uuid,
+    include_pending: bool,
+    pool:            web::Data<PgPool>
+) -> HttpResponse {
+    let content_type       = exporter.content_type();
+    let disposition        = content_disposition(&format!("{}.{}", filename, exporter.file_extension()));
+    let (sender, receiver) = mpsc::channel::<Result<Bytes, ContentError>>(EXPORT_BUFFER_ROWS);
+
+    actix_web::rt::spawn(async move {
+        if let Some(header) = exporter.header() {
+            if sender.send(Ok(Bytes::from(header))).await.is_err() {
+                return
+            }
+        }
+
+        let mut dates = stream_exported_dates(&tour_id, include_pending, &pool);
+
+        while let Some(date) = dates.next().await {
+            let chunk = date
+                .map(|date| Bytes::from(exporter.row(&date)))
+                .context("Failed to query dates for tour")
+                .map_err(ContentError::from);
+            let failed = chunk.is_err();
+
+            // Either way there's nothing more to send
+            if sender.send(chunk).await.is_err() || failed {
+                return
+            }
+        }
+
+        if let Some(footer) = exporter.footer() {
+            let _ = sender.send(Ok(Bytes::from(footer))).await;
+        }
+    });
+
+    let body = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header(disposition)
+        .streaming(body)
+}
+
+/// Tour names can be anything, so `filename` gets a copy with only safe
+/// ASCII in it and `filename*` has the real name, RFC 5987 encoded, for
+/// clients that understand it
+fn content_disposition(filename: &str) -> ContentDisposition {
+    let ascii = filename
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || " .-_".contains(c) { c } else { '_' })
+        .collect();
+
+    ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters:  vec![
+            DispositionParam::Filename(ascii),
+            DispositionParam::FilenameExt(ExtendedValue {
+                charset:      Charset::Ext("UTF-8".to_string()),
+                language_tag: None,
+                value:        filename.as_bytes().to_vec(),
+            }),
+        ],
+    }
+}
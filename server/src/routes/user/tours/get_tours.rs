@@ -0,0 +1,65 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::ApiAuth;
+use crate::domain::{
+    query_tour_by_id,
+    query_tour_dates,
+    query_tours_by_user,
+    TourParams,
+    TourResponse
+};
+use crate::error::ContentError;
+use crate::utils::{active_org, user_can_view};
+
+#[tracing::instrument(
+    skip(req, pool)
+)]
+pub async fn user_get_tours(
+    req:  HttpRequest,
+    pool: web::Data<PgPool>,
+    _:    ApiAuth
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let org_id  = active_org(&req).map(|org| org.org_id);
+    let tours   = query_tours_by_user(user_id, org_id, &pool)
+        .await
+        .context("Failed to get tours from database")?;
+
+    Ok(HttpResponse::Ok().json(tours))
+}
+
+#[tracing::instrument(
+    skip(req, params, pool)
+)]
+pub async fn user_get_tour(
+    req:    HttpRequest,
+    params: web::Query<TourParams>,
+    pool:   web::Data<PgPool>,
+    _:      ApiAuth
+) -> Result<HttpResponse, ContentError> {
+    let tour = query_tour_by_id(&params.tour_id, &pool)
+        .await
+        .context("Failed to query tour from database")?
+        .ok_or(ContentError::ValidationError("Tour not found".to_string()))?;
+
+    user_can_view(&req, &tour.user_id, &tour.org_id)?;
+
+    let dates = query_tour_dates(&tour.tour_id, &pool)
+        .await
+        .context("Failed to query dates for tour")?;
+
+    let response = TourResponse {
+        tour_id:    tour.tour_id,
+        user_id:    tour.user_id,
+        org_id:     tour.org_id,
+        tour_name:  tour.tour_name,
+        start_date: tour.start_date,
+        end_date:   tour.end_date,
+        dates
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
@@ -0,0 +1,19 @@
+mod add_tour;
+mod calendar;
+mod delete_tour;
+mod edit_tour;
+mod export_tour;
+mod get_tours;
+mod plan_route;
+mod tour_dates;
+mod tour_legs;
+
+pub use add_tour::*;
+pub use calendar::*;
+pub use delete_tour::*;
+pub use edit_tour::*;
+pub use export_tour::*;
+pub use get_tours::*;
+pub use plan_route::*;
+pub use tour_dates::*;
+pub use tour_legs::*;
@@ -0,0 +1,141 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::{scope, ApiAuth};
+use crate::domain::{
+    insert_route_plan,
+    query_contacts_by_ids,
+    query_shared_contact_ids,
+    ContactResponse,
+    RoutePlan,
+    RoutePlanData,
+    RoutePlanRequest,
+    SaveRoutePlanData,
+    SavedRoutePlan
+};
+use crate::error::ContentError;
+use crate::geocoder::Geocoder;
+use crate::gmaps_api_client::Location;
+use crate::routes::locate_city;
+use crate::routing::RoutingProvider;
+use crate::utils::{active_org, new_data_org};
+
+#[tracing::instrument(
+    skip(req, json, pool, redis, geocoder, routing)
+)]
+pub async fn plan_route(
+    req:      HttpRequest,
+    json:     web::Json<RoutePlanData>,
+    pool:     web::Data<PgPool>,
+    redis:    web::Data<redis::Client>,
+    geocoder: web::Data<dyn Geocoder>,
+    routing:  web::Data<dyn RoutingProvider>,
+    _:        ApiAuth
+) -> Result<HttpResponse, ContentError> {
+    let request: RoutePlanRequest = json.0.try_into()
+        .map_err(ContentError::ValidationError)?;
+
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let start   = locate_city(&request.start_city, &redis, geocoder.as_ref()).await?;
+    let end     = locate_city(&request.end_city, &redis, geocoder.as_ref()).await?;
+
+    let contacts = query_contacts_by_ids(&pool, &request.candidate_ids)
+        .await
+        .context("Failed to query candidate contacts from database")?;
+    let shared   = query_shared_contact_ids(user_id, &pool)
+        .await
+        .context("Failed to query shared contacts from database")?;
+    let org_id   = active_org(&req).map(|org| org.org_id);
+
+    // Other users' private contacts are treated as missing unless shared
+    let candidates: Vec<ContactResponse> = contacts
+        .into_iter()
+        .filter(|c| can_book(c, user_id, org_id, &shared))
+        .filter(|c| request.accepts(c))
+        .collect();
+    let rejected_ids: Vec<i32> = request.candidate_ids
+        .iter()
+        .filter(|id| !candidates.iter().any(|c| &c.contact_id == *id))
+        .copied()
+        .collect();
+
+    // Every candidate here has coordinates, `accepts` checks for them
+    let locations: Vec<Location> = std::iter::once(start)
+        .chain(candidates.iter().map(|c| Location {
+            lat: c.latitude.unwrap_or_default(),
+            lng: c.longitude.unwrap_or_default(),
+        }))
+        .chain(std::iter::once(end))
+        .collect();
+    let matrix = routing.matrix(&locations).await?;
+
+    let plan = RoutePlan::new(&request, start, end, candidates, rejected_ids, &matrix)?;
+
+    Ok(HttpResponse::Ok().json(plan))
+}
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn save_route_plan(
+    req:  HttpRequest,
+    json: web::Json<SaveRoutePlanData>,
+    pool: web::Data<PgPool>,
+    _:    ApiAuth<scope::ToursWrite>
+) -> Result<HttpResponse, ContentError> {
+    let plan: SavedRoutePlan = json.0.try_into()
+        .map_err(ContentError::ValidationError)?;
+
+    let ext         = req.extensions();
+    let user_id     = ext.get::<uuid::Uuid>().unwrap();
+    let contact_ids = plan.contact_ids();
+    let contacts    = query_contacts_by_ids(&pool, &contact_ids)
+        .await
+        .context("Failed to query contacts from database")?;
+    let shared      = query_shared_contact_ids(user_id, &pool)
+        .await
+        .context("Failed to query shared contacts from database")?;
+    let org_id      = new_data_org(&req)?;
+
+    // Private contacts can only be booked by whoever added them or was given access
+    let bookable = contacts
+        .iter()
+        .filter(|c| can_book(c, user_id, org_id, &shared))
+        .count();
+
+    if bookable != contact_ids.len() {
+        return Err(ContentError::ValidationError("Contact not found".to_string()))
+    }
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    let tour = insert_route_plan(&plan, user_id, org_id, &mut transaction)
+        .await
+        .context("Failed to save route plan as a tour")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to save route plan")?;
+
+    Ok(HttpResponse::Ok().json(tour))
+}
+
+fn can_book(
+    contact: &ContactResponse,
+    user_id: &uuid::Uuid,
+    org_id:  Option<uuid::Uuid>,
+    shared:  &[i32]
+) -> bool {
+    let owned = match contact.org_id {
+        Some(_) => contact.org_id == org_id,
+        None => &contact.user_id == user_id,
+    };
+
+    !contact.is_private || owned || shared.contains(&contact.contact_id)
+}
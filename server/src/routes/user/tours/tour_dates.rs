@@ -0,0 +1,161 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::{scope, ApiAuth};
+use crate::domain::{
+    delete_tour_date,
+    delete_tour_date_contact,
+    insert_tour_date,
+    query_contact_by_id,
+    query_tour_by_id,
+    query_tour_date_by_id,
+    update_tour_date,
+    upsert_tour_date_contact,
+    ContactViewer,
+    MoveTourDateData,
+    NewTourDateData,
+    Tour,
+    TourDateContactData,
+    TourDateContactDeleteData,
+    TourDateDeleteData
+};
+use crate::error::ContentError;
+use crate::utils::user_matches;
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn add_tour_date(
+    req:  HttpRequest,
+    json: web::Json<NewTourDateData>,
+    pool: web::Data<PgPool>,
+    _:    ApiAuth<scope::ToursWrite>
+) -> Result<HttpResponse, ContentError> {
+    let tour = query_tour_by_id(&json.tour_id, &pool)
+        .await
+        .context("Failed to query tour from database")?
+        .ok_or(ContentError::ValidationError("Tour not found".to_string()))?;
+
+    user_matches(&req, &tour.user_id, &tour.org_id)?;
+
+    if !tour.includes(&json.show_date) {
+        return Err(ContentError::ValidationError("Date is outside of the tour".to_string()))
+    }
+
+    let tour_date = insert_tour_date(&tour.tour_id, &json.show_date, &pool)
+        .await
+        .context("Failed to insert new tour date into database")?;
+
+    Ok(HttpResponse::Ok().json(tour_date))
+}
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn move_tour_date(
+    req:  HttpRequest,
+    json: web::Json<MoveTourDateData>,
+    pool: web::Data<PgPool>,
+    _:    ApiAuth<scope::ToursWrite>
+) -> Result<HttpResponse, ContentError> {
+    let tour = tour_for_date(&json.tour_date_id, &pool).await?;
+
+    user_matches(&req, &tour.user_id, &tour.org_id)?;
+
+    if !tour.includes(&json.show_date) {
+        return Err(ContentError::ValidationError("Date is outside of the tour".to_string()))
+    }
+
+    // Contacts reference the date row, so they move with it
+    let tour_date = update_tour_date(&json.tour_date_id, &json.show_date, &pool)
+        .await
+        .context("Failed to move tour date")?;
+
+    Ok(HttpResponse::Ok().json(tour_date))
+}
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn user_delete_tour_date(
+    req:  HttpRequest,
+    json: web::Json<TourDateDeleteData>,
+    pool: web::Data<PgPool>,
+    _:    ApiAuth<scope::ToursWrite>
+) -> Result<HttpResponse, ContentError> {
+    let tour = tour_for_date(&json.tour_date_id, &pool).await?;
+
+    user_matches(&req, &tour.user_id, &tour.org_id)?;
+    delete_tour_date(&json.tour_date_id, &pool)
+        .await
+        .context("Failed to delete tour date")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn set_tour_date_contact(
+    req:  HttpRequest,
+    json: web::Json<TourDateContactData>,
+    pool: web::Data<PgPool>,
+    _:    ApiAuth<scope::ToursWrite>
+) -> Result<HttpResponse, ContentError> {
+    let ext     = req.extensions();
+    let user_id = ext.get::<uuid::Uuid>().unwrap();
+    let tour    = tour_for_date(&json.tour_date_id, &pool).await?;
+
+    user_matches(&req, &tour.user_id, &tour.org_id)?;
+
+    // Private contacts can only be booked by whoever added them, users
+    // they've been shared with, or for the organisation that owns them
+    let contact = query_contact_by_id(&pool, &json.contact_id, ContactViewer::User(user_id, tour.org_id))
+        .await
+        .context("Failed to query contact from database")?
+        .ok_or(ContentError::ValidationError("Contact not found".to_string()))?;
+
+    upsert_tour_date_contact(&json.tour_date_id, &contact.contact_id, json.status, &pool)
+        .await
+        .context("Failed to set contact for tour date")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    skip(req, json, pool)
+)]
+pub async fn remove_tour_date_contact(
+    req:  HttpRequest,
+    json: web::Json<TourDateContactDeleteData>,
+    pool: web::Data<PgPool>,
+    _:    ApiAuth<scope::ToursWrite>
+) -> Result<HttpResponse, ContentError> {
+    let tour = tour_for_date(&json.tour_date_id, &pool).await?;
+
+    user_matches(&req, &tour.user_id, &tour.org_id)?;
+    delete_tour_date_contact(&json.tour_date_id, &json.contact_id, &pool)
+        .await
+        .context("Failed to remove contact from tour date")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+async fn tour_for_date(
+    tour_date_id: &Uuid,
+    pool:         &PgPool
+) -> Result<Tour, ContentError> {
+    let tour_date = query_tour_date_by_id(tour_date_id, pool)
+        .await
+        .context("Failed to query tour date from database")?
+        .ok_or(ContentError::ValidationError("Tour date not found".to_string()))?;
+
+    let tour = query_tour_by_id(&tour_date.tour_id, pool)
+        .await
+        .context("Failed to query tour from database")?
+        .ok_or(ContentError::ValidationError("Tour not found".to_string()))?;
+
+    Ok(tour)
+}
@@ -0,0 +1,40 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::auth::ApiAuth;
+use crate::domain::{query_tour_by_id, query_tour_dates, tour_stops, TourLegs, TourLegsParams};
+use crate::error::ContentError;
+use crate::routing::RoutingProvider;
+use crate::utils::user_can_view;
+
+#[tracing::instrument(
+    skip(req, params, pool, routing)
+)]
+pub async fn user_get_tour_legs(
+    req:     HttpRequest,
+    params:  web::Query<TourLegsParams>,
+    pool:    web::Data<PgPool>,
+    routing: web::Data<dyn RoutingProvider>,
+    _:       ApiAuth
+) -> Result<HttpResponse, ContentError> {
+    let max_drive_hours = params.max_drive_hours()
+        .map_err(ContentError::ValidationError)?;
+
+    let tour = query_tour_by_id(&params.tour_id, &pool)
+        .await
+        .context("Failed to query tour from database")?
+        .ok_or(ContentError::ValidationError("Tour not found".to_string()))?;
+
+    user_can_view(&req, &tour.user_id, &tour.org_id)?;
+
+    let dates = query_tour_dates(&tour.tour_id, &pool)
+        .await
+        .context("Failed to query dates for tour")?;
+
+    let (stops, unlocated) = tour_stops(&dates);
+    let locations: Vec<_>  = stops.iter().map(|s| s.location).collect();
+    let route_legs         = routing.legs(&locations).await?;
+
+    Ok(HttpResponse::Ok().json(TourLegs::new(stops, route_legs, max_drive_hours, unlocated)))
+}
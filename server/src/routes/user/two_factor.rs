@@ -0,0 +1,164 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::auth::{
+    delete_totp,
+    enable_totp,
+    generate_recovery_codes,
+    generate_totp_secret,
+    provisioning_uri,
+    query_totp,
+    replace_recovery_codes,
+    store_pending_totp,
+    verify_second_factor,
+    verify_totp,
+    JwtMiddleware,
+    StoredTotp,
+    TwoFactorCodeData
+};
+use crate::error::ContentError;
+use crate::routes::get_email;
+
+/// Starts setting up two-factor. The secret and its `otpauth://` URI, for
+/// showing as a QR code, are returned once; it isn't used until confirmed
+/// with a code through `enable_two_factor`.
+#[tracing::instrument(
+    skip(pool, jwt)
+)]
+pub async fn enrol_two_factor(
+    pool: web::Data<PgPool>,
+    jwt:  JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let secret = generate_totp_secret();
+    let stored = store_pending_totp(&jwt.user_id, &secret, &pool)
+        .await
+        .context("Failed to store TOTP secret")?;
+
+    if !stored {
+        return Err(ContentError::ValidationError("Two-factor authentication is already on".to_string()))
+    }
+
+    let email = get_email(jwt.user_id, &pool).await?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "secret":     secret,
+        "otpauthUri": provisioning_uri(&secret, &email)
+    })))
+}
+
+/// Turns two-factor on once the user shows their authenticator works, and
+/// returns their recovery codes. They aren't shown again.
+#[tracing::instrument(
+    skip(json, pool, redis, jwt)
+)]
+pub async fn enable_two_factor(
+    json:  web::Json<TwoFactorCodeData>,
+    pool:  web::Data<PgPool>,
+    redis: web::Data<redis::Client>,
+    jwt:   JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let totp = query_totp(&jwt.user_id, &**pool)
+        .await
+        .context("Failed to query TOTP secret")?;
+
+    let secret = match totp {
+        Some(StoredTotp { secret, enabled_at: None }) => secret,
+        Some(_) => return Err(ContentError::ValidationError("Two-factor authentication is already on".to_string())),
+        None => return Err(ContentError::ValidationError("Start enrolling in two-factor authentication first".to_string())),
+    };
+
+    let mut conn = redis.get_tokio_connection().await?;
+
+    if !verify_totp(&mut conn, &jwt.user_id, &secret, &json.code).await? {
+        return Err(ContentError::ValidationError("Invalid two-factor code".to_string()))
+    }
+
+    let recovery_codes  = generate_recovery_codes();
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    enable_totp(&jwt.user_id, &mut transaction)
+        .await
+        .context("Failed to enable TOTP")?;
+    replace_recovery_codes(&jwt.user_id, &recovery_codes, &mut transaction)
+        .await
+        .context("Failed to store recovery codes")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to enable two-factor")?;
+
+    Ok(HttpResponse::Ok().json(json!({ "recoveryCodes": recovery_codes })))
+}
+
+/// Turns two-factor off. Needs a current code, or a recovery code.
+#[tracing::instrument(
+    skip(json, pool, redis, jwt)
+)]
+pub async fn disable_two_factor(
+    json:  web::Json<TwoFactorCodeData>,
+    pool:  web::Data<PgPool>,
+    redis: web::Data<redis::Client>,
+    jwt:   JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let mut conn = redis.get_tokio_connection().await?;
+
+    if !verify_second_factor(&jwt.user_id, &json.code, &mut conn, &pool).await? {
+        return Err(ContentError::ValidationError("Invalid two-factor code".to_string()))
+    }
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    delete_totp(&jwt.user_id, &mut transaction)
+        .await
+        .context("Failed to delete TOTP")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to disable two-factor")?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Replaces the user's recovery codes, e.g. once they've used a few
+#[tracing::instrument(
+    skip(json, pool, redis, jwt)
+)]
+pub async fn regenerate_recovery_codes(
+    json:  web::Json<TwoFactorCodeData>,
+    pool:  web::Data<PgPool>,
+    redis: web::Data<redis::Client>,
+    jwt:   JwtMiddleware
+) -> Result<HttpResponse, ContentError> {
+    let mut conn = redis.get_tokio_connection().await?;
+
+    if !verify_second_factor(&jwt.user_id, &json.code, &mut conn, &pool).await? {
+        return Err(ContentError::ValidationError("Invalid two-factor code".to_string()))
+    }
+
+    let recovery_codes  = generate_recovery_codes();
+    let mut transaction = pool
+        .begin()
+        .await
+        .context("Failed to acquire a Postgres connection from the pool")?;
+
+    replace_recovery_codes(&jwt.user_id, &recovery_codes, &mut transaction)
+        .await
+        .context("Failed to store recovery codes")?;
+
+    transaction
+        .commit()
+        .await
+        .context("Failed to commit SQL transaction to replace recovery codes")?;
+
+    Ok(HttpResponse::Ok().json(json!({ "recoveryCodes": recovery_codes })))
+}
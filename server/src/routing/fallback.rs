@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use crate::error::RoutingError;
+use crate::gmaps_api_client::Location;
+use crate::routing::{HaversineRouter, RouteLeg, RoutingProvider};
+
+/// Asks `primary` first and estimates with `fallback` when it can't be
+/// reached or answers with something unreadable, so a routing server being
+/// down doesn't take tour pages with it. A primary that says there's no
+/// route is believed.
+pub struct FallbackRouter {
+    primary:  Arc<dyn RoutingProvider>,
+    fallback: HaversineRouter,
+}
+
+impl FallbackRouter {
+    pub fn new(primary: Arc<dyn RoutingProvider>, fallback: HaversineRouter) -> Self {
+        Self {
+            primary,
+            fallback,
+        }
+    }
+}
+
+fn should_fall_back(e: &RoutingError) -> bool {
+    if matches!(e, RoutingError::NoRouteFound(_)) {
+        return false
+    }
+
+    tracing::warn!(error = ?e, "Routing provider failed, falling back to estimated legs");
+    true
+}
+
+#[async_trait::async_trait]
+impl RoutingProvider for FallbackRouter {
+    async fn legs(&self, stops: &[Location]) -> Result<Vec<RouteLeg>, RoutingError> {
+        match self.primary.legs(stops).await {
+            Err(e) if should_fall_back(&e) => self.fallback.legs(stops).await,
+            result => result,
+        }
+    }
+
+    async fn matrix(&self, stops: &[Location]) -> Result<Vec<Vec<RouteLeg>>, RoutingError> {
+        match self.primary.matrix(stops).await {
+            Err(e) if should_fall_back(&e) => self.fallback.matrix(stops).await,
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::assert_err;
+    use std::sync::Arc;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::gmaps_api_client::Location;
+    use crate::routing::{FallbackRouter, HaversineRouter, OsrmClient, RoutingProvider};
+
+    const ASHEVILLE: Location = Location { lat: 35.5, lng: -82.5 };
+    const RALEIGH:   Location = Location { lat: 35.75, lng: -78.75 };
+
+    fn router(base_url: String) -> FallbackRouter {
+        let osrm = OsrmClient::new(base_url, "driving".to_string(), std::time::Duration::from_millis(200));
+
+        FallbackRouter::new(Arc::new(osrm), HaversineRouter::new(1.3, 80.0))
+    }
+
+    #[tokio::test]
+    async fn failing_server_falls_back_to_estimates() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let router = router(mock_server.uri());
+        let legs   = router.legs(&[ASHEVILLE, RALEIGH]).await.unwrap();
+        assert_eq!(1, legs.len());
+        assert!(legs[0].estimated);
+
+        let matrix = router.matrix(&[ASHEVILLE, RALEIGH]).await.unwrap();
+        assert!(matrix[0][1].estimated);
+        assert!(matrix[0][1].distance_km > 0.0);
+    }
+
+    #[tokio::test]
+    async fn missing_routes_are_not_estimated() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "code":    "NoRoute",
+                "message": "Impossible route between points"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        assert_err!(router(mock_server.uri()).legs(&[ASHEVILLE, RALEIGH]).await);
+    }
+}
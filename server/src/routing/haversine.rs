@@ -0,0 +1,82 @@
+use crate::domain::haversine_km;
+use crate::error::RoutingError;
+use crate::gmaps_api_client::Location;
+use crate::routing::{RouteLeg, RoutingProvider};
+
+/// Estimates legs from straight-line distance. Roads are never straight, so
+/// the distance is stretched by `road_factor` and driven at a flat average
+/// speed. Needs no network access, which makes it the fallback provider.
+#[derive(Clone, Debug)]
+pub struct HaversineRouter {
+    road_factor:       f64,
+    average_speed_kmh: f64,
+}
+
+impl HaversineRouter {
+    pub fn new(road_factor: f64, average_speed_kmh: f64) -> Self {
+        Self {
+            road_factor,
+            average_speed_kmh,
+        }
+    }
+
+    pub fn leg(&self, from: &Location, to: &Location) -> RouteLeg {
+        let distance_km = haversine_km(from, to) * self.road_factor;
+
+        RouteLeg {
+            distance_km,
+            drive_minutes: distance_km / self.average_speed_kmh * 60.0,
+            estimated:     true,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RoutingProvider for HaversineRouter {
+    async fn legs(&self, stops: &[Location]) -> Result<Vec<RouteLeg>, RoutingError> {
+        Ok(stops.windows(2).map(|pair| self.leg(&pair[0], &pair[1])).collect())
+    }
+
+    async fn matrix(&self, stops: &[Location]) -> Result<Vec<Vec<RouteLeg>>, RoutingError> {
+        Ok(stops.iter().map(|from| stops.iter().map(|to| self.leg(from, to)).collect()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gmaps_api_client::Location;
+    use crate::routing::{HaversineRouter, RoutingProvider};
+
+    const ASHEVILLE: Location = Location { lat: 35.5951, lng: -82.5515 };
+    const RALEIGH:   Location = Location { lat: 35.7796, lng: -78.6382 };
+
+    #[tokio::test]
+    async fn one_leg_per_consecutive_pair() {
+        let router = HaversineRouter::new(1.0, 60.0);
+
+        assert!(router.legs(&[]).await.unwrap().is_empty());
+        assert!(router.legs(&[ASHEVILLE]).await.unwrap().is_empty());
+        assert_eq!(2, router.legs(&[ASHEVILLE, RALEIGH, ASHEVILLE]).await.unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn matrix_is_symmetric_with_an_empty_diagonal() {
+        let router = HaversineRouter::new(1.3, 80.0);
+        let matrix = router.matrix(&[ASHEVILLE, RALEIGH]).await.unwrap();
+
+        assert_eq!(0.0, matrix[0][0].distance_km);
+        assert_eq!(matrix[0][1], matrix[1][0]);
+    }
+
+    #[test]
+    fn road_factor_stretches_distance_and_time() {
+        let straight = HaversineRouter::new(1.0, 60.0).leg(&ASHEVILLE, &RALEIGH);
+        let by_road  = HaversineRouter::new(1.5, 60.0).leg(&ASHEVILLE, &RALEIGH);
+
+        // ~355km as the crow flies
+        assert!((straight.distance_km - 355.0).abs() < 5.0);
+        assert!((by_road.distance_km - straight.distance_km * 1.5).abs() < 0.01);
+        // 60km/h means one minute per kilometre
+        assert!((by_road.drive_minutes - by_road.distance_km).abs() < 0.01);
+    }
+}
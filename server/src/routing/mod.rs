@@ -0,0 +1,89 @@
+mod fallback;
+mod haversine;
+mod osrm;
+
+pub use fallback::*;
+pub use haversine::*;
+pub use osrm::*;
+
+use serde::{Deserialize, Serialize};
+use serde_aux::field_attributes::deserialize_number_from_string;
+use std::sync::Arc;
+
+use crate::error::RoutingError;
+use crate::gmaps_api_client::Location;
+
+/// Works out how far apart stops are by road.
+#[async_trait::async_trait]
+pub trait RoutingProvider: Send + Sync {
+    /// Returns one leg per consecutive pair of `stops`, so `stops.len() - 1`
+    /// legs for two or more stops and none otherwise.
+    async fn legs(&self, stops: &[Location]) -> Result<Vec<RouteLeg>, RoutingError>;
+
+    /// Returns the leg from every stop to every other, indexed
+    /// `[from][to]`. Unreachable pairs take infinitely long.
+    async fn matrix(&self, stops: &[Location]) -> Result<Vec<Vec<RouteLeg>>, RoutingError>;
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteLeg {
+    pub distance_km:   f64,
+    pub drive_minutes: f64,
+    /// Worked out from straight-line distance rather than actual roads
+    pub estimated:     bool,
+}
+
+impl RouteLeg {
+    pub const UNREACHABLE: RouteLeg = RouteLeg {
+        distance_km:   f64::INFINITY,
+        drive_minutes: f64::INFINITY,
+        estimated:     false,
+    };
+
+    pub fn is_reachable(&self) -> bool {
+        self.distance_km.is_finite() && self.drive_minutes.is_finite()
+    }
+}
+
+/// Which routing provider to use, picked with `backend`.
+#[derive(serde::Deserialize, Clone)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum RoutingSettings {
+    Haversine {
+        road_factor:       f64,
+        average_speed_kmh: f64,
+    },
+    /// Falls back to haversine estimates, made with `road_factor` and
+    /// `average_speed_kmh`, when the OSRM server fails
+    Osrm {
+        base_url:          String,
+        profile:           String,
+        #[serde(deserialize_with = "deserialize_number_from_string")]
+        timeout_millis:    u64,
+        road_factor:       f64,
+        average_speed_kmh: f64,
+    },
+}
+
+impl RoutingSettings {
+    pub fn build(self) -> Arc<dyn RoutingProvider> {
+        match self {
+            Self::Haversine { road_factor, average_speed_kmh } => Arc::new(
+                HaversineRouter::new(road_factor, average_speed_kmh)
+            ),
+            Self::Osrm { base_url, profile, timeout_millis, road_factor, average_speed_kmh } => {
+                let osrm = OsrmClient::new(
+                    base_url,
+                    profile,
+                    std::time::Duration::from_millis(timeout_millis)
+                );
+
+                Arc::new(FallbackRouter::new(
+                    Arc::new(osrm),
+                    HaversineRouter::new(road_factor, average_speed_kmh)
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::error::RoutingError;
+use crate::gmaps_api_client::Location;
+use crate::routing::{RouteLeg, RoutingProvider};
+
+/// Client for the route service of an OSRM server, or anything speaking the
+/// same API. All stops go in one request and come back as per-leg totals.
+pub struct OsrmClient {
+    http_client: Client,
+    base_url:    String,
+    profile:     String,
+}
+
+impl OsrmClient {
+    pub fn new(
+        base_url: String,
+        profile:  String,
+        timeout:  std::time::Duration
+    ) -> OsrmClient {
+        let http_client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap();
+        Self {
+            http_client,
+            base_url,
+            profile,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RoutingProvider for OsrmClient {
+    async fn legs(&self, stops: &[Location]) -> Result<Vec<RouteLeg>, RoutingError> {
+        if stops.len() < 2 {
+            return Ok(Vec::new())
+        }
+
+        let url      = format!("{}/route/v1/{}/{}", self.base_url, self.profile, coordinates(stops));
+        let response = self.http_client
+            .get(&url)
+            .query(&[("overview", "false")])
+            .send()
+            .await?
+            .json::<RouteResponse>()
+            .await?;
+
+        if response.code != "Ok" {
+            let message = response.message.unwrap_or(response.code);
+            return Err(RoutingError::NoRouteFound(message))
+        }
+
+        let route = response.routes
+            .into_iter()
+            .next()
+            .ok_or_else(|| RoutingError::NoRouteFound("No routes returned".to_string()))?;
+
+        if route.legs.len() != stops.len() - 1 {
+            return Err(anyhow::anyhow!(
+                "Expected {} legs from OSRM but got {}",
+                stops.len() - 1,
+                route.legs.len()
+            ).into())
+        }
+
+        Ok(route.legs.into_iter().map(RouteLeg::from).collect())
+    }
+
+    async fn matrix(&self, stops: &[Location]) -> Result<Vec<Vec<RouteLeg>>, RoutingError> {
+        if stops.is_empty() {
+            return Ok(Vec::new())
+        }
+
+        let url      = format!("{}/table/v1/{}/{}", self.base_url, self.profile, coordinates(stops));
+        let response = self.http_client
+            .get(&url)
+            .query(&[("annotations", "duration,distance")])
+            .send()
+            .await?
+            .json::<TableResponse>()
+            .await?;
+
+        if response.code != "Ok" {
+            let message = response.message.unwrap_or(response.code);
+            return Err(RoutingError::NoRouteFound(message))
+        }
+
+        let (durations, distances) = match (response.durations, response.distances) {
+            (Some(durations), Some(distances)) => (durations, distances),
+            _ => return Err(anyhow::anyhow!("OSRM table is missing durations or distances").into())
+        };
+
+        if durations.len() != stops.len() || distances.len() != stops.len() {
+            return Err(anyhow::anyhow!(
+                "Expected a {0}x{0} table from OSRM but got {1} rows",
+                stops.len(),
+                durations.len()
+            ).into())
+        }
+
+        let matrix = durations
+            .into_iter()
+            .zip(distances)
+            .map(|(durations, distances)| {
+                durations
+                    .into_iter()
+                    .zip(distances)
+                    .map(|cell| match cell {
+                        (Some(duration), Some(distance)) => Leg { distance, duration }.into(),
+                        _ => RouteLeg::UNREACHABLE,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(matrix)
+    }
+}
+
+/// OSRM wants longitude first
+fn coordinates(stops: &[Location]) -> String {
+    stops
+        .iter()
+        .map(|s| format!("{},{}", s.lng, s.lat))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+#[derive(Debug, Deserialize)]
+struct RouteResponse {
+    code:    String,
+    message: Option<String>,
+    #[serde(default)]
+    routes:  Vec<Route>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TableResponse {
+    code:      String,
+    message:   Option<String>,
+    /// Seconds, null where there's no route
+    durations: Option<Vec<Vec<Option<f64>>>>,
+    /// Metres, null where there's no route
+    distances: Option<Vec<Vec<Option<f64>>>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Route {
+    legs: Vec<Leg>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Leg {
+    /// Metres
+    distance: f64,
+    /// Seconds
+    duration: f64,
+}
+
+impl From<Leg> for RouteLeg {
+    fn from(value: Leg) -> Self {
+        Self {
+            distance_km:   value.distance / 1000.0,
+            drive_minutes: value.duration / 60.0,
+            estimated:     false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::assert_err;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::error::RoutingError;
+    use crate::gmaps_api_client::Location;
+    use crate::routing::{OsrmClient, RouteLeg, RoutingProvider};
+
+    const ASHEVILLE: Location = Location { lat: 35.5, lng: -82.5 };
+    const RALEIGH:   Location = Location { lat: 35.75, lng: -78.75 };
+
+    fn client(base_url: String) -> OsrmClient {
+        OsrmClient::new(base_url, "driving".to_string(), std::time::Duration::from_millis(200))
+    }
+
+    #[tokio::test]
+    async fn legs_are_read_from_the_first_route() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/route/v1/driving/-82.5,35.5;-78.75,35.75"))
+            .and(query_param("overview", "false"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code":   "Ok",
+                "routes": [{
+                    "distance": 398000.0,
+                    "duration": 13500.0,
+                    "legs": [{ "distance": 398000.0, "duration": 13500.0 }]
+                }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let legs = client(mock_server.uri()).legs(&[ASHEVILLE, RALEIGH]).await.unwrap();
+
+        assert_eq!(1, legs.len());
+        assert_eq!(398.0, legs[0].distance_km);
+        assert_eq!(225.0, legs[0].drive_minutes);
+    }
+
+    #[tokio::test]
+    async fn matrix_is_read_from_the_table_service() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/table/v1/driving/-82.5,35.5;-78.75,35.75"))
+            .and(query_param("annotations", "duration,distance"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "code":      "Ok",
+                "durations": [[0.0, 13500.0], [13600.0, null]],
+                "distances": [[0.0, 398000.0], [399000.0, null]]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let matrix = client(mock_server.uri()).matrix(&[ASHEVILLE, RALEIGH]).await.unwrap();
+
+        assert_eq!(398.0, matrix[0][1].distance_km);
+        assert_eq!(399.0, matrix[1][0].distance_km);
+        assert_eq!(RouteLeg::UNREACHABLE, matrix[1][1]);
+    }
+
+    #[tokio::test]
+    async fn single_stop_does_not_call_the_server() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        assert!(client(mock_server.uri()).legs(&[ASHEVILLE]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn unroutable_stops_are_an_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "code":    "NoRoute",
+                "message": "Impossible route between points"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let result = client(mock_server.uri()).legs(&[ASHEVILLE, RALEIGH]).await;
+
+        assert!(matches!(result, Err(RoutingError::NoRouteFound(_))));
+    }
+
+    #[tokio::test]
+    async fn garbage_response_is_an_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(502).set_body_string("Bad gateway"))
+            .mount(&mock_server)
+            .await;
+
+        assert_err!(client(mock_server.uri()).legs(&[ASHEVILLE, RALEIGH]).await);
+    }
+}
@@ -10,43 +10,110 @@ use actix_web::{
 use secrecy::{ExposeSecret, Secret};
 use sqlx::{PgPool, postgres::PgPoolOptions};
 use std::net::TcpListener;
+use std::sync::Arc;
 use tracing_actix_web::TracingLogger;
 
+use crate::auth::JwtKeys;
 use crate::configuration::{DatabaseSettings, JWTSettings, Settings};
 use crate::email_client::EmailClient;
-use crate::gmaps_api_client::GoogleMapsAPIClient;
+use crate::geocoder::Geocoder;
 use crate::routes::{
+    accept_invite,
+    add_api_token,
     add_contact,
+    add_inquiry_template,
+    add_organisation,
+    add_outreach,
+    add_tour,
+    add_tour_date,
+    admin_confirm_user,
     admin_delete_contact,
     admin_delete_review,
     admin_edit_contact,
     admin_edit_review,
+    admin_geocode_missing,
+    admin_get_address_mismatches,
+    admin_get_emails,
+    admin_get_geocoding_jobs,
     admin_get_recent_reviews,
     admin_get_reviews_by_user,
+    admin_require_two_factor,
+    admin_resend_email,
+    admin_revoke_sessions,
+    admin_set_user_status,
     approve_contact,
     change_password,
     confirm,
+    contacts_in_bounds,
+    contacts_nearby,
+    create_calendar_token,
+    disable_two_factor,
+    enable_two_factor,
+    enrol_two_factor,
+    export_ics,
+    export_songkick,
     find_coordinates_for_city,
     generate_reset_token,
+    get_api_tokens,
     get_contact_by_id,
+    get_contact_shares,
+    get_follow_ups,
     get_genres,
+    get_inquiry_templates,
+    get_members,
+    get_organisations,
+    get_outreach,
     get_pending_contacts,
+    get_sent_inquiries,
+    get_shared_contacts,
     health_check, 
+    import_contacts,
+    invite_member,
+    jwks,
     log_in,
+    log_in_two_factor,
     log_out,
+    log_out_everywhere,
+    move_tour_date,
+    plan_route,
     private_contacts,
     public_contacts,
+    refresh_session,
+    regenerate_recovery_codes,
+    remove_member,
+    remove_tour_date_contact,
+    resend_confirmation,
     reset_password,
+    revoke_calendar_token,
     review_contact,
     reviews_for_contact,
+    save_route_plan,
+    send_inquiry,
+    set_tour_date_contact,
+    share_contact,
     sign_up,
+    switch_organisation,
+    tour_calendar_feed,
+    unshare_contact,
     user_delete_contact,
+    user_delete_inquiry_template,
+    user_delete_outreach,
     user_delete_review,
+    user_delete_tour,
+    user_delete_tour_date,
     user_edit_contact,
+    user_edit_inquiry_template,
+    user_edit_outreach,
     user_edit_review,
+    user_edit_tour,
     user_get_contacts,
-    user_get_reviews
+    user_get_reviews,
+    user_get_tour,
+    user_get_tour_legs,
+    user_get_tours,
+    user_revoke_api_token
 };
+use crate::routing::RoutingProvider;
 
 pub struct Application {
     port:   u16,
@@ -56,7 +123,8 @@ pub struct Application {
 impl Application {
     pub async fn build(configuration: Settings) -> Result<Self, anyhow::Error> {
         let connection_pool = get_connection_pool(&configuration.database);
-        let email_client    = configuration.email_client.client();
+        let inquiry_limit   = configuration.email_client.daily_inquiry_limit;
+        let email_client    = configuration.email_client.client()?;
 
         let address  = format!("{}:{}", configuration.application.host, configuration.application.port);
         let listener = TcpListener::bind(address)?;
@@ -65,12 +133,14 @@ impl Application {
             listener,
             connection_pool,
             email_client,
+            inquiry_limit,
             configuration.application.base_url,
             configuration.application.hmac_secret,
             configuration.redis_uri,
             configuration.jwt_settings,
             configuration.frontend_url,
-            configuration.gmaps_client
+            configuration.geocoder.build()?,
+            configuration.routing.build()
         ).await?;
 
         Ok(Self { port, server })
@@ -87,6 +157,9 @@ impl Application {
 
 pub struct ApplicationBaseUrl(pub String);
 
+/// Inquiry emails each user can send in 24 hours
+pub struct DailyInquiryLimit(pub i64);
+
 pub fn get_connection_pool(db_config: &DatabaseSettings) -> PgPool {
     PgPoolOptions::new()
         .acquire_timeout(std::time::Duration::from_secs(2))
@@ -94,21 +167,26 @@ pub fn get_connection_pool(db_config: &DatabaseSettings) -> PgPool {
 }
 
 async fn run(
-    listener:     TcpListener,
-    db_pool:      PgPool,
-    email_client: EmailClient,
-    base_url:     String,
-    hmac_secret:  Secret<String>,
-    redis_uri:    Secret<String>,
-    jwt_settings: JWTSettings,
-    frontend_url: String,
-    gmaps_client: GoogleMapsAPIClient,
+    listener:      TcpListener,
+    db_pool:       PgPool,
+    email_client:  EmailClient,
+    inquiry_limit: i64,
+    base_url:      String,
+    hmac_secret:   Secret<String>,
+    redis_uri:     Secret<String>,
+    jwt_settings:  JWTSettings,
+    frontend_url:  String,
+    geocoder:      Arc<dyn Geocoder>,
+    routing:       Arc<dyn RoutingProvider>,
 ) -> Result<Server, anyhow::Error> {
     let base_url     = web::Data::new(ApplicationBaseUrl(base_url));
     let db_pool      = web::Data::new(db_pool);
     let email_client = web::Data::new(email_client);
+    let inq_limit    = web::Data::new(DailyInquiryLimit(inquiry_limit));
+    let jwt_keys     = web::Data::new(JwtKeys::from_settings(&jwt_settings)?);
     let jwt_settings = web::Data::new(jwt_settings);
-    let gmaps_client = web::Data::new(gmaps_client);
+    let geocoder     = web::Data::from(geocoder);
+    let routing      = web::Data::from(routing);
     let _secret_key  = Key::from(hmac_secret.expose_secret().as_bytes()); // previously used for RedisSession. idk if necessary
     let redis_client = web::Data::new(
         redis::Client::open(redis_uri.expose_secret().clone())?
@@ -133,21 +211,30 @@ async fn run(
             .wrap(TracingLogger::default())
             .wrap(cors)
             .route("/health_check", web::get().to(health_check))
+            .route("/.well-known/jwks.json", web::get().to(jwks))
             .route("/confirm", web::get().to(confirm))
             .route("/contact", web::get().to(get_contact_by_id))
             .route("/contacts", web::get().to(public_contacts))
+            .route("/contacts/nearby", web::get().to(contacts_nearby))
+            .route("/contacts/in-bounds", web::get().to(contacts_in_bounds))
             .route("/find-coordinates-for-city", web::post().to(find_coordinates_for_city))
             .route("/genres", web::get().to(get_genres))
             .route("/generate-reset-token", web::post().to(generate_reset_token))
             .route("/login", web::post().to(log_in))
+            .route("/login/two-factor", web::post().to(log_in_two_factor))
+            .route("/refresh", web::post().to(refresh_session))
+            .route("/resend-confirmation", web::post().to(resend_confirmation))
             .route("/reset-password", web::post().to(reset_password))
             .route("/signup", web::post().to(sign_up))
             .route("/reviews", web::get().to(reviews_for_contact))
+            .route("/tour-calendar", web::get().to(tour_calendar_feed))
             .service(
                 web::scope("/user")
                     .route("/add-contact", web::post().to(add_contact))
                     .route("/change-password", web::post().to(change_password))
+                    .route("/import-contacts", web::post().to(import_contacts))
                     .route("/logout", web::post().to(log_out))
+                    .route("/logout-all", web::post().to(log_out_everywhere))
                     .route("/contacts", web::get().to(user_get_contacts))
                     .route("/private-contacts", web::get().to(private_contacts))
                     .route("/review-contact", web::post().to(review_contact))
@@ -155,7 +242,65 @@ async fn run(
                     .route("/delete-review", web::post().to(user_delete_review))
                     .route("/edit-contact", web::post().to(user_edit_contact))
                     .route("/edit-review", web::post().to(user_edit_review))
+                    .route("/share-contact", web::post().to(share_contact))
+                    .route("/unshare-contact", web::post().to(unshare_contact))
+                    .route("/contact-shares", web::get().to(get_contact_shares))
+                    .route("/shared-contacts", web::get().to(get_shared_contacts))
                     .route("/my-reviews", web::get().to(user_get_reviews))
+                    .route("/add-outreach", web::post().to(add_outreach))
+                    .route("/edit-outreach", web::post().to(user_edit_outreach))
+                    .route("/delete-outreach", web::post().to(user_delete_outreach))
+                    .route("/outreach", web::get().to(get_outreach))
+                    .route("/follow-ups", web::get().to(get_follow_ups))
+                    .route("/add-inquiry-template", web::post().to(add_inquiry_template))
+                    .route("/edit-inquiry-template", web::post().to(user_edit_inquiry_template))
+                    .route("/delete-inquiry-template", web::post().to(user_delete_inquiry_template))
+                    .route("/inquiry-templates", web::get().to(get_inquiry_templates))
+                    .route("/send-inquiry", web::post().to(send_inquiry))
+                    .route("/sent-inquiries", web::get().to(get_sent_inquiries))
+                    .service(
+                        web::scope("/two-factor")
+                            .route("/enrol", web::post().to(enrol_two_factor))
+                            .route("/enable", web::post().to(enable_two_factor))
+                            .route("/disable", web::post().to(disable_two_factor))
+                            .route("/recovery-codes", web::post().to(regenerate_recovery_codes))
+                    )
+                    .service(
+                        web::scope("/api-tokens")
+                            .route("", web::get().to(get_api_tokens))
+                            .route("/add-token", web::post().to(add_api_token))
+                            .route("/revoke", web::post().to(user_revoke_api_token))
+                    )
+                    .service(
+                        web::scope("/organisations")
+                            .route("", web::get().to(get_organisations))
+                            .route("/add-organisation", web::post().to(add_organisation))
+                            .route("/switch", web::post().to(switch_organisation))
+                            .route("/members", web::get().to(get_members))
+                            .route("/invite", web::post().to(invite_member))
+                            .route("/accept-invite", web::get().to(accept_invite))
+                            .route("/remove-member", web::post().to(remove_member))
+                    )
+                    .service(
+                        web::scope("/tours")
+                            .route("", web::get().to(user_get_tours))
+                            .route("/tour", web::get().to(user_get_tour))
+                            .route("/export-songkick", web::get().to(export_songkick))
+                            .route("/export-ics", web::get().to(export_ics))
+                            .route("/calendar-token", web::post().to(create_calendar_token))
+                            .route("/revoke-calendar-token", web::post().to(revoke_calendar_token))
+                            .route("/legs", web::get().to(user_get_tour_legs))
+                            .route("/plan-route", web::post().to(plan_route))
+                            .route("/save-route-plan", web::post().to(save_route_plan))
+                            .route("/add-tour", web::post().to(add_tour))
+                            .route("/edit-tour", web::post().to(user_edit_tour))
+                            .route("/delete-tour", web::post().to(user_delete_tour))
+                            .route("/add-date", web::post().to(add_tour_date))
+                            .route("/move-date", web::post().to(move_tour_date))
+                            .route("/delete-date", web::post().to(user_delete_tour_date))
+                            .route("/set-date-contact", web::post().to(set_tour_date_contact))
+                            .route("/remove-date-contact", web::post().to(remove_tour_date_contact))
+                    )
             )
             .service(
                 web::scope("/admin")
@@ -167,12 +312,24 @@ async fn run(
                     .route("/delete-review", web::post().to(admin_delete_review))
                     .route("/edit-contact", web::post().to(admin_edit_contact))
                     .route("/edit-review", web::post().to(admin_edit_review))
+                    .route("/geocoding-jobs", web::get().to(admin_get_geocoding_jobs))
+                    .route("/geocode-missing", web::post().to(admin_geocode_missing))
+                    .route("/address-mismatches", web::get().to(admin_get_address_mismatches))
+                    .route("/confirm-user", web::post().to(admin_confirm_user))
+                    .route("/set-user-status", web::post().to(admin_set_user_status))
+                    .route("/revoke-sessions", web::post().to(admin_revoke_sessions))
+                    .route("/emails", web::get().to(admin_get_emails))
+                    .route("/resend-email", web::post().to(admin_resend_email))
+                    .route("/require-two-factor", web::post().to(admin_require_two_factor))
             )
             .app_data(db_pool.clone())
             .app_data(email_client.clone())
             .app_data(jwt_settings.clone())
-            .app_data(gmaps_client.clone())
+            .app_data(jwt_keys.clone())
+            .app_data(geocoder.clone())
+            .app_data(routing.clone())
             .app_data(base_url.clone())
+            .app_data(inq_limit.clone())
             .app_data(redis_client.clone())
     })
     .listen(listener)?
@@ -209,7 +366,7 @@ async fn run(
 //             .to_string();
         
 //         sqlx::query!(
-//             "INSERT INTO users (user_id, email, password_hash) VALUES ($1, $2, $3)",
+//             "INSERT INTO users (user_id, email, password_hash, status) VALUES ($1, $2, $3, 'confirmed')",
 //             self.user_id,
 //             self.email,
 //             hash
@@ -5,8 +5,16 @@ use rand::{thread_rng, Rng};
 use sqlx::types::chrono::NaiveDateTime;
 use uuid::Uuid;
 
+use crate::auth::TwoFactorMissing;
+use crate::domain::ActiveOrganisation;
 use crate::error::{AdminError, ContentError};
 
+/// The organisation the requester is acting for, if any. Only set once
+/// `JwtMiddleware` or `ApiAuth` has checked they're still a member.
+pub fn active_org(req: &HttpRequest) -> Option<ActiveOrganisation> {
+    req.extensions().get::<ActiveOrganisation>().copied()
+}
+
 pub fn error_chain_fmt(
     e: &impl std::error::Error,
     f: &mut std::fmt::Formatter<'_>,
@@ -41,6 +49,8 @@ pub fn is_admin(
 
     if role.to_string() != admin {
         Err(AdminError::InvalidToken)
+    } else if ext.get::<TwoFactorMissing>().is_some() {
+        Err(AdminError::TwoFactorRequired)
     } else {
         Ok(())
     }
@@ -57,14 +67,54 @@ pub fn is_token_expired(created_at: NaiveDateTime, expiration_duration: Duration
     created_at + expiration_duration <= current_time
 }
 
+/// Which organisation new contacts, tours and reviews belong to. Members
+/// can't add anything while acting for an organisation.
+pub fn new_data_org(req: &HttpRequest) -> Result<Option<Uuid>, ContentError> {
+    match active_org(req) {
+        Some(org) if org.role.can_edit() => Ok(Some(org.org_id)),
+        Some(_) => Err(ContentError::AuthorizationError),
+        None => Ok(None),
+    }
+}
+
+/// Read access: the requester created it, or it belongs to the organisation
+/// they're acting for.
+pub fn user_can_view(
+    req:      &HttpRequest,
+    owner_id: &Uuid,
+    org_id:   &Option<Uuid>
+) -> Result<(), ContentError> {
+    owner_allows(req, owner_id, org_id, false)
+}
+
+/// Write access: the requester created it, or it belongs to the organisation
+/// they're acting for and they're an owner or booker there. Once something
+/// belongs to an organisation only its members' roles count.
 pub fn user_matches(
-    req_id:  &Uuid,
-    data_id: &Uuid
+    req:      &HttpRequest,
+    owner_id: &Uuid,
+    org_id:   &Option<Uuid>
 ) -> Result<(), ContentError> {
-    if req_id != data_id {
-        Err(ContentError::AuthorizationError)
-    } else {
+    owner_allows(req, owner_id, org_id, true)
+}
+
+fn owner_allows(
+    req:      &HttpRequest,
+    owner_id: &Uuid,
+    org_id:   &Option<Uuid>,
+    edit:     bool
+) -> Result<(), ContentError> {
+    let allowed = match org_id {
+        Some(org_id) => active_org(req)
+            .filter(|org| &org.org_id == org_id)
+            .map_or(false, |org| !edit || org.role.can_edit()),
+        None => req.extensions().get::<Uuid>() == Some(owner_id),
+    };
+
+    if allowed {
         Ok(())
+    } else {
+        Err(ContentError::AuthorizationError)
     }
 }
 
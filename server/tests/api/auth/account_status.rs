@@ -0,0 +1,152 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::helpers::{spawn_app, TestApp};
+
+fn test_user_login_body(app: &TestApp) -> serde_json::Value {
+    serde_json::json!({
+        "email":    &app.test_user.email,
+        "password": &app.test_user.password
+    })
+}
+
+async fn set_test_user_status(app: &TestApp, status: &str) {
+    sqlx::query!(
+        "UPDATE users SET status = $1 WHERE user_id = $2",
+        status,
+        app.test_user.user_id
+    ).execute(&app.db_pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn pending_users_cannot_log_in() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    let json = serde_json::json!({
+        "email":    "pending@test.test",
+        "password": "test"
+    });
+    app.sign_up(&json).await;
+
+    let response = app.post_login(&json).await;
+
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(response.text().await.unwrap(), "Please confirm your email before logging in");
+}
+
+#[tokio::test]
+async fn wrong_passwords_are_rejected_before_account_status_is_checked() {
+    let app = spawn_app().await;
+    app.sign_up(serde_json::json!({
+        "email":    "pending@test.test",
+        "password": "test"
+    })).await;
+
+    let response = app.post_login(serde_json::json!({
+        "email":    "pending@test.test",
+        "password": "wrong"
+    })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn suspended_and_banned_users_cannot_log_in() {
+    let app  = spawn_app().await;
+    let body = test_user_login_body(&app);
+
+    set_test_user_status(&app, "suspended").await;
+    let response = app.post_login(&body).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(response.text().await.unwrap(), "This account has been suspended");
+
+    set_test_user_status(&app, "banned").await;
+    let response = app.post_login(&body).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(response.text().await.unwrap(), "This account has been banned");
+}
+
+#[tokio::test]
+async fn suspending_a_user_locks_them_out_of_user_routes() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+    assert_eq!(app.get_private_contacts().await.status().as_u16(), 200);
+
+    set_test_user_status(&app, "suspended").await;
+
+    assert_eq!(app.get_private_contacts().await.status().as_u16(), 403);
+    let response = app.add_tour(serde_json::json!({
+        "tourName":  "Locked out",
+        "startDate": "2026-05-01",
+        "endDate":   "2026-05-10"
+    })).await;
+    assert_eq!(response.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn admins_can_suspend_and_reinstate_users() {
+    let app  = spawn_app().await;
+    let body = test_user_login_body(&app);
+
+    app.admin_login().await;
+    let response = app.set_user_status(serde_json::json!({
+        "userId": app.test_user.user_id,
+        "status": "suspended"
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(app.post_login(&body).await.status().as_u16(), 403);
+
+    app.admin_login().await;
+    let response = app.set_user_status(serde_json::json!({
+        "userId": app.test_user.user_id,
+        "status": "confirmed"
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(app.post_login(&body).await.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn setting_user_status_is_admin_only() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+
+    let response = app.set_user_status(serde_json::json!({
+        "userId": app.admin.user_id,
+        "status": "banned"
+    })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn admins_cannot_change_their_own_status() {
+    let app = spawn_app().await;
+    app.admin_login().await;
+
+    let response = app.set_user_status(serde_json::json!({
+        "userId": app.admin.user_id,
+        "status": "banned"
+    })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn unknown_statuses_are_rejected() {
+    let app = spawn_app().await;
+    app.admin_login().await;
+
+    let response = app.set_user_status(serde_json::json!({
+        "userId": app.test_user.user_id,
+        "status": "deleted"
+    })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
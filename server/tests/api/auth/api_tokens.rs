@@ -0,0 +1,189 @@
+use byot_server::auth::ApiTokenSummary;
+use crate::helpers::{spawn_app, TestApp};
+
+fn contact() -> serde_json::Value {
+    serde_json::json!({
+        "displayName": "scripted",
+        "city":        "asheville",
+        "ageRange":    "18+",
+        "contactType": "venue",
+        "isPrivate":   true,
+        "genres":      [1]
+    })
+}
+
+fn tour() -> serde_json::Value {
+    serde_json::json!({
+        "tourName":  "scripted tour",
+        "startDate": "2024-06-01",
+        "endDate":   "2024-06-14"
+    })
+}
+
+/// Logs in as the test user and makes a token with the given scopes
+async fn create_token(app: &TestApp, scopes: &[&str]) -> (uuid::Uuid, String) {
+    let response = app.test_user_login().await;
+    assert_eq!(200, response.status().as_u16());
+
+    let response = app.add_api_token(serde_json::json!({
+        "name":   "import script",
+        "scopes": scopes
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let token_id = body["tokenId"].as_str().unwrap().parse().unwrap();
+    let token    = body["token"].as_str().unwrap().to_string();
+
+    (token_id, token)
+}
+
+#[tokio::test]
+async fn api_tokens_work_as_bearer_tokens() {
+    let app        = spawn_app().await;
+    let (_, token) = create_token(&app, &["read"]).await;
+
+    let response = app.get_private_contacts_with_token(&token).await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn api_tokens_are_stored_hashed_and_track_last_use() {
+    let app               = spawn_app().await;
+    let (token_id, token) = create_token(&app, &["read"]).await;
+
+    let saved = sqlx::query!("SELECT token_hash, last_used_at FROM api_tokens WHERE token_id = $1", token_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_ne!(token, saved.token_hash);
+    assert!(saved.last_used_at.is_none());
+
+    app.get_private_contacts_with_token(&token).await;
+
+    let tokens: Vec<ApiTokenSummary> = app.get_api_tokens().await.json().await.unwrap();
+    assert_eq!(1, tokens.len());
+    assert_eq!("import script", tokens[0].name);
+    assert!(tokens[0].last_used_at.is_some());
+}
+
+#[tokio::test]
+async fn read_only_tokens_cannot_write() {
+    let app        = spawn_app().await;
+    let (_, token) = create_token(&app, &["read"]).await;
+
+    assert_eq!(403, app.add_contact_with_token(&token, contact()).await.status().as_u16());
+    assert_eq!(403, app.add_tour_with_token(&token, tour()).await.status().as_u16());
+}
+
+#[tokio::test]
+async fn write_scopes_only_cover_their_own_routes() {
+    let app        = spawn_app().await;
+    let (_, token) = create_token(&app, &["contacts:write"]).await;
+
+    assert_eq!(200, app.add_contact_with_token(&token, contact()).await.status().as_u16());
+    assert_eq!(403, app.add_tour_with_token(&token, tour()).await.status().as_u16());
+
+    let (_, token) = create_token(&app, &["tours:write"]).await;
+
+    assert_eq!(403, app.add_contact_with_token(&token, contact()).await.status().as_u16());
+    assert_eq!(200, app.add_tour_with_token(&token, tour()).await.status().as_u16());
+}
+
+#[tokio::test]
+async fn revoked_tokens_are_rejected() {
+    let app               = spawn_app().await;
+    let (token_id, token) = create_token(&app, &["read"]).await;
+
+    let response = app.revoke_api_token(serde_json::json!({ "tokenId": token_id })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    assert_eq!(401, app.get_private_contacts_with_token(&token).await.status().as_u16());
+
+    let tokens: Vec<ApiTokenSummary> = app.get_api_tokens().await.json().await.unwrap();
+    assert!(tokens.is_empty());
+}
+
+#[tokio::test]
+async fn users_cannot_revoke_anothers_token() {
+    let app               = spawn_app().await;
+    let (token_id, token) = create_token(&app, &["read"]).await;
+
+    app.admin_login().await;
+    let response = app.revoke_api_token(serde_json::json!({ "tokenId": token_id })).await;
+    assert_eq!(400, response.status().as_u16());
+
+    assert_eq!(200, app.get_private_contacts_with_token(&token).await.status().as_u16());
+}
+
+#[tokio::test]
+async fn changing_password_revokes_api_tokens() {
+    let app        = spawn_app().await;
+    let (_, token) = create_token(&app, &["read"]).await;
+
+    let new_password = uuid::Uuid::new_v4().to_string();
+    let response     = app.post_change_password(&serde_json::json!({
+        "currentPassword":  &app.test_user.password,
+        "newPassword":      &new_password,
+        "newPasswordCheck": &new_password
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    assert_eq!(401, app.get_private_contacts_with_token(&token).await.status().as_u16());
+}
+
+#[tokio::test]
+async fn logging_out_everywhere_revokes_api_tokens() {
+    let app        = spawn_app().await;
+    let (_, token) = create_token(&app, &["read"]).await;
+
+    assert_eq!(200, app.post_logout_all().await.status().as_u16());
+
+    assert_eq!(401, app.get_private_contacts_with_token(&token).await.status().as_u16());
+}
+
+#[tokio::test]
+async fn api_tokens_stop_working_when_the_account_is_suspended() {
+    let app        = spawn_app().await;
+    let (_, token) = create_token(&app, &["read"]).await;
+
+    sqlx::query!("UPDATE users SET status = 'suspended' WHERE user_id = $1", app.test_user.user_id)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    assert_eq!(403, app.get_private_contacts_with_token(&token).await.status().as_u16());
+}
+
+#[tokio::test]
+async fn tokens_need_a_name_and_known_scopes() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(200, response.status().as_u16());
+
+    let cases = [
+        serde_json::json!({ "name": " ", "scopes": ["read"] }),
+        serde_json::json!({ "name": "script", "scopes": [] }),
+        serde_json::json!({ "name": "script", "scopes": ["admin"] }),
+    ];
+
+    for case in cases {
+        assert_eq!(400, app.add_api_token(&case).await.status().as_u16(), "{}", case);
+    }
+}
+
+#[tokio::test]
+async fn malformed_authorization_headers_are_rejected() {
+    let app = spawn_app().await;
+
+    for header in ["", "Bearer", "abc", "Basic dXNlcjpwYXNz"] {
+        let response = reqwest::Client::new()
+            .get(&format!("{}/user/private-contacts", &app.address))
+            .header("Authorization", header)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(401, response.status().as_u16(), "{:?}", header);
+    }
+}
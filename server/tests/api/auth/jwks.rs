@@ -0,0 +1,56 @@
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn jwks_lists_the_configured_public_keys() {
+    let app = spawn_app().await;
+
+    let response = app.get_jwks().await;
+    assert_eq!(200, response.status().as_u16());
+
+    let jwks: JwkSet = response.json().await.unwrap();
+    assert_eq!(1, jwks.keys.len());
+    assert!(jwks.find("test").is_some());
+}
+
+#[tokio::test]
+async fn tokens_can_be_verified_with_the_jwks() {
+    let app = spawn_app().await;
+
+    let response = app.test_user_login().await;
+    assert_eq!(200, response.status().as_u16());
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let token                   = body["token"].as_str().unwrap();
+    let jwks: JwkSet            = app.get_jwks().await.json().await.unwrap();
+
+    let header = decode_header(token).unwrap();
+    assert_eq!(Algorithm::EdDSA, header.alg);
+
+    let jwk = jwks.find(&header.kid.unwrap()).expect("Token signed with an unlisted key");
+    let key = DecodingKey::from_jwk(jwk).unwrap();
+
+    let claims = decode::<serde_json::Value>(token, &key, &Validation::new(Algorithm::EdDSA)).unwrap();
+    assert_eq!(app.test_user.user_id.to_string(), claims.claims["sub"]);
+}
+
+#[tokio::test]
+async fn tokens_with_an_unknown_kid_are_rejected() {
+    let app = spawn_app().await;
+
+    let response = app.test_user_login().await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    let token                   = body["token"].as_str().unwrap();
+
+    // Swap the header for one naming a key the server doesn't have
+    let header = base64::Engine::encode(
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+        r#"{"typ":"JWT","alg":"EdDSA","kid":"unknown"}"#
+    );
+    let forged = format!("{}.{}", header, token.split_once('.').unwrap().1);
+
+    let response = app.get_private_contacts_with_token(&forged).await;
+    assert_eq!(401, response.status().as_u16());
+}
@@ -1,4 +1,10 @@
+mod account_status;
+mod api_tokens;
 // mod change_password;
+mod jwks;
 // mod login;
+mod refresh;
 // mod reset_password;
-mod sign_up;
\ No newline at end of file
+mod sessions;
+mod sign_up;
+mod two_factor;
\ No newline at end of file
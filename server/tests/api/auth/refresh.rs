@@ -0,0 +1,148 @@
+use base64::Engine;
+
+use byot_server::domain::Organisation;
+use crate::helpers::{spawn_app, TestApp};
+
+struct Session {
+    access_token:  String,
+    refresh_token: String,
+}
+
+async fn session_from(response: reqwest::Response) -> Session {
+    assert_eq!(response.status().as_u16(), 200);
+
+    let refresh_cookie = response.cookies()
+        .find(|c| c.name() == "refresh_token")
+        .expect("No refresh token cookie");
+    assert!(refresh_cookie.http_only());
+
+    let refresh_token = refresh_cookie.value().to_string();
+    let body: serde_json::Value = response.json().await.unwrap();
+
+    Session {
+        access_token: body["token"].as_str().unwrap().to_string(),
+        refresh_token,
+    }
+}
+
+async fn log_in(app: &TestApp) -> Session {
+    session_from(app.test_user_login().await).await
+}
+
+fn claims(token: &str) -> serde_json::Value {
+    let payload = token.split('.').nth(1).unwrap();
+    let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(payload).unwrap();
+
+    serde_json::from_slice(&decoded).unwrap()
+}
+
+#[tokio::test]
+async fn access_tokens_last_as_long_as_configured() {
+    let app     = spawn_app().await;
+    let session = log_in(&app).await;
+    let claims  = claims(&session.access_token);
+
+    let lifetime = claims["exp"].as_i64().unwrap() - claims["iat"].as_i64().unwrap();
+
+    assert_eq!(lifetime, app.jwt_settings.expires_in.num_seconds());
+}
+
+#[tokio::test]
+async fn refreshing_issues_new_tokens() {
+    let app     = spawn_app().await;
+    let session = log_in(&app).await;
+
+    let refreshed = session_from(app.post_refresh().await).await;
+
+    assert_ne!(refreshed.refresh_token, session.refresh_token);
+    assert_ne!(claims(&refreshed.access_token)["jti"], claims(&session.access_token)["jti"]);
+    assert_eq!(app.get_private_contacts_with_token(&refreshed.access_token).await.status().as_u16(), 200);
+
+    // The new refresh token works in turn
+    session_from(app.post_refresh().await).await;
+}
+
+#[tokio::test]
+async fn refreshing_without_a_token_is_rejected() {
+    let app = spawn_app().await;
+
+    assert_eq!(app.post_refresh().await.status().as_u16(), 401);
+    assert_eq!(app.post_refresh_with_token("not-a-token").await.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn reusing_a_refresh_token_ends_the_session() {
+    let app     = spawn_app().await;
+    let session = log_in(&app).await;
+    let other   = log_in(&app).await;
+
+    let refreshed = session_from(app.post_refresh_with_token(&session.refresh_token).await).await;
+
+    let response = app.post_refresh_with_token(&session.refresh_token).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    // Whoever had the rotated token is logged out too
+    let response = app.post_refresh_with_token(&refreshed.refresh_token).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    // Other sessions are left alone
+    session_from(app.post_refresh_with_token(&other.refresh_token).await).await;
+}
+
+#[tokio::test]
+async fn logging_out_revokes_the_refresh_token() {
+    let app     = spawn_app().await;
+    let session = log_in(&app).await;
+
+    app.post_logout().await;
+
+    let response = app.post_refresh_with_token(&session.refresh_token).await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn logging_out_everywhere_revokes_every_refresh_token() {
+    let app   = spawn_app().await;
+    let other = log_in(&app).await;
+    let this  = log_in(&app).await;
+
+    app.post_logout_all().await;
+
+    assert_eq!(app.post_refresh_with_token(&other.refresh_token).await.status().as_u16(), 401);
+    assert_eq!(app.post_refresh_with_token(&this.refresh_token).await.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn suspended_users_cannot_refresh() {
+    let app     = spawn_app().await;
+    let session = log_in(&app).await;
+
+    sqlx::query!(
+        "UPDATE users SET status = 'suspended' WHERE user_id = $1",
+        app.test_user.user_id
+    ).execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let response = app.post_refresh_with_token(&session.refresh_token).await;
+    assert_eq!(response.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn refreshed_tokens_keep_acting_for_the_organisation() {
+    let app = spawn_app().await;
+    log_in(&app).await;
+
+    let org: Organisation = app.add_organisation(serde_json::json!({
+        "orgName": "the band"
+    }))
+    .await
+    .json()
+    .await
+    .unwrap();
+    app.switch_organisation(serde_json::json!({ "orgId": org.org_id })).await;
+
+    let refreshed = session_from(app.post_refresh().await).await;
+
+    assert_eq!(claims(&refreshed.access_token)["org"], org.org_id.to_string());
+}
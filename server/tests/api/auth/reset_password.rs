@@ -61,6 +61,7 @@ async fn password_reset_link_returns_200_for_correct_token() {
         .await;
 
     app.generate_reset_token(body).await;
+    app.dispatch_all_pending_emails().await;
 
     let email_request      = &app.email_server.received_requests().await.unwrap()[0];
     let confirmation_links = app.get_confirmation_links(&email_request);
@@ -0,0 +1,125 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::helpers::{spawn_app, TestApp};
+
+/// Logs the test user in and returns the token from the response body
+async fn login_token(app: &TestApp) -> String {
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+
+    body["token"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn logging_out_revokes_the_token() {
+    let app   = spawn_app().await;
+    let token = login_token(&app).await;
+    assert_eq!(app.get_private_contacts_with_token(&token).await.status().as_u16(), 200);
+
+    app.post_logout().await;
+
+    assert_eq!(app.get_private_contacts_with_token(&token).await.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn logging_out_leaves_other_sessions_alone() {
+    let app   = spawn_app().await;
+    let other = login_token(&app).await;
+    login_token(&app).await;
+
+    app.post_logout().await;
+
+    assert_eq!(app.get_private_contacts_with_token(&other).await.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn logging_out_everywhere_revokes_every_session() {
+    let app   = spawn_app().await;
+    let other = login_token(&app).await;
+    let this  = login_token(&app).await;
+
+    let response = app.post_logout_all().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    assert_eq!(app.get_private_contacts_with_token(&other).await.status().as_u16(), 401);
+    assert_eq!(app.get_private_contacts_with_token(&this).await.status().as_u16(), 401);
+
+    // Logging back in works straight away
+    let token = login_token(&app).await;
+    assert_eq!(app.get_private_contacts_with_token(&token).await.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn changing_password_revokes_other_sessions() {
+    let app   = spawn_app().await;
+    let other = login_token(&app).await;
+    login_token(&app).await;
+
+    let response = app.post_change_password(serde_json::json!({
+        "currentPassword":  &app.test_user.password,
+        "newPassword":      "new-password",
+        "newPasswordCheck": "new-password"
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    assert_eq!(app.get_private_contacts_with_token(&other).await.status().as_u16(), 401);
+
+    // This device gets a new token
+    let body: serde_json::Value = response.json().await.unwrap();
+    let token = body["token"].as_str().unwrap();
+    assert_eq!(app.get_private_contacts_with_token(token).await.status().as_u16(), 200);
+    assert_eq!(app.get_private_contacts().await.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn resetting_password_revokes_every_session() {
+    let app   = spawn_app().await;
+    let token = login_token(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.generate_reset_token(serde_json::json!({ "email": &app.test_user.email })).await;
+    app.dispatch_all_pending_emails().await;
+
+    let email_request      = &app.email_server.received_requests().await.unwrap()[0];
+    let confirmation_links = app.get_confirmation_links(email_request);
+    let reset_token        = app.get_token_from_links(confirmation_links);
+
+    let response = app.reset_password(serde_json::json!({
+        "resetToken":       reset_token,
+        "newPassword":      "password",
+        "newPasswordCheck": "password"
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    assert_eq!(app.get_private_contacts_with_token(&token).await.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn admins_can_revoke_a_users_sessions() {
+    let app   = spawn_app().await;
+    let token = login_token(&app).await;
+
+    app.admin_login().await;
+    let response = app.revoke_sessions(serde_json::json!({ "userId": app.test_user.user_id })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    assert_eq!(app.get_private_contacts_with_token(&token).await.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn revoking_sessions_is_admin_only() {
+    let app = spawn_app().await;
+    login_token(&app).await;
+
+    let response = app.revoke_sessions(serde_json::json!({ "userId": app.admin.user_id })).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
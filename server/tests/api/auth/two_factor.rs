@@ -0,0 +1,269 @@
+use byot_server::auth::{base32_decode, totp_code, TOTP_STEP};
+use crate::helpers::{spawn_app, TestApp};
+
+/// The code for `offset` steps from now. Each step's code can only be used
+/// once, or it's taken as a replay.
+fn code(secret: &str, offset: i64) -> String {
+    let step = chrono::Utc::now().timestamp() / TOTP_STEP as i64 + offset;
+
+    totp_code(&base32_decode(secret).unwrap(), step as u64)
+}
+
+/// Turns on two-factor for whoever is logged in. Uses the current step.
+async fn enable(app: &TestApp) -> (String, Vec<String>) {
+    let response = app.enrol_two_factor().await;
+    assert_eq!(200, response.status().as_u16());
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let secret = body["secret"].as_str().unwrap().to_string();
+
+    let response = app.enable_two_factor(serde_json::json!({ "code": code(&secret, 0) })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let recovery_codes = body["recoveryCodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c.as_str().unwrap().to_string())
+        .collect();
+
+    (secret, recovery_codes)
+}
+
+/// Logs in with the password and returns the challenge token
+async fn start_login(app: &TestApp) -> String {
+    let response = app.test_user_login().await;
+    assert_eq!(200, response.status().as_u16());
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!("two_factor_required", body["status"]);
+    assert!(body.get("token").is_none());
+
+    body["challengeToken"].as_str().unwrap().to_string()
+}
+
+async fn finish_login(app: &TestApp, challenge_token: &str, code: &str) -> reqwest::Response {
+    app.post_login_two_factor(serde_json::json!({
+        "challengeToken": challenge_token,
+        "code":           code
+    })).await
+}
+
+#[tokio::test]
+async fn enrolling_returns_a_provisioning_uri() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+
+    let response = app.enrol_two_factor().await;
+    assert_eq!(200, response.status().as_u16());
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let secret = body["secret"].as_str().unwrap();
+    let uri    = body["otpauthUri"].as_str().unwrap();
+    assert!(uri.starts_with("otpauth://totp/BYOT:"));
+    assert!(uri.contains(&format!("secret={}", secret)));
+
+    // Nothing changes until enrolment is confirmed with a code
+    let response = app.test_user_login().await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["token"].is_string());
+}
+
+#[tokio::test]
+async fn enabling_needs_a_valid_code() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+
+    let response = app.enable_two_factor(serde_json::json!({ "code": "123456" })).await;
+    assert_eq!(400, response.status().as_u16());
+
+    app.enrol_two_factor().await;
+    let response = app.enable_two_factor(serde_json::json!({ "code": "000000x" })).await;
+    assert_eq!(400, response.status().as_u16());
+
+    let (_, recovery_codes) = enable(&app).await;
+    assert_eq!(10, recovery_codes.len());
+
+    let response = app.enrol_two_factor().await;
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn login_needs_a_second_factor_once_enabled() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+    let (secret, _) = enable(&app).await;
+
+    let challenge_token = start_login(&app).await;
+
+    let response = finish_login(&app, &challenge_token, "not a code").await;
+    assert_eq!(401, response.status().as_u16());
+
+    let response = finish_login(&app, &challenge_token, &code(&secret, 1)).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let token = body["token"].as_str().unwrap();
+    assert_eq!(200, app.get_private_contacts_with_token(token).await.status().as_u16());
+
+    // The challenge only works once
+    let response = finish_login(&app, &challenge_token, &code(&secret, 1)).await;
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn codes_cannot_be_replayed() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+    let (secret, _) = enable(&app).await;
+    let code        = code(&secret, 1);
+
+    let challenge_token = start_login(&app).await;
+    assert_eq!(200, finish_login(&app, &challenge_token, &code).await.status().as_u16());
+
+    let challenge_token = start_login(&app).await;
+    assert_eq!(401, finish_login(&app, &challenge_token, &code).await.status().as_u16());
+}
+
+#[tokio::test]
+async fn racing_logins_cannot_share_a_code() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+    let (secret, _) = enable(&app).await;
+    let code        = code(&secret, 1);
+
+    let first_token  = start_login(&app).await;
+    let second_token = start_login(&app).await;
+
+    let (first, second) = tokio::join!(
+        finish_login(&app, &first_token, &code),
+        finish_login(&app, &second_token, &code)
+    );
+
+    let mut statuses = [first.status().as_u16(), second.status().as_u16()];
+    statuses.sort();
+    assert_eq!([200, 401], statuses);
+}
+
+#[tokio::test]
+async fn recovery_codes_work_once() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+    let (_, recovery_codes) = enable(&app).await;
+
+    let challenge_token = start_login(&app).await;
+    let response        = finish_login(&app, &challenge_token, &recovery_codes[0].to_uppercase()).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let challenge_token = start_login(&app).await;
+    let response        = finish_login(&app, &challenge_token, &recovery_codes[0]).await;
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn regenerating_recovery_codes_replaces_the_old_ones() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+    let (secret, old_codes) = enable(&app).await;
+
+    let response = app.regenerate_recovery_codes(serde_json::json!({ "code": code(&secret, 1) })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let new_code = body["recoveryCodes"][0].as_str().unwrap();
+
+    let challenge_token = start_login(&app).await;
+    assert_eq!(401, finish_login(&app, &challenge_token, &old_codes[1]).await.status().as_u16());
+    assert_eq!(200, finish_login(&app, &challenge_token, new_code).await.status().as_u16());
+}
+
+#[tokio::test]
+async fn challenges_end_after_too_many_wrong_codes() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+    let (secret, _) = enable(&app).await;
+
+    let challenge_token = start_login(&app).await;
+
+    for _ in 0..5 {
+        assert_eq!(401, finish_login(&app, &challenge_token, "000000").await.status().as_u16());
+    }
+
+    let response = finish_login(&app, &challenge_token, &code(&secret, 1)).await;
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn wrong_codes_count_against_the_user_across_logins() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+    let (secret, recovery_codes) = enable(&app).await;
+
+    for _ in 0..2 {
+        let challenge_token = start_login(&app).await;
+
+        for _ in 0..5 {
+            assert_eq!(401, finish_login(&app, &challenge_token, "000000").await.status().as_u16());
+        }
+    }
+
+    // A fresh login doesn't help, and neither do right codes until it lifts
+    let challenge_token = start_login(&app).await;
+    assert_eq!(429, finish_login(&app, &challenge_token, &code(&secret, 1)).await.status().as_u16());
+    assert_eq!(429, finish_login(&app, &challenge_token, &recovery_codes[0]).await.status().as_u16());
+
+    let response = app.disable_two_factor(serde_json::json!({ "code": recovery_codes[0] })).await;
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn disabling_two_factor_goes_back_to_password_login() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+    let (_, recovery_codes) = enable(&app).await;
+
+    let response = app.disable_two_factor(serde_json::json!({ "code": "000000" })).await;
+    assert_eq!(400, response.status().as_u16());
+
+    let response = app.disable_two_factor(serde_json::json!({ "code": recovery_codes[0] })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let response = app.test_user_login().await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["token"].is_string());
+}
+
+#[tokio::test]
+async fn admins_can_require_two_factor_for_admins() {
+    let app = spawn_app().await;
+    app.admin_login().await;
+
+    // Not before they have it themselves
+    let response = app.require_two_factor(serde_json::json!({ "required": true })).await;
+    assert_eq!(400, response.status().as_u16());
+
+    let (_, recovery_codes) = enable(&app).await;
+
+    let response = app.require_two_factor(serde_json::json!({ "required": true })).await;
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(200, app.get_emails("failed").await.status().as_u16());
+
+    // Without it the admin routes are shut
+    app.disable_two_factor(serde_json::json!({ "code": recovery_codes[0] })).await;
+    assert_eq!(403, app.get_emails("failed").await.status().as_u16());
+
+    // Other users aren't affected
+    let response = app.test_user_login().await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(200, app.get_private_contacts_with_token(body["token"].as_str().unwrap()).await.status().as_u16());
+}
+
+#[tokio::test]
+async fn requiring_two_factor_is_admin_only() {
+    let app = spawn_app().await;
+    app.test_user_login().await;
+
+    let response = app.require_two_factor(serde_json::json!({ "required": true })).await;
+    assert_eq!(401, response.status().as_u16());
+}
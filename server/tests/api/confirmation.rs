@@ -1,7 +1,7 @@
 use wiremock::{ResponseTemplate, Mock};
 use wiremock::matchers::{path, method};
 
-use crate::helpers::spawn_app;
+use crate::helpers::{spawn_app, TestApp};
 
 #[tokio::test]
 async fn confirmations_without_token_are_rejected_with_a_400() {
@@ -29,6 +29,7 @@ async fn the_link_returned_by_signup_returns_a_200_if_called() {
         .await;
 
     app.sign_up(json).await;
+    app.dispatch_all_pending_emails().await;
 
     let email_request      = &app.email_server.received_requests().await.unwrap()[0];
     let confirmation_links = app.get_confirmation_links(&email_request);
@@ -53,6 +54,7 @@ async fn clicking_on_the_confirmation_link_confirms_a_user() {
         .await;
 
     app.sign_up(json).await;
+    app.dispatch_all_pending_emails().await;
 
     let email_request      = &app.email_server.received_requests().await.unwrap()[0];
     let confirmation_links = app.get_confirmation_links(&email_request);
@@ -69,5 +71,152 @@ async fn clicking_on_the_confirmation_link_confirms_a_user() {
         .expect("Failed to fetch saved subscription.");
     
     assert_eq!(saved.email, "click@click.click");
-    assert_eq!(saved.status.expect("matches"), "confirmed");
-}
\ No newline at end of file
+    assert_eq!(saved.status, "confirmed");
+}
+
+async fn sign_up_and_get_link(app: &TestApp, email: &str) -> reqwest::Url {
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    app.sign_up(serde_json::json!({
+        "email": email,
+        "password": "test"
+    })).await;
+    app.dispatch_all_pending_emails().await;
+
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+
+    app.get_confirmation_links(email_request).html
+}
+
+async fn user_status(app: &TestApp, email: &str) -> String {
+    sqlx::query!("SELECT status FROM users WHERE email = $1", email)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .status
+}
+
+#[tokio::test]
+async fn expired_confirmation_links_are_rejected() {
+    let app  = spawn_app().await;
+    let link = sign_up_and_get_link(&app, "expired@test.test").await;
+
+    sqlx::query!("UPDATE confirmation_tokens SET created_at = created_at - INTERVAL '2 days'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let response = reqwest::get(link).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(user_status(&app, "expired@test.test").await, "pending");
+}
+
+#[tokio::test]
+async fn resent_confirmation_link_confirms_a_user() {
+    let app = spawn_app().await;
+    sign_up_and_get_link(&app, "resend@test.test").await;
+
+    let response = app.resend_confirmation(serde_json::json!({ "email": "resend@test.test" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.dispatch_all_pending_emails().await;
+
+    let email_requests = app.email_server.received_requests().await.unwrap();
+    assert_eq!(email_requests.len(), 2);
+
+    let link     = app.get_confirmation_links(&email_requests[1]).html;
+    let response = reqwest::get(link).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(user_status(&app, "resend@test.test").await, "confirmed");
+}
+
+#[tokio::test]
+async fn resending_confirmation_is_rate_limited() {
+    let app  = spawn_app().await;
+    let json = serde_json::json!({ "email": "limit@test.test" });
+    sign_up_and_get_link(&app, "limit@test.test").await;
+
+    for _ in 0..2 {
+        let response = app.resend_confirmation(&json).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app.resend_confirmation(&json).await;
+    assert_eq!(response.status().as_u16(), 429);
+}
+
+#[tokio::test]
+async fn confirmed_users_cannot_resend_confirmation() {
+    let app  = spawn_app().await;
+    let link = sign_up_and_get_link(&app, "done@test.test").await;
+    reqwest::get(link).await.unwrap().error_for_status().unwrap();
+
+    let response = app.resend_confirmation(serde_json::json!({ "email": "done@test.test" })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn admins_can_confirm_users_manually() {
+    let app = spawn_app().await;
+    sign_up_and_get_link(&app, "manual@test.test").await;
+
+    let user = sqlx::query!("SELECT user_id FROM users WHERE email = 'manual@test.test'")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    app.test_user_login().await;
+    let response = app.admin_confirm_user(serde_json::json!({ "userId": user.user_id })).await;
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(user_status(&app, "manual@test.test").await, "pending");
+
+    app.admin_login().await;
+    let response = app.admin_confirm_user(serde_json::json!({ "userId": user.user_id })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(user_status(&app, "manual@test.test").await, "confirmed");
+
+    let response = app.admin_confirm_user(serde_json::json!({ "userId": uuid::Uuid::new_v4() })).await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn admin_confirm_explains_why_a_user_was_not_confirmed() {
+    let app = spawn_app().await;
+    sign_up_and_get_link(&app, "manual@test.test").await;
+
+    let user = sqlx::query!("SELECT user_id FROM users WHERE email = 'manual@test.test'")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+
+    app.admin_login().await;
+    app.admin_confirm_user(serde_json::json!({ "userId": user.user_id }))
+        .await
+        .error_for_status()
+        .unwrap();
+
+    let response = app.admin_confirm_user(serde_json::json!({ "userId": user.user_id })).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert!(response.text().await.unwrap().contains("User is already confirmed"));
+
+    sqlx::query!("UPDATE users SET status = 'suspended' WHERE user_id = $1", user.user_id)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let response = app.admin_confirm_user(serde_json::json!({ "userId": user.user_id })).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert!(response.text().await.unwrap().contains("User is suspended"));
+    assert_eq!(user_status(&app, "manual@test.test").await, "suspended");
+
+    let response = app.admin_confirm_user(serde_json::json!({ "userId": uuid::Uuid::new_v4() })).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert!(response.text().await.unwrap().contains("User not found"));
+}
@@ -0,0 +1,86 @@
+use byot_server::domain::NearbyContacts;
+use crate::helpers::{spawn_app, TestApp};
+
+const CSV: &str = "display_name,city,state,genres
+Static Age,Asheville,NC,punk/hardcore
+Gallery 5,Richmond,VA,punk/hardcore
+Cary Street Cafe,Richmond,VA,punk/hardcore
+Ottobar,Baltimore,MD,punk/hardcore
+No Coordinates,Richmond,VA,punk/hardcore";
+
+const COORDINATES: [(&str, f32, f32); 4] = [
+    ("Static Age", 35.5951, -82.5515),
+    ("Gallery 5", 37.5470, -77.4457),
+    ("Cary Street Cafe", 37.5525, -77.4650),
+    ("Ottobar", 39.3188, -76.6189),
+];
+
+async fn seeded_app() -> TestApp {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.import_contacts("commit", CSV).await;
+    assert_eq!(200, response.status().as_u16());
+
+    for (name, lat, lng) in COORDINATES {
+        sqlx::query!(
+            "UPDATE contacts SET latitude = $1, longitude = $2 WHERE display_name = $3",
+            lat,
+            lng,
+            name
+        )
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    }
+
+    app
+}
+
+fn names(nearby: &NearbyContacts) -> Vec<&str> {
+    nearby.contacts.iter().map(|c| c.contact.display_name.as_str()).collect()
+}
+
+#[tokio::test]
+async fn nearby_contacts_are_sorted_by_distance() {
+    let app    = seeded_app().await;
+    let nearby = app.contacts_nearby(&[("lat", "37.5407"), ("lng", "-77.4360"), ("radiusKm", "250")])
+        .await
+        .json::<NearbyContacts>()
+        .await
+        .unwrap();
+
+    assert_eq!(vec!["Gallery 5", "Cary Street Cafe", "Ottobar"], names(&nearby));
+    assert!(nearby.contacts[0].distance_km < 2.0);
+    assert!(nearby.contacts[2].distance_km > 200.0);
+}
+
+#[tokio::test]
+async fn contacts_in_bounds_are_returned() {
+    let app    = seeded_app().await;
+    let nearby = app.contacts_in_bounds(&[("north", "38"), ("south", "35"), ("east", "-77"), ("west", "-83")])
+        .await
+        .json::<NearbyContacts>()
+        .await
+        .unwrap();
+
+    let mut found = names(&nearby);
+    found.sort();
+
+    assert_eq!(vec!["Cary Street Cafe", "Gallery 5", "Static Age"], found);
+}
+
+#[tokio::test]
+async fn invalid_geo_queries_are_rejected() {
+    let app = spawn_app().await;
+
+    let response = app.contacts_nearby(&[("lat", "37.5"), ("radiusKm", "100")]).await;
+    assert_eq!(400, response.status().as_u16());
+
+    let response = app.contacts_nearby(&[("lat", "37.5"), ("lng", "-77.4"), ("radiusKm", "-5")]).await;
+    assert_eq!(400, response.status().as_u16());
+
+    let response = app.contacts_in_bounds(&[("north", "35"), ("south", "38"), ("east", "-77"), ("west", "-83")]).await;
+    assert_eq!(400, response.status().as_u16());
+}
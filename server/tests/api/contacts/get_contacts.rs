@@ -1,9 +1,6 @@
-use byot_server::domain::{ContactResponse};
+use byot_server::domain::ContactPage;
 use crate::helpers::spawn_app;
 
-#[derive(serde::Deserialize, Debug)]
-struct Contacts(Vec<ContactResponse>);
-
 #[tokio::test]
 async fn contacts_returns_a_200() {
     let app      = spawn_app().await;
@@ -47,11 +44,11 @@ async fn private_contact_not_returned_with_public_contacts() {
     assert_eq!(200, response.status().as_u16());
 
     // Query public contacts
-    let response: Contacts = app.get_contacts()
+    let response: ContactPage = app.get_contacts()
         .await
         .json()
         .await
         .unwrap();
 
-    assert!(response.0.is_empty());
+    assert!(response.contacts.is_empty());
 }
\ No newline at end of file
@@ -0,0 +1,84 @@
+use byot_server::domain::{ContactPage, ImportReport};
+use crate::helpers::spawn_app;
+
+const CSV: &str = "display_name,address,city,state,zip_code,capacity,email,contact_form,age_range,contact_type,is_private,genres
+Static Age,16 Lexington,Asheville,NC,28806,99,info@static.com,,all,venue,false,punk/hardcore
+St. Vitus,1120 Manhattan Ave,Brooklyn,NY,11222,200,,https://www.saintvitusbar.com,21+,venue,false,metal;punk/hardcore
+No City,,,,,,,,all,venue,false,indie
+Bad Genre,,Richmond,VA,,,not-an-email,,all,venue,false,polka";
+
+#[tokio::test]
+async fn unauthenticated_user_cannot_import_contacts() {
+    let app      = spawn_app().await;
+    let response = app.import_contacts("dry-run", CSV).await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn dry_run_reports_errors_without_saving() {
+    // Log in
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Dry run
+    let response = app.import_contacts("dry-run", CSV).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let report = response.json::<ImportReport>().await.unwrap();
+
+    assert_eq!(4, report.total_rows);
+    assert_eq!(2, report.valid_rows);
+    assert_eq!(0, report.imported);
+    assert_eq!(vec![4, 5], report.errors.iter().map(|e| e.row).collect::<Vec<_>>());
+    assert_eq!(2, report.errors[1].errors.len()); // bad email and unknown genre
+
+    // Nothing was saved
+    let contacts = app.get_contacts()
+        .await
+        .json::<ContactPage>()
+        .await
+        .unwrap();
+
+    assert!(contacts.contacts.is_empty());
+}
+
+#[tokio::test]
+async fn commit_saves_valid_rows() {
+    // Log in
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Commit
+    let response = app.import_contacts("commit", CSV).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let report = response.json::<ImportReport>().await.unwrap();
+    assert_eq!(2, report.imported);
+
+    // Valid rows were saved with their genres
+    let contacts = app.get_contacts()
+        .await
+        .json::<ContactPage>()
+        .await
+        .unwrap();
+    let vitus    = contacts.contacts.iter().find(|c| c.display_name == "St. Vitus").unwrap();
+
+    assert_eq!(2, contacts.contacts.len());
+    assert_eq!(2, vitus.genres.len());
+    assert_eq!(app.test_user.user_id, vitus.user_id);
+}
+
+#[tokio::test]
+async fn file_missing_required_columns_is_rejected() {
+    // Log in
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.import_contacts("commit", "name,town\nStatic Age,Asheville").await;
+
+    assert_eq!(400, response.status().as_u16());
+}
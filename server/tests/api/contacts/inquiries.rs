@@ -0,0 +1,194 @@
+use wiremock::{ResponseTemplate, Mock};
+use wiremock::matchers::{path, method};
+
+use byot_server::domain::{ContactResponse, InquiryTemplate, Outreach, OutreachChannel, SentInquiry};
+use crate::helpers::{spawn_app, TestApp};
+
+async fn create_contact_with_email(app: &TestApp) -> ContactResponse {
+    let response = app.test_user_login().await;
+    assert_eq!(200, response.status().as_u16());
+
+    let response = app.add_contact(serde_json::json!({
+        "displayName": "The Mothlight",
+        "city":        "asheville",
+        "email":       "booking@mothlight.example.com",
+        "ageRange":    "all",
+        "contactType": "venue",
+        "isPrivate":   true,
+        "genres":      [1]
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    response.json().await.unwrap()
+}
+
+async fn create_template(app: &TestApp) -> InquiryTemplate {
+    let response = app.add_inquiry_template(serde_json::json!({
+        "templateName": "first contact",
+        "subject":      "Booking inquiry for {{venue_name}}",
+        "body":         "Hi {{venue_name}},\nWe're playing {{city}} on {{dates}}. Listen at {{band_link}}"
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    response.json().await.unwrap()
+}
+
+fn send_json(template: &InquiryTemplate, contact: &ContactResponse) -> serde_json::Value {
+    serde_json::json!({
+        "templateId":    template.template_id,
+        "contactId":     contact.contact_id,
+        "requestedFrom": "2024-06-01",
+        "requestedTo":   "2024-06-03",
+        "bandLink":      "https://band.example.com"
+    })
+}
+
+async fn mock_email_server(app: &TestApp) {
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+}
+
+#[tokio::test]
+async fn inquiry_is_rendered_and_sent_with_reply_to() {
+    let app      = spawn_app().await;
+    let contact  = create_contact_with_email(&app).await;
+    let template = create_template(&app).await;
+    mock_email_server(&app).await;
+
+    let response = app.send_inquiry(send_json(&template, &contact)).await;
+    assert_eq!(200, response.status().as_u16());
+
+    // Inquiries go through the outbox like every other email
+    assert!(app.email_server.received_requests().await.unwrap().is_empty());
+    app.dispatch_all_pending_emails().await;
+
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    assert_eq!("booking@mothlight.example.com", body["To"]);
+    assert_eq!(app.test_user.email, body["ReplyTo"]);
+    assert_eq!("Booking inquiry for The Mothlight", body["Subject"]);
+    assert_eq!(
+        "Hi The Mothlight,\nWe're playing asheville on June 1, 2024 to June 3, 2024. Listen at https://band.example.com",
+        body["TextBody"]
+    );
+}
+
+#[tokio::test]
+async fn sent_inquiries_are_logged_to_outreach() {
+    let app      = spawn_app().await;
+    let contact  = create_contact_with_email(&app).await;
+    let template = create_template(&app).await;
+    mock_email_server(&app).await;
+
+    let sent: SentInquiry = app.send_inquiry(send_json(&template, &contact))
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    let inquiries: Vec<SentInquiry> = app.get_sent_inquiries().await.json().await.unwrap();
+    assert_eq!(1, inquiries.len());
+    assert_eq!(sent.inquiry_id, inquiries[0].inquiry_id);
+
+    let outreach: Vec<Outreach> = app.get_outreach(&contact.contact_id.to_string())
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, outreach.len());
+    assert_eq!(OutreachChannel::Email, outreach[0].channel);
+    assert_eq!(sent.outreach_id, Some(outreach[0].outreach_id));
+}
+
+#[tokio::test]
+async fn daily_send_limit_is_enforced() {
+    let app      = spawn_app().await;
+    let contact  = create_contact_with_email(&app).await;
+    let template = create_template(&app).await;
+    mock_email_server(&app).await;
+
+    // The test app allows two a day
+    for _ in 0..2 {
+        let response = app.send_inquiry(send_json(&template, &contact)).await;
+        assert_eq!(200, response.status().as_u16());
+    }
+
+    let response = app.send_inquiry(send_json(&template, &contact)).await;
+    assert_eq!(429, response.status().as_u16());
+
+    app.dispatch_all_pending_emails().await;
+    assert_eq!(2, app.email_server.received_requests().await.unwrap().len());
+}
+
+#[tokio::test]
+async fn racing_sends_cannot_pass_the_daily_limit() {
+    let app      = spawn_app().await;
+    let contact  = create_contact_with_email(&app).await;
+    let template = create_template(&app).await;
+    mock_email_server(&app).await;
+
+    let json      = send_json(&template, &contact);
+    let responses = futures_util::future::join_all(
+        (0..4).map(|_| app.send_inquiry(json.clone()))
+    ).await;
+
+    let sent = responses.iter().filter(|r| r.status().as_u16() == 200).count();
+    assert_eq!(2, sent);
+
+    let inquiries: Vec<SentInquiry> = app.get_sent_inquiries().await.json().await.unwrap();
+    assert_eq!(2, inquiries.len());
+}
+
+#[tokio::test]
+async fn inquiry_fails_without_values_for_its_placeholders() {
+    let app      = spawn_app().await;
+    let contact  = create_contact_with_email(&app).await;
+    let template = create_template(&app).await;
+    mock_email_server(&app).await;
+
+    let response = app.send_inquiry(serde_json::json!({
+        "templateId": template.template_id,
+        "contactId":  contact.contact_id
+    })).await;
+    assert_eq!(400, response.status().as_u16());
+
+    app.dispatch_all_pending_emails().await;
+    assert!(app.email_server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn templates_can_be_edited_and_deleted() {
+    let app      = spawn_app().await;
+    create_contact_with_email(&app).await;
+    let template = create_template(&app).await;
+
+    let response = app.add_inquiry_template(serde_json::json!({
+        "templateName": "bad",
+        "subject":      "hi",
+        "body":         "Dear {{booker}}"
+    })).await;
+    assert_eq!(400, response.status().as_u16());
+
+    let edited: InquiryTemplate = app.user_edit_inquiry_template(serde_json::json!({
+        "templateId":   template.template_id,
+        "templateName": "follow up",
+        "subject":      "Following up with {{venue_name}}",
+        "body":         "Any news?"
+    }))
+    .await
+    .json()
+    .await
+    .unwrap();
+    assert_eq!("follow up", edited.template_name);
+
+    let response = app.user_delete_inquiry_template(serde_json::json!({
+        "templateId": template.template_id
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let templates: Vec<InquiryTemplate> = app.get_inquiry_templates().await.json().await.unwrap();
+    assert!(templates.is_empty());
+}
@@ -1,5 +1,11 @@
 // mod create_contacts;
 // mod delete_contacts;
 // mod edit_contacts;
-// mod get_contacts;
-mod pending_contacts;
\ No newline at end of file
+mod geo_contacts;
+mod get_contacts;
+mod import_contacts;
+mod inquiries;
+mod outreach;
+mod pending_contacts;
+mod search_contacts;
+mod share_contacts;
@@ -0,0 +1,178 @@
+use chrono::{Duration, NaiveDate, Utc};
+
+use byot_server::domain::{ContactResponse, FollowUp, Outreach, OutreachOutcome};
+use crate::helpers::{spawn_app, TestApp, TestUser};
+
+fn days_ago(days: i64) -> NaiveDate {
+    (Utc::now() - Duration::days(days)).date_naive()
+}
+
+async fn create_contact(app: &TestApp) -> ContactResponse {
+    let response = app.test_user_login().await;
+    assert_eq!(200, response.status().as_u16());
+
+    let is_private = true;
+    app.create_contact(is_private)
+        .await
+        .json::<ContactResponse>()
+        .await
+        .unwrap()
+}
+
+async fn log_outreach(app: &TestApp, contact_id: i32, date: NaiveDate) -> Outreach {
+    let response = app.add_outreach(serde_json::json!({
+        "contactId":     contact_id,
+        "outreachDate":  date,
+        "channel":       "email",
+        "requestedFrom": "2024-06-01",
+        "requestedTo":   "2024-06-03",
+        "notes":         "asked about a friday"
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn outreach_is_listed_newest_first_for_its_author_only() {
+    let app     = spawn_app().await;
+    let other   = TestUser::generate();
+    other.store(&app.db_pool).await;
+    let contact = create_contact(&app).await;
+    let id      = contact.contact_id.to_string();
+
+    let first  = log_outreach(&app, contact.contact_id, days_ago(20)).await;
+    let second = log_outreach(&app, contact.contact_id, days_ago(2)).await;
+    assert_eq!(OutreachOutcome::NoReply, first.outcome);
+
+    let outreach: Vec<Outreach> = app.get_outreach(&id).await.json().await.unwrap();
+    assert_eq!(2, outreach.len());
+    assert_eq!(second.outreach_id, outreach[0].outreach_id);
+    assert_eq!(first.outreach_id, outreach[1].outreach_id);
+
+    app.post_logout().await;
+    app.post_login(serde_json::json!({
+        "email":    &other.email,
+        "password": &other.password
+    })).await;
+
+    let outreach: Vec<Outreach> = app.get_outreach(&id).await.json().await.unwrap();
+    assert!(outreach.is_empty());
+
+    let response = app.user_edit_outreach(serde_json::json!({
+        "outreachId":   first.outreach_id,
+        "outreachDate": days_ago(20),
+        "channel":      "email",
+        "outcome":      "offer"
+    })).await;
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn deleting_outreach_removes_it() {
+    let app      = spawn_app().await;
+    let contact  = create_contact(&app).await;
+    let outreach = log_outreach(&app, contact.contact_id, days_ago(3)).await;
+
+    let response = app.user_delete_outreach(serde_json::json!({
+        "outreachId": outreach.outreach_id
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let outreach: Vec<Outreach> = app.get_outreach(&contact.contact_id.to_string())
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert!(outreach.is_empty());
+}
+
+#[tokio::test]
+async fn deleting_missing_outreach_is_rejected() {
+    let app      = spawn_app().await;
+    let contact  = create_contact(&app).await;
+    let outreach = log_outreach(&app, contact.contact_id, days_ago(3)).await;
+
+    let response = app.user_delete_outreach(serde_json::json!({
+        "outreachId": uuid::Uuid::new_v4()
+    })).await;
+    assert_eq!(400, response.status().as_u16());
+
+    app.admin_login().await;
+    let response = app.user_delete_outreach(serde_json::json!({
+        "outreachId": outreach.outreach_id
+    })).await;
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn unanswered_inquiries_are_due_a_follow_up() {
+    let app     = spawn_app().await;
+    let contact = create_contact(&app).await;
+    let stale   = log_outreach(&app, contact.contact_id, days_ago(10)).await;
+
+    let due: Vec<FollowUp> = app.get_follow_ups(7).await.json().await.unwrap();
+    assert_eq!(1, due.len());
+    assert_eq!(stale.outreach_id, due[0].outreach.outreach_id);
+    assert_eq!(10, due[0].days_waiting);
+    assert_eq!(contact.display_name, due[0].display_name);
+
+    let due: Vec<FollowUp> = app.get_follow_ups(14).await.json().await.unwrap();
+    assert!(due.is_empty());
+
+    // Recording a reply takes it off the list
+    let response = app.user_edit_outreach(serde_json::json!({
+        "outreachId":   stale.outreach_id,
+        "outreachDate": days_ago(10),
+        "channel":      "email",
+        "outcome":      "hold",
+        "notes":        "holding the 2nd"
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let due: Vec<FollowUp> = app.get_follow_ups(7).await.json().await.unwrap();
+    assert!(due.is_empty());
+}
+
+#[tokio::test]
+async fn following_up_resets_the_clock() {
+    let app     = spawn_app().await;
+    let contact = create_contact(&app).await;
+
+    log_outreach(&app, contact.contact_id, days_ago(10)).await;
+    log_outreach(&app, contact.contact_id, days_ago(1)).await;
+
+    let due: Vec<FollowUp> = app.get_follow_ups(7).await.json().await.unwrap();
+    assert!(due.is_empty());
+}
+
+#[tokio::test]
+async fn outreach_requires_a_visible_contact_and_valid_dates() {
+    let app     = spawn_app().await;
+    let other   = TestUser::generate();
+    other.store(&app.db_pool).await;
+    let contact = create_contact(&app).await;
+
+    let response = app.add_outreach(serde_json::json!({
+        "contactId":     contact.contact_id,
+        "outreachDate":  days_ago(0),
+        "channel":       "phone",
+        "requestedFrom": "2024-06-10",
+        "requestedTo":   "2024-06-01"
+    })).await;
+    assert_eq!(400, response.status().as_u16());
+
+    app.post_logout().await;
+    app.post_login(serde_json::json!({
+        "email":    &other.email,
+        "password": &other.password
+    })).await;
+
+    // Someone else's private contact
+    let response = app.add_outreach(serde_json::json!({
+        "contactId":    contact.contact_id,
+        "outreachDate": days_ago(0),
+        "channel":      "form"
+    })).await;
+    assert_eq!(400, response.status().as_u16());
+}
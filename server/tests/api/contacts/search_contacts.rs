@@ -0,0 +1,109 @@
+use byot_server::domain::ContactPage;
+use crate::helpers::{spawn_app, TestApp};
+
+const CSV: &str = "display_name,city,state,capacity,age_range,contact_type,genres
+Static Age,Asheville,NC,99,all,venue,punk/hardcore
+St. Vitus,Brooklyn,NY,200,21+,venue,metal;punk/hardcore
+The Mothlight,Asheville,NC,,21+,venue,indie
+Basement Shows,Richmond,VA,40,all,diy,punk/hardcore";
+
+async fn seeded_app() -> TestApp {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.import_contacts("commit", CSV).await;
+    assert_eq!(200, response.status().as_u16());
+
+    app
+}
+
+fn names(page: &ContactPage) -> Vec<&str> {
+    page.contacts.iter().map(|c| c.display_name.as_str()).collect()
+}
+
+#[tokio::test]
+async fn contacts_are_filtered_by_query_parameters() {
+    let app = seeded_app().await;
+
+    let page = app.search_contacts(&[("minCapacity", "50"), ("allowNullCapacity", "false")])
+        .await
+        .json::<ContactPage>()
+        .await
+        .unwrap();
+    assert_eq!(vec!["Static Age", "St. Vitus"], names(&page));
+    assert_eq!(2, page.total_count);
+
+    let page = app.search_contacts(&[("ageRanges", "21+"), ("q", "asheville")])
+        .await
+        .json::<ContactPage>()
+        .await
+        .unwrap();
+    assert_eq!(vec!["The Mothlight"], names(&page));
+
+    let page = app.search_contacts(&[("contactTypes", "diy,promoter")])
+        .await
+        .json::<ContactPage>()
+        .await
+        .unwrap();
+    assert_eq!(vec!["Basement Shows"], names(&page));
+}
+
+#[tokio::test]
+async fn contacts_are_filtered_by_genre() {
+    let app   = seeded_app().await;
+    let vitus = app.search_contacts(&[("q", "vitus")])
+        .await
+        .json::<ContactPage>()
+        .await
+        .unwrap();
+    let metal = vitus.contacts[0].genres.iter()
+        .find(|g| g.genre_name == "metal")
+        .unwrap()
+        .genre_id
+        .to_string();
+
+    let page = app.search_contacts(&[("genres", metal.as_str())])
+        .await
+        .json::<ContactPage>()
+        .await
+        .unwrap();
+
+    assert_eq!(vec!["St. Vitus"], names(&page));
+    // Contacts keep all of their genres, not only the matching one
+    assert_eq!(2, page.contacts[0].genres.len());
+}
+
+#[tokio::test]
+async fn contacts_are_paginated_with_a_cursor() {
+    let app = seeded_app().await;
+
+    let first = app.search_contacts(&[("limit", "3")])
+        .await
+        .json::<ContactPage>()
+        .await
+        .unwrap();
+    assert_eq!(3, first.contacts.len());
+    assert_eq!(4, first.total_count);
+
+    let cursor = first.next_cursor.unwrap().to_string();
+    let second = app.search_contacts(&[("limit", "3"), ("cursor", cursor.as_str())])
+        .await
+        .json::<ContactPage>()
+        .await
+        .unwrap();
+    assert_eq!(vec!["Basement Shows"], names(&second));
+    assert_eq!(4, second.total_count);
+    assert_eq!(None, second.next_cursor);
+}
+
+#[tokio::test]
+async fn invalid_search_parameters_are_rejected() {
+    let app = spawn_app().await;
+
+    let response = app.search_contacts(&[("minCapacity", "200"), ("maxCapacity", "100")]).await;
+    assert_eq!(400, response.status().as_u16());
+
+    let response = app.search_contacts(&[("limit", "0")]).await;
+    assert_eq!(400, response.status().as_u16());
+}
@@ -0,0 +1,212 @@
+use byot_server::domain::{ContactAccess, ContactResponse, ContactShare, SharedContact};
+use crate::helpers::{spawn_app, TestApp, TestUser};
+
+async fn create_private_contact(app: &TestApp) -> ContactResponse {
+    let response = app.test_user_login().await;
+    assert_eq!(200, response.status().as_u16());
+
+    let is_private = true;
+    app.create_contact(is_private)
+        .await
+        .json::<ContactResponse>()
+        .await
+        .unwrap()
+}
+
+async fn login_as(app: &TestApp, user: &TestUser) {
+    app.post_logout().await;
+    let response = app.post_login(serde_json::json!({
+        "email":    &user.email,
+        "password": &user.password
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn unauthenticated_user_cannot_share_contact() {
+    let app      = spawn_app().await;
+    let response = app.share_contact(serde_json::json!({
+        "contactId": 1,
+        "email":     "someone@example.com"
+    })).await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn shared_contact_is_visible_to_grantee() {
+    let app     = spawn_app().await;
+    let grantee = TestUser::generate();
+    grantee.store(&app.db_pool).await;
+    let contact = create_private_contact(&app).await;
+
+    let response = app.share_contact(serde_json::json!({
+        "contactId": contact.contact_id,
+        "email":     &grantee.email
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let shares: Vec<ContactShare> = app.get_contact_shares(&contact.contact_id.to_string())
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, shares.len());
+    assert_eq!(grantee.email, shares[0].email);
+    assert_eq!(ContactAccess::Read, shares[0].access);
+
+    login_as(&app, &grantee).await;
+
+    let contacts: Vec<ContactResponse> = app.user_get_contacts()
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, contacts.len());
+    assert_eq!(contact.contact_id, contacts[0].contact_id);
+
+    let shared: Vec<SharedContact> = app.get_shared_contacts()
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, shared.len());
+    assert_eq!(app.test_user.email, shared[0].shared_by);
+    assert_eq!(contact.contact_id, shared[0].contact.contact_id);
+}
+
+#[tokio::test]
+async fn private_contacts_are_not_visible_to_other_users() {
+    let app   = spawn_app().await;
+    let other = TestUser::generate();
+    other.store(&app.db_pool).await;
+    let contact = create_private_contact(&app).await;
+
+    login_as(&app, &other).await;
+
+    let contacts: Vec<ContactResponse> = app.user_get_contacts()
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert!(contacts.is_empty());
+
+    let response: Option<ContactResponse> = app.get_contact(&contact.contact_id.to_string())
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert!(response.is_none());
+}
+
+#[tokio::test]
+async fn read_access_cannot_edit_but_edit_access_can() {
+    let app     = spawn_app().await;
+    let grantee = TestUser::generate();
+    grantee.store(&app.db_pool).await;
+    let contact = create_private_contact(&app).await;
+    let share   = |access: &str| serde_json::json!({
+        "contactId": contact.contact_id,
+        "email":     &grantee.email,
+        "access":    access
+    });
+    let edited  = serde_json::json!({
+        "contactId":   contact.contact_id,
+        "userId":      grantee.user_id,
+        "displayName": "changed name",
+        "city":        "new city",
+        "ageRange":    "all",
+        "contactType": "venue",
+        "isPrivate":   false,
+        "genres":      [1]
+    });
+
+    let response = app.share_contact(share("read")).await;
+    assert_eq!(200, response.status().as_u16());
+
+    login_as(&app, &grantee).await;
+    let response = app.user_edit_contact(&edited).await;
+    assert_eq!(401, response.status().as_u16());
+
+    login_as(&app, &app.test_user).await;
+    let response = app.share_contact(share("edit")).await;
+    assert_eq!(200, response.status().as_u16());
+
+    login_as(&app, &grantee).await;
+    let response = app.user_edit_contact(&edited).await;
+    assert_eq!(200, response.status().as_u16());
+
+    // Editors can't make someone else's contact public
+    let contact: ContactResponse = response.json().await.unwrap();
+    assert_eq!("changed name", contact.display_name);
+    assert!(contact.is_private);
+    assert_eq!(app.test_user.user_id, contact.user_id);
+}
+
+#[tokio::test]
+async fn unsharing_removes_access() {
+    let app     = spawn_app().await;
+    let grantee = TestUser::generate();
+    grantee.store(&app.db_pool).await;
+    let contact = create_private_contact(&app).await;
+    let json    = serde_json::json!({
+        "contactId": contact.contact_id,
+        "email":     &grantee.email
+    });
+
+    app.share_contact(&json).await;
+    let response = app.unshare_contact(&json).await;
+    assert_eq!(200, response.status().as_u16());
+
+    login_as(&app, &grantee).await;
+
+    let shared: Vec<SharedContact> = app.get_shared_contacts()
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert!(shared.is_empty());
+}
+
+#[tokio::test]
+async fn only_the_owner_can_share_a_contact() {
+    let app   = spawn_app().await;
+    let other = TestUser::generate();
+    other.store(&app.db_pool).await;
+    let contact = create_private_contact(&app).await;
+
+    login_as(&app, &other).await;
+
+    let response = app.share_contact(serde_json::json!({
+        "contactId": contact.contact_id,
+        "email":     &other.email
+    })).await;
+    assert_eq!(401, response.status().as_u16());
+
+    let response = app.get_contact_shares(&contact.contact_id.to_string()).await;
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn sharing_fails_for_unknown_users_and_public_contacts() {
+    let app     = spawn_app().await;
+    let contact = create_private_contact(&app).await;
+
+    let response = app.share_contact(serde_json::json!({
+        "contactId": contact.contact_id,
+        "email":     "nobody@example.com"
+    })).await;
+    assert_eq!(400, response.status().as_u16());
+
+    let is_private = false;
+    let public: ContactResponse = app.create_contact(is_private)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let response = app.share_contact(serde_json::json!({
+        "contactId": public.contact_id,
+        "email":     &app.admin.email
+    })).await;
+    assert_eq!(400, response.status().as_u16());
+}
@@ -0,0 +1,131 @@
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use byot_server::domain::OutboxEmail;
+use crate::helpers::{spawn_app, TestApp};
+
+async fn sign_up(app: &TestApp) {
+    let response = app.sign_up(serde_json::json!({
+        "email":    "outbox@test.test",
+        "password": "test"
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+async fn emails(app: &TestApp, status: &str) -> Vec<OutboxEmail> {
+    let response = app.get_emails(status).await;
+    assert_eq!(200, response.status().as_u16());
+
+    response.json().await.unwrap()
+}
+
+/// Makes every queued email due now, skipping the backoff
+async fn skip_backoff(app: &TestApp) {
+    sqlx::query!("UPDATE email_outbox SET run_after = current_timestamp WHERE status = 'queued'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+}
+
+async fn mock_email_server(app: &TestApp, status: u16) {
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(status))
+        .mount(&app.email_server)
+        .await;
+}
+
+#[tokio::test]
+async fn emails_are_sent_by_the_worker() {
+    let app = spawn_app().await;
+    mock_email_server(&app, 200).await;
+
+    sign_up(&app).await;
+    assert!(app.email_server.received_requests().await.unwrap().is_empty());
+
+    app.dispatch_all_pending_emails().await;
+
+    assert_eq!(1, app.email_server.received_requests().await.unwrap().len());
+
+    app.admin_login().await;
+    let sent = emails(&app, "sent").await;
+    assert_eq!(1, sent.len());
+    assert_eq!("outbox@test.test", sent[0].recipient);
+}
+
+#[tokio::test]
+async fn sign_up_succeeds_when_the_email_server_is_down() {
+    let app = spawn_app().await;
+    mock_email_server(&app, 500).await;
+
+    sign_up(&app).await;
+
+    // The email is pushed back, so a second pass doesn't try it again
+    app.dispatch_all_pending_emails().await;
+    app.dispatch_all_pending_emails().await;
+
+    let saved = sqlx::query!("SELECT status FROM users WHERE email = 'outbox@test.test'")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!("pending", saved.status);
+
+    app.admin_login().await;
+    let queued = emails(&app, "queued").await;
+    assert_eq!(1, queued.len());
+    assert_eq!(1, queued[0].attempts);
+    assert!(queued[0].last_error.is_some());
+}
+
+#[tokio::test]
+async fn emails_fail_after_repeated_errors_and_can_be_resent() {
+    let app = spawn_app().await;
+    mock_email_server(&app, 500).await;
+    sign_up(&app).await;
+
+    for _ in 0..10 {
+        app.dispatch_all_pending_emails().await;
+        skip_backoff(&app).await;
+    }
+
+    app.admin_login().await;
+    let failed = emails(&app, "failed").await;
+    assert_eq!(1, failed.len());
+    assert_eq!(8, failed[0].attempts);
+
+    app.email_server.reset().await;
+    mock_email_server(&app, 200).await;
+
+    let response = app.resend_email(serde_json::json!({ "emailId": failed[0].email_id })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    app.dispatch_all_pending_emails().await;
+
+    assert_eq!(1, app.email_server.received_requests().await.unwrap().len());
+    assert!(emails(&app, "failed").await.is_empty());
+    assert_eq!(1, emails(&app, "sent").await.len());
+}
+
+#[tokio::test]
+async fn only_failed_emails_can_be_resent() {
+    let app = spawn_app().await;
+    sign_up(&app).await;
+
+    app.admin_login().await;
+    let queued = emails(&app, "queued").await;
+
+    let response = app.resend_email(serde_json::json!({ "emailId": queued[0].email_id })).await;
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn outbox_is_admin_only() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    assert_eq!(401, app.get_emails("failed").await.status().as_u16());
+
+    let response = app.resend_email(serde_json::json!({ "emailId": uuid::Uuid::new_v4() })).await;
+    assert_eq!(401, response.status().as_u16());
+}
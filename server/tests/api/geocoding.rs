@@ -0,0 +1,284 @@
+use wiremock::matchers::{method, query_param};
+use wiremock::{Mock, ResponseTemplate};
+
+use byot_server::domain::{AddressMismatch, GeocodingJob};
+use crate::helpers::{spawn_app, TestApp};
+
+fn google_response(lat: f32, lng: f32) -> serde_json::Value {
+    google_response_in(lat, lng, "NC", "North Carolina")
+}
+
+fn google_response_in(lat: f32, lng: f32, region: &str, region_name: &str) -> serde_json::Value {
+    let location = serde_json::json!({ "lat": lat, "lng": lng });
+    let bounds   = serde_json::json!({ "northeast": location, "southwest": location });
+
+    serde_json::json!({
+        "status": "OK",
+        "results": [{
+            "address_components": [
+                { "long_name": "Asheville", "short_name": "Asheville", "types": ["locality", "political"] },
+                { "long_name": region_name, "short_name": region, "types": ["administrative_area_level_1", "political"] },
+                { "long_name": "28711", "short_name": "28711", "types": ["postal_code"] },
+                { "long_name": "United States", "short_name": "US", "types": ["country", "political"] }
+            ],
+            "formatted_address":  "123 Fake St, Asheville, NC 28711, USA",
+            "place_id":           "test-place",
+            "types":              ["street_address"],
+            "geometry": {
+                "bounds":        bounds,
+                "location":      location,
+                "location_type": "ROOFTOP",
+                "viewport":      bounds
+            }
+        }]
+    })
+}
+
+async fn approved_contact(app: &TestApp) -> i32 {
+    let response = app.admin_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.create_contact(false).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let contact  = app.get_first_pending_contact().await;
+    let response = app.approve_contact(contact.clone()).await;
+    assert_eq!(200, response.status().as_u16());
+
+    contact.contact_id
+}
+
+async fn coordinates(app: &TestApp, contact_id: i32) -> (Option<f32>, Option<f32>) {
+    let row = sqlx::query!(
+        "SELECT latitude, longitude FROM contacts WHERE contact_id = $1",
+        contact_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    (row.latitude, row.longitude)
+}
+
+async fn jobs(app: &TestApp, status: &str) -> Vec<GeocodingJob> {
+    let response = app.get_geocoding_jobs(status).await;
+    assert_eq!(200, response.status().as_u16());
+
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn approving_a_contact_geocodes_it_in_the_background() {
+    let app        = spawn_app().await;
+    let contact_id = approved_contact(&app).await;
+
+    // Nothing is looked up until the worker runs
+    assert_eq!((None, None), coordinates(&app, contact_id).await);
+    assert_eq!(1, jobs(&app, "queued").await.len());
+
+    Mock::given(method("GET"))
+        .and(query_param("address", "123 fake st, asheville, NC, 28711"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(google_response(35.5, -82.5)))
+        .expect(1)
+        .mount(&app.gmaps_server)
+        .await;
+
+    app.dispatch_all_pending_geocoding_jobs().await;
+
+    assert_eq!((Some(35.5), Some(-82.5)), coordinates(&app, contact_id).await);
+    assert_eq!("approval", jobs(&app, "done").await[0].reason);
+}
+
+#[tokio::test]
+async fn normalised_address_is_saved_with_the_typed_one() {
+    let app        = spawn_app().await;
+    let contact_id = approved_contact(&app).await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(google_response(35.5, -82.5)))
+        .mount(&app.gmaps_server)
+        .await;
+    app.dispatch_all_pending_geocoding_jobs().await;
+
+    let row = sqlx::query!(
+        r#"
+        SELECT city, state, geocoded_locality, geocoded_region, geocoded_postal_code, geocoded_country
+        FROM contacts WHERE contact_id = $1
+        "#,
+        contact_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+
+    assert_eq!("asheville", row.city);
+    assert_eq!(Some("Asheville".to_string()), row.geocoded_locality);
+    assert_eq!(Some("NC".to_string()), row.geocoded_region);
+    assert_eq!(Some("28711".to_string()), row.geocoded_postal_code);
+    assert_eq!(Some("US".to_string()), row.geocoded_country);
+
+    let response = app.get_address_mismatches().await;
+    assert_eq!(200, response.status().as_u16());
+    assert!(response.json::<Vec<AddressMismatch>>().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn contacts_geocoded_somewhere_else_are_reported() {
+    let app        = spawn_app().await;
+    let contact_id = approved_contact(&app).await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(google_response_in(35.5, -82.5, "SC", "South Carolina")))
+        .mount(&app.gmaps_server)
+        .await;
+    app.dispatch_all_pending_geocoding_jobs().await;
+
+    let mismatches = app.get_address_mismatches()
+        .await
+        .json::<Vec<AddressMismatch>>()
+        .await
+        .unwrap();
+
+    assert_eq!(1, mismatches.len());
+    assert_eq!(contact_id, mismatches[0].contact_id);
+    assert_eq!(Some("NC".to_string()), mismatches[0].state);
+    assert_eq!(vec!["state".to_string()], mismatches[0].address_mismatches);
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried_later() {
+    let app        = spawn_app().await;
+    let contact_id = approved_contact(&app).await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&app.gmaps_server)
+        .await;
+
+    // The job is pushed back, so a second pass doesn't pick it up again
+    app.dispatch_all_pending_geocoding_jobs().await;
+    app.dispatch_all_pending_geocoding_jobs().await;
+
+    let queued = jobs(&app, "queued").await;
+    assert_eq!(1, queued.len());
+    assert_eq!(1, queued[0].attempts);
+    assert!(queued[0].last_error.is_some());
+    assert_eq!((None, None), coordinates(&app, contact_id).await);
+}
+
+#[tokio::test]
+async fn jobs_claimed_by_another_worker_are_skipped() {
+    let app        = spawn_app().await;
+    let contact_id = approved_contact(&app).await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(google_response(35.5, -82.5)))
+        .expect(0)
+        .mount(&app.gmaps_server)
+        .await;
+
+    // As if a worker had claimed it and was still waiting on the geocoder
+    sqlx::query!(
+        "UPDATE geocoding_jobs SET locked_until = current_timestamp + INTERVAL '5 minutes'"
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    app.dispatch_all_pending_geocoding_jobs().await;
+
+    assert_eq!(1, jobs(&app, "queued").await.len());
+    assert_eq!((None, None), coordinates(&app, contact_id).await);
+}
+
+#[tokio::test]
+async fn unknown_addresses_fail_with_a_reason() {
+    let app = spawn_app().await;
+    approved_contact(&app).await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "status":  "ZERO_RESULTS",
+            "results": []
+        })))
+        .expect(1)
+        .mount(&app.gmaps_server)
+        .await;
+
+    app.dispatch_all_pending_geocoding_jobs().await;
+
+    let failed = jobs(&app, "failed").await;
+    assert_eq!(1, failed.len());
+    assert!(failed[0].last_error.as_ref().unwrap().contains("No results found"));
+}
+
+#[tokio::test]
+async fn changing_address_of_verified_contact_queues_a_job() {
+    let app        = spawn_app().await;
+    let contact_id = approved_contact(&app).await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(google_response(35.5, -82.5)))
+        .mount(&app.gmaps_server)
+        .await;
+    app.dispatch_all_pending_geocoding_jobs().await;
+
+    let contact = app.get_first_contact().await;
+    let edited  = serde_json::json!({
+        "contactId":   contact_id,
+        "userId":      contact.user_id,
+        "displayName": "test for pending",
+        "address":     "16 Lexington Ave",
+        "city":        "asheville",
+        "state":       "NC",
+        "zipCode":     "28801",
+        "ageRange":    "all",
+        "contactType": "venue",
+        "isPrivate":   false,
+        "genres":      [1]
+    });
+    let response = app.admin_edit_contact(edited).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let queued = jobs(&app, "queued").await;
+    assert_eq!(1, queued.len());
+    assert_eq!("address_change", queued[0].reason);
+}
+
+#[tokio::test]
+async fn backfill_queues_contacts_missing_coordinates() {
+    let app = spawn_app().await;
+    approved_contact(&app).await;
+
+    // Approval already queued the contact, so the backfill has nothing to add
+    let response = app.geocode_missing().await;
+    assert_eq!(200, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(0, body["queued"]);
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "status":  "ZERO_RESULTS",
+            "results": []
+        })))
+        .mount(&app.gmaps_server)
+        .await;
+    app.dispatch_all_pending_geocoding_jobs().await;
+
+    let response = app.geocode_missing().await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(1, body["queued"]);
+    assert_eq!("backfill", jobs(&app, "queued").await[0].reason);
+}
+
+#[tokio::test]
+async fn geocoding_jobs_are_admin_only() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    assert_eq!(401, app.get_geocoding_jobs("failed").await.status().as_u16());
+    assert_eq!(401, app.geocode_missing().await.status().as_u16());
+    assert_eq!(401, app.get_address_mismatches().await.status().as_u16());
+}
@@ -1,7 +1,11 @@
 mod auth;
-// mod confirmation;
-// mod contacts;
+mod confirmation;
+mod contacts;
+mod emails;
 // mod genres;
+mod geocoding;
 mod helpers;
+mod organisations;
 // mod health_check;
-// mod reviews;
\ No newline at end of file
+// mod reviews;
+mod tours;
\ No newline at end of file
@@ -0,0 +1,175 @@
+use wiremock::{ResponseTemplate, Mock};
+use wiremock::matchers::{path, method};
+
+use byot_server::domain::{OrgMember, OrgRole, Organisation};
+use crate::helpers::{spawn_app, TestApp, TestUser};
+
+pub async fn create_organisation(app: &TestApp) -> Organisation {
+    let response = app.test_user_login().await;
+    assert_eq!(200, response.status().as_u16());
+
+    let org: Organisation = app.add_organisation(serde_json::json!({
+        "orgName": "the band"
+    }))
+    .await
+    .json()
+    .await
+    .unwrap();
+
+    let response = app.switch_organisation(serde_json::json!({
+        "orgId": org.org_id
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+
+    org
+}
+
+/// Invites `user` to the organisation the current user is acting for and
+/// accepts it as them. Leaves `user` logged in.
+pub async fn invite_and_accept(app: &TestApp, user: &TestUser, role: &str) {
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    let response = app.invite_member(serde_json::json!({
+        "email": &user.email,
+        "role":  role
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+    app.dispatch_all_pending_emails().await;
+
+    let requests      = app.email_server.received_requests().await.unwrap();
+    let email_request = requests.last().unwrap();
+    let invite_links  = app.get_confirmation_links(email_request);
+
+    login_as(app, user).await;
+
+    let response = app.api_client
+        .get(invite_links.html)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(200, response.status().as_u16());
+}
+
+pub async fn login_as(app: &TestApp, user: &TestUser) {
+    app.post_logout().await;
+    let response = app.post_login(serde_json::json!({
+        "email":    &user.email,
+        "password": &user.password
+    })).await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn creator_is_the_owner_of_a_new_organisation() {
+    let app = spawn_app().await;
+    let org = create_organisation(&app).await;
+
+    assert_eq!(OrgRole::Owner, org.role);
+
+    let orgs: Vec<Organisation> = app.get_organisations()
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, orgs.len());
+    assert_eq!("the band", orgs[0].org_name);
+
+    let members: Vec<OrgMember> = app.get_org_members()
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, members.len());
+    assert_eq!(app.test_user.user_id, members[0].user_id);
+}
+
+#[tokio::test]
+async fn invited_user_joins_with_the_invited_role() {
+    let app    = spawn_app().await;
+    let booker = TestUser::generate();
+    booker.store(&app.db_pool).await;
+    let org    = create_organisation(&app).await;
+
+    invite_and_accept(&app, &booker, "booker").await;
+
+    let orgs: Vec<Organisation> = app.get_organisations()
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(1, orgs.len());
+    assert_eq!(org.org_id, orgs[0].org_id);
+    assert_eq!(OrgRole::Booker, orgs[0].role);
+}
+
+#[tokio::test]
+async fn invites_only_work_for_the_invited_email() {
+    let app     = spawn_app().await;
+    let invitee = TestUser::generate();
+    let other   = TestUser::generate();
+    invitee.store(&app.db_pool).await;
+    other.store(&app.db_pool).await;
+    create_organisation(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    app.invite_member(serde_json::json!({ "email": &invitee.email })).await;
+    app.dispatch_all_pending_emails().await;
+
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let invite_links  = app.get_confirmation_links(email_request);
+
+    login_as(&app, &other).await;
+
+    let response = app.api_client
+        .get(invite_links.html)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn only_owners_can_invite_or_remove_members() {
+    let app    = spawn_app().await;
+    let booker = TestUser::generate();
+    booker.store(&app.db_pool).await;
+    let org    = create_organisation(&app).await;
+
+    invite_and_accept(&app, &booker, "booker").await;
+    app.switch_organisation(serde_json::json!({ "orgId": org.org_id })).await;
+
+    let response = app.invite_member(serde_json::json!({
+        "email": "someone@example.com"
+    })).await;
+    assert_eq!(401, response.status().as_u16());
+
+    let response = app.remove_member(serde_json::json!({
+        "userId": app.test_user.user_id
+    })).await;
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn switching_to_an_organisation_requires_membership() {
+    let app   = spawn_app().await;
+    let other = TestUser::generate();
+    other.store(&app.db_pool).await;
+    let org   = create_organisation(&app).await;
+
+    login_as(&app, &other).await;
+
+    let response = app.switch_organisation(serde_json::json!({
+        "orgId": org.org_id
+    })).await;
+    assert_eq!(401, response.status().as_u16());
+}
@@ -0,0 +1,2 @@
+mod members;
+mod shared_data;
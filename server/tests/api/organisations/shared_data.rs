@@ -0,0 +1,119 @@
+use byot_server::domain::{ContactResponse, Tour};
+use crate::helpers::{spawn_app, TestUser};
+use crate::organisations::members::{create_organisation, invite_and_accept, login_as};
+
+#[tokio::test]
+async fn booker_data_is_shared_with_the_organisation() {
+    let app    = spawn_app().await;
+    let booker = TestUser::generate();
+    let member = TestUser::generate();
+    booker.store(&app.db_pool).await;
+    member.store(&app.db_pool).await;
+    let org    = create_organisation(&app).await;
+    let switch = serde_json::json!({ "orgId": org.org_id });
+
+    invite_and_accept(&app, &booker, "booker").await;
+    login_as(&app, &app.test_user).await;
+    app.switch_organisation(&switch).await;
+    invite_and_accept(&app, &member, "member").await;
+
+    // Booker adds a private contact and a tour for the organisation
+    login_as(&app, &booker).await;
+    app.switch_organisation(&switch).await;
+    let is_private = true;
+    let contact: ContactResponse = app.create_contact(is_private)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let tour = app.create_tour().await;
+    assert_eq!(Some(org.org_id), contact.org_id);
+    assert_eq!(Some(org.org_id), tour.org_id);
+
+    // Member sees them once acting for the organisation
+    login_as(&app, &member).await;
+    let tours: Vec<Tour> = app.get_tours().await.json().await.unwrap();
+    assert!(tours.is_empty());
+
+    app.switch_organisation(&switch).await;
+    let tours: Vec<Tour> = app.get_tours().await.json().await.unwrap();
+    assert_eq!(1, tours.len());
+    let contacts: Vec<ContactResponse> = app.user_get_contacts().await.json().await.unwrap();
+    assert_eq!(1, contacts.len());
+    assert_eq!(contact.contact_id, contacts[0].contact_id);
+
+    let response = app.get_tour(&tour.tour_id.to_string()).await;
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn members_can_view_but_not_edit() {
+    let app    = spawn_app().await;
+    let member = TestUser::generate();
+    member.store(&app.db_pool).await;
+    let org    = create_organisation(&app).await;
+    let switch = serde_json::json!({ "orgId": org.org_id });
+    let tour   = app.create_tour().await;
+
+    invite_and_accept(&app, &member, "member").await;
+    app.switch_organisation(&switch).await;
+
+    let response = app.get_tour(&tour.tour_id.to_string()).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let response = app.add_tour_date(serde_json::json!({
+        "tourId":   tour.tour_id,
+        "showDate": "2024-06-03"
+    })).await;
+    assert_eq!(401, response.status().as_u16());
+
+    let response = app.add_tour(serde_json::json!({
+        "tourName":  "winter tour",
+        "startDate": "2024-12-01",
+        "endDate":   "2024-12-14"
+    })).await;
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn removed_members_lose_access_immediately() {
+    let app    = spawn_app().await;
+    let member = TestUser::generate();
+    member.store(&app.db_pool).await;
+    let org    = create_organisation(&app).await;
+    let switch = serde_json::json!({ "orgId": org.org_id });
+    let tour   = app.create_tour().await;
+
+    invite_and_accept(&app, &member, "member").await;
+    app.switch_organisation(&switch).await;
+    let response = app.get_tour(&tour.tour_id.to_string()).await;
+    assert_eq!(200, response.status().as_u16());
+
+    // Owner removes them while their token still names the organisation
+    let owner = reqwest::Client::builder()
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    owner.post(&format!("{}/login", &app.address))
+        .json(&serde_json::json!({
+            "email":    &app.test_user.email,
+            "password": &app.test_user.password
+        }))
+        .send()
+        .await
+        .unwrap();
+    owner.post(&format!("{}/user/organisations/switch", &app.address))
+        .json(&switch)
+        .send()
+        .await
+        .unwrap();
+    let response = owner.post(&format!("{}/user/organisations/remove-member", &app.address))
+        .json(&serde_json::json!({ "userId": member.user_id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(200, response.status().as_u16());
+
+    let response = app.get_tour(&tour.tour_id.to_string()).await;
+    assert_eq!(401, response.status().as_u16());
+}
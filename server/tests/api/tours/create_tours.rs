@@ -0,0 +1,95 @@
+use byot_server::domain::{Tour, TourResponse};
+use crate::helpers::spawn_app;
+
+#[derive(serde::Deserialize)]
+struct Tours(Vec<Tour>);
+
+#[tokio::test]
+async fn unauthenticated_user_cannot_create_tour() {
+    let app  = spawn_app().await;
+    let tour = serde_json::json!({
+        "tourName":  "summer tour",
+        "startDate": "2024-06-01",
+        "endDate":   "2024-06-14"
+    });
+    let response = app.add_tour(&tour).await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn authenticated_user_can_create_tour() {
+    // Log in
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Create tour
+    let tour = app.create_tour().await;
+    assert_eq!(tour.user_id, app.test_user.user_id);
+
+    // Tour is listed for user
+    let tours = app.get_tours()
+        .await
+        .json::<Tours>()
+        .await
+        .unwrap();
+
+    assert_eq!(1, tours.0.len());
+    assert_eq!(tour.tour_id, tours.0.first().unwrap().tour_id);
+}
+
+#[tokio::test]
+async fn tour_cannot_end_before_it_starts() {
+    // Log in
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Create tour
+    let tour = serde_json::json!({
+        "tourName":  "backwards tour",
+        "startDate": "2024-06-14",
+        "endDate":   "2024-06-01"
+    });
+    let response = app.add_tour(&tour).await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn user_cannot_view_anothers_tour() {
+    // Log in and create tour
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    // Log out
+    app.post_logout().await;
+
+    // Another user logs in
+    let response = app.admin_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_tour(&tour.tour_id.to_string()).await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn new_tour_has_no_dates() {
+    // Log in and create tour
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    let tour = app.get_tour(&tour.tour_id.to_string())
+        .await
+        .json::<TourResponse>()
+        .await
+        .unwrap();
+
+    assert!(tour.dates.is_empty());
+}
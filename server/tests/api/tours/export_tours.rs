@@ -0,0 +1,227 @@
+use byot_server::domain::{CalendarSubscription, Tour, TourDate};
+use crate::helpers::{spawn_app, TestApp};
+
+/// Books the test contact as confirmed on the 3rd and pending on the 4th
+async fn tour_with_bookings(app: &TestApp) -> Tour {
+    let tour = app.create_tour().await;
+
+    let is_private = false;
+    let response   = app.create_contact(is_private).await;
+    assert_eq!(200, response.status().as_u16());
+    let contact    = app.get_first_contact().await;
+
+    for (show_date, status) in [("2024-06-03", "confirmed"), ("2024-06-04", "pending")] {
+        let date = serde_json::json!({
+            "tourId":   tour.tour_id,
+            "showDate": show_date
+        });
+        let tour_date = app.add_tour_date(&date)
+            .await
+            .json::<TourDate>()
+            .await
+            .unwrap();
+
+        let json = serde_json::json!({
+            "tourDateId": tour_date.tour_date_id,
+            "contactId":  contact.contact_id,
+            "status":     status
+        });
+        let response = app.set_tour_date_contact(&json).await;
+        assert_eq!(200, response.status().as_u16());
+    }
+
+    tour
+}
+
+#[tokio::test]
+async fn songkick_export_only_contains_confirmed_dates() {
+    // Log in and create tour
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    // Create contact
+    let is_private = false;
+    let response   = app.create_contact(is_private).await;
+    assert_eq!(200, response.status().as_u16());
+    let contact    = app.get_first_contact().await;
+
+    // Add one confirmed and one pending date
+    for (show_date, status) in [("2024-06-03", "confirmed"), ("2024-06-04", "pending")] {
+        let date = serde_json::json!({
+            "tourId":   tour.tour_id,
+            "showDate": show_date
+        });
+        let tour_date = app.add_tour_date(&date)
+            .await
+            .json::<TourDate>()
+            .await
+            .unwrap();
+
+        let json = serde_json::json!({
+            "tourDateId": tour_date.tour_date_id,
+            "contactId":  contact.contact_id,
+            "status":     status
+        });
+        let response = app.set_tour_date_contact(&json).await;
+        assert_eq!(200, response.status().as_u16());
+    }
+
+    // Export
+    let response = app.export_songkick(&tour.tour_id.to_string()).await;
+    assert_eq!(200, response.status().as_u16());
+    assert!(
+        response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/csv")
+    );
+    // Streamed, so the length isn't known up front
+    assert_eq!("chunked", response.headers()["transfer-encoding"]);
+
+    let body  = response.text().await.unwrap();
+    let lines = body.lines().collect::<Vec<_>>();
+
+    assert_eq!(2, lines.len());
+    assert_eq!("Date,Venue Name,Address,City,State,Zip,Country", lines[0]);
+    assert_eq!("2024-06-03,test for pending,123 fake st,asheville,NC,28711,", lines[1]);
+}
+
+#[tokio::test]
+async fn user_cannot_export_anothers_tour() {
+    // Log in and create tour
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    // Log out
+    app.post_logout().await;
+
+    // Another user logs in
+    let response = app.admin_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.export_songkick(&tour.tour_id.to_string()).await;
+
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn ics_export_has_an_event_per_confirmed_date() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = tour_with_bookings(&app).await;
+
+    let response = app.export_ics(&tour.tour_id.to_string(), false).await;
+    assert_eq!(200, response.status().as_u16());
+    assert!(
+        response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/calendar")
+    );
+
+    let body = response.text().await.unwrap();
+
+    assert!(body.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(body.ends_with("END:VCALENDAR\r\n"));
+    assert_eq!(1, body.matches("BEGIN:VEVENT").count());
+    assert!(body.contains("DTSTART;VALUE=DATE:20240603\r\n"));
+    assert!(body.contains("LOCATION:123 fake st\\, asheville\\, NC\\, 28711\r\n"));
+}
+
+#[tokio::test]
+async fn pending_dates_can_be_added_as_tentative() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = tour_with_bookings(&app).await;
+
+    let body = app.export_ics(&tour.tour_id.to_string(), true)
+        .await
+        .text()
+        .await
+        .unwrap();
+
+    assert_eq!(2, body.matches("BEGIN:VEVENT").count());
+    assert_eq!(1, body.matches("STATUS:TENTATIVE").count());
+}
+
+#[tokio::test]
+async fn calendar_subscription_works_without_logging_in_until_revoked() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = tour_with_bookings(&app).await;
+
+    let json         = serde_json::json!({ "tourId": tour.tour_id });
+    let subscription = app.create_calendar_token(&json)
+        .await
+        .json::<CalendarSubscription>()
+        .await
+        .unwrap();
+    assert!(subscription.url.ends_with(&format!("/tour-calendar?token={}", subscription.token)));
+
+    let response = app.get_tour_calendar(&subscription.token).await;
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(1, response.text().await.unwrap().matches("BEGIN:VEVENT").count());
+
+    // Making a new link revokes the old one
+    let replacement = app.create_calendar_token(&json)
+        .await
+        .json::<CalendarSubscription>()
+        .await
+        .unwrap();
+    assert_eq!(401, app.get_tour_calendar(&subscription.token).await.status().as_u16());
+    assert_eq!(200, app.get_tour_calendar(&replacement.token).await.status().as_u16());
+
+    let response = app.revoke_calendar_token(&json).await;
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(401, app.get_tour_calendar(&replacement.token).await.status().as_u16());
+}
+
+#[tokio::test]
+async fn user_cannot_subscribe_to_anothers_tour() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    app.post_logout().await;
+    let response = app.admin_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let json = serde_json::json!({ "tourId": tour.tour_id });
+
+    assert_eq!(401, app.create_calendar_token(&json).await.status().as_u16());
+    assert_eq!(401, app.revoke_calendar_token(&json).await.status().as_u16());
+    assert_eq!(401, app.export_ics(&tour.tour_id.to_string(), false).await.status().as_u16());
+}
+
+#[tokio::test]
+async fn export_filenames_are_safe_for_the_header() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let tour = app.add_tour(&serde_json::json!({
+        "tourName":  "Été tour; 2024",
+        "startDate": "2024-06-01",
+        "endDate":   "2024-06-14"
+    }))
+    .await
+    .json::<Tour>()
+    .await
+    .unwrap();
+
+    let response    = app.export_songkick(&tour.tour_id.to_string()).await;
+    assert_eq!(200, response.status().as_u16());
+    let disposition = response.headers()["content-disposition"].to_str().unwrap();
+
+    assert!(disposition.starts_with("attachment; "));
+    assert!(disposition.contains("filename=\"_t_ tour_ 2024-songkick.csv\""));
+    assert!(disposition.contains("filename*=UTF-8''%C3%89t%C3%A9%20tour%3B%202024%2Dsongkick.csv"));
+}
@@ -0,0 +1,5 @@
+mod create_tours;
+mod export_tours;
+mod plan_route;
+mod tour_dates;
+mod tour_legs;
\ No newline at end of file
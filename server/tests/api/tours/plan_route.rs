@@ -0,0 +1,190 @@
+use byot_server::domain::{RoutePlan, Tour, TourResponse};
+use wiremock::matchers::{method, path_regex, query_param};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::helpers::{spawn_app, TestApp};
+
+/// Adds a located contact and returns its id
+async fn add_candidate(app: &TestApp, name: &str, capacity: i32, is_private: bool) -> i32 {
+    let contact = serde_json::json!({
+        "displayName": name,
+        "city":        "asheville",
+        "capacity":    capacity,
+        "ageRange":    "all",
+        "contactType": "venue",
+        "isPrivate":   is_private,
+        "genres":      [1]
+    });
+    let response = app.add_contact(&contact).await;
+    assert_eq!(200, response.status().as_u16());
+
+    sqlx::query!(
+        "UPDATE contacts SET latitude = 35.5, longitude = -80.0 WHERE display_name = $1 RETURNING contact_id",
+        name
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+    .contact_id
+}
+
+async fn mock_city(app: &TestApp, city: &str) {
+    let location = serde_json::json!({ "lat": 35.5, "lng": -80.0 });
+    let bounds   = serde_json::json!({ "northeast": location, "southwest": location });
+
+    Mock::given(method("GET"))
+        .and(query_param("address", city))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "status": "OK",
+            "results": [{
+                "address_components": [],
+                "formatted_address":  city,
+                "place_id":           city,
+                "types":              ["locality"],
+                "geometry": {
+                    "location":      location,
+                    "location_type": "APPROXIMATE",
+                    "viewport":      bounds
+                }
+            }]
+        })))
+        .mount(&app.gmaps_server)
+        .await;
+}
+
+/// OSRM table for stops along a straight road, 100km per hour
+async fn mock_table(app: &TestApp, positions_km: &[f64]) {
+    let distances: Vec<Vec<f64>> = positions_km
+        .iter()
+        .map(|a| positions_km.iter().map(|b| (a - b).abs() * 1000.0).collect())
+        .collect();
+    let durations: Vec<Vec<f64>> = distances
+        .iter()
+        .map(|row| row.iter().map(|d| d * 0.036).collect())
+        .collect();
+
+    Mock::given(method("GET"))
+        .and(path_regex("^/table/v1/driving/"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "code":      "Ok",
+            "durations": durations,
+            "distances": distances
+        })))
+        .expect(1)
+        .mount(&app.routing_server)
+        .await;
+}
+
+#[tokio::test]
+async fn plan_orders_candidates_and_can_be_saved() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let far    = add_candidate(&app, "far", 200, false).await;
+    let near   = add_candidate(&app, "near", 200, false).await;
+    let middle = add_candidate(&app, "middle", 200, false).await;
+    let small  = add_candidate(&app, "small", 50, false).await;
+
+    mock_city(&app, "plan start").await;
+    mock_city(&app, "plan end").await;
+    // Start, then candidates by id, then the end
+    mock_table(&app, &[0.0, 700.0, 300.0, 500.0, 1000.0]).await;
+
+    let json = serde_json::json!({
+        "startCity":    "plan start",
+        "endCity":      "plan end",
+        "dates":        ["2024-06-03", "2024-06-04", "2024-06-05"],
+        "candidateIds": [far, near, middle, small],
+        "genres":       [1],
+        "minCapacity":  100,
+        "ageRanges":    ["all"]
+    });
+    let response = app.plan_route(&json).await;
+    assert_eq!(200, response.status().as_u16());
+    let plan     = response.json::<RoutePlan>().await.unwrap();
+
+    let order: Vec<i32> = plan.stops.iter().map(|s| s.contact.contact_id).collect();
+    assert_eq!(vec![near, middle, far], order);
+    assert_eq!(vec![small], plan.rejected_ids);
+    assert!(plan.open_dates.is_empty());
+    assert_eq!(1000.0, plan.total_distance_km);
+
+    // Save the plan as a tour
+    let stops: Vec<_> = plan.stops
+        .iter()
+        .map(|s| serde_json::json!({ "showDate": s.show_date, "contactId": s.contact.contact_id }))
+        .collect();
+    let json = serde_json::json!({
+        "tourName": "planned tour",
+        "stops":    stops
+    });
+    let response = app.save_route_plan(&json).await;
+    assert_eq!(200, response.status().as_u16());
+    let tour     = response.json::<Tour>().await.unwrap();
+
+    let tour = app.get_tour(&tour.tour_id.to_string())
+        .await
+        .json::<TourResponse>()
+        .await
+        .unwrap();
+
+    assert_eq!("2024-06-03", tour.start_date.to_string());
+    assert_eq!("2024-06-05", tour.end_date.to_string());
+    assert_eq!(3, tour.dates.len());
+    assert_eq!(near, tour.dates[0].contacts[0].contact.contact_id);
+}
+
+#[tokio::test]
+async fn invalid_plans_are_rejected() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let test_cases = vec![
+        (serde_json::json!({
+            "startCity":    "plan start",
+            "endCity":      "plan end",
+            "dates":        [],
+            "candidateIds": [1]
+        }), "no dates"),
+        (serde_json::json!({
+            "startCity":    "",
+            "endCity":      "plan end",
+            "dates":        ["2024-06-03"],
+            "candidateIds": [1]
+        }), "no start city"),
+        (serde_json::json!({
+            "startCity":     "plan start",
+            "endCity":       "plan end",
+            "dates":         ["2024-06-03"],
+            "candidateIds":  [1],
+            "maxDriveHours": 48
+        }), "too much driving"),
+    ];
+
+    for (json, error) in test_cases {
+        let response = app.plan_route(&json).await;
+
+        assert_eq!(400, response.status().as_u16(), "Plan with {} was accepted", error);
+    }
+}
+
+#[tokio::test]
+async fn other_users_private_contacts_cannot_be_saved() {
+    let app      = spawn_app().await;
+    let response = app.admin_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let private  = add_candidate(&app, "admin's secret", 100, true).await;
+
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let json = serde_json::json!({
+        "tourName": "planned tour",
+        "stops":    [{ "showDate": "2024-06-03", "contactId": private }]
+    });
+    let response = app.save_route_plan(&json).await;
+
+    assert_eq!(400, response.status().as_u16());
+}
@@ -0,0 +1,147 @@
+use byot_server::domain::{BookingStatus, TourDate, TourResponse};
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn date_must_be_within_tour() {
+    // Log in and create tour
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    // Add date after the tour ends
+    let date = serde_json::json!({
+        "tourId":   tour.tour_id,
+        "showDate": "2024-07-01"
+    });
+    let response = app.add_tour_date(&date).await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn moving_a_date_keeps_its_contacts() {
+    // Log in and create tour
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    // Create contact
+    let is_private = false;
+    let response   = app.create_contact(is_private).await;
+    assert_eq!(200, response.status().as_u16());
+    let contact    = app.get_first_contact().await;
+
+    // Add date
+    let date = serde_json::json!({
+        "tourId":   tour.tour_id,
+        "showDate": "2024-06-03"
+    });
+    let tour_date = app.add_tour_date(&date)
+        .await
+        .json::<TourDate>()
+        .await
+        .unwrap();
+
+    // Attach contact to date
+    let json = serde_json::json!({
+        "tourDateId": tour_date.tour_date_id,
+        "contactId":  contact.contact_id,
+        "status":     "pending"
+    });
+    let response = app.set_tour_date_contact(&json).await;
+    assert_eq!(200, response.status().as_u16());
+
+    // Move date
+    let json = serde_json::json!({
+        "tourDateId": tour_date.tour_date_id,
+        "showDate":   "2024-06-05"
+    });
+    let response = app.move_tour_date(&json).await;
+    assert_eq!(200, response.status().as_u16());
+
+    // Contact is still attached
+    let tour = app.get_tour(&tour.tour_id.to_string())
+        .await
+        .json::<TourResponse>()
+        .await
+        .unwrap();
+    let date = tour.dates.first().unwrap();
+
+    assert_eq!("2024-06-05", date.show_date.to_string());
+    assert_eq!(1, date.contacts.len());
+    assert_eq!(contact.contact_id, date.contacts[0].contact.contact_id);
+    assert_eq!(BookingStatus::Pending, date.contacts[0].status);
+}
+
+#[tokio::test]
+async fn status_can_be_updated() {
+    // Log in and create tour
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    // Create contact
+    let is_private = false;
+    let response   = app.create_contact(is_private).await;
+    assert_eq!(200, response.status().as_u16());
+    let contact    = app.get_first_contact().await;
+
+    // Add date
+    let date = serde_json::json!({
+        "tourId":   tour.tour_id,
+        "showDate": "2024-06-03"
+    });
+    let tour_date = app.add_tour_date(&date)
+        .await
+        .json::<TourDate>()
+        .await
+        .unwrap();
+
+    // Attach contact then confirm
+    for status in ["contacted", "confirmed"] {
+        let json = serde_json::json!({
+            "tourDateId": tour_date.tour_date_id,
+            "contactId":  contact.contact_id,
+            "status":     status
+        });
+        let response = app.set_tour_date_contact(&json).await;
+        assert_eq!(200, response.status().as_u16());
+    }
+
+    let tour = app.get_tour(&tour.tour_id.to_string())
+        .await
+        .json::<TourResponse>()
+        .await
+        .unwrap();
+    let date = tour.dates.first().unwrap();
+
+    assert_eq!(1, date.contacts.len());
+    assert_eq!(BookingStatus::Confirmed, date.contacts[0].status);
+}
+
+#[tokio::test]
+async fn user_cannot_add_date_to_anothers_tour() {
+    // Log in and create tour
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    // Log out
+    app.post_logout().await;
+
+    // Another user logs in
+    let response = app.admin_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let date = serde_json::json!({
+        "tourId":   tour.tour_id,
+        "showDate": "2024-06-03"
+    });
+    let response = app.add_tour_date(&date).await;
+
+    assert_eq!(401, response.status().as_u16());
+}
@@ -0,0 +1,205 @@
+use byot_server::domain::{TourDate, TourLegs};
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+use crate::helpers::{spawn_app, TestApp};
+
+/// Adds a contact and books it on a new date, placing it on the map if
+/// coordinates are given
+async fn book_contact(
+    app:         &TestApp,
+    tour_id:     Uuid,
+    name:        &str,
+    show_date:   &str,
+    coordinates: Option<(f32, f32)>
+) -> Uuid {
+    let contact = serde_json::json!({
+        "displayName": name,
+        "city":        "asheville",
+        "ageRange":    "all",
+        "contactType": "venue",
+        "isPrivate":   true,
+        "genres":      [1]
+    });
+    let response = app.add_contact(&contact).await;
+    assert_eq!(200, response.status().as_u16());
+
+    let contact_id = sqlx::query!(
+        "UPDATE contacts SET latitude = $1, longitude = $2 WHERE display_name = $3 RETURNING contact_id",
+        coordinates.map(|c| c.0),
+        coordinates.map(|c| c.1),
+        name
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+    .contact_id;
+
+    let date = serde_json::json!({
+        "tourId":   tour_id,
+        "showDate": show_date
+    });
+    let tour_date = app.add_tour_date(&date)
+        .await
+        .json::<TourDate>()
+        .await
+        .unwrap();
+
+    let json = serde_json::json!({
+        "tourDateId": tour_date.tour_date_id,
+        "contactId":  contact_id,
+        "status":     "confirmed"
+    });
+    let response = app.set_tour_date_contact(&json).await;
+    assert_eq!(200, response.status().as_u16());
+
+    tour_date.tour_date_id
+}
+
+#[tokio::test]
+async fn legs_join_consecutive_dates_and_flag_long_drives() {
+    // Log in and create tour
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    // Book three located venues and one the geocoder hasn't reached yet
+    book_contact(&app, tour.tour_id, "first", "2024-06-03", Some((35.5, -82.5))).await;
+    book_contact(&app, tour.tour_id, "second", "2024-06-04", Some((35.75, -78.75))).await;
+    book_contact(&app, tour.tour_id, "third", "2024-06-07", Some((40.75, -74.0))).await;
+    let unlocated = book_contact(&app, tour.tour_id, "fourth", "2024-06-08", None).await;
+
+    Mock::given(method("GET"))
+        .and(path("/route/v1/driving/-82.5,35.5;-78.75,35.75;-74,40.75"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "code":   "Ok",
+            "routes": [{
+                "legs": [
+                    { "distance": 400000.0, "duration": 14400.0 },
+                    { "distance": 900000.0, "duration": 36000.0 }
+                ]
+            }]
+        })))
+        .expect(1)
+        .mount(&app.routing_server)
+        .await;
+
+    let tour_id  = tour.tour_id.to_string();
+    let response = app.get_tour_legs(&[("tourId", &tour_id), ("maxDriveHours", "3.5")]).await;
+    assert_eq!(200, response.status().as_u16());
+    let legs     = response.json::<TourLegs>().await.unwrap();
+
+    assert_eq!(2, legs.legs.len());
+    assert_eq!("first", legs.legs[0].from.display_name);
+    assert_eq!("second", legs.legs[0].to.display_name);
+    assert_eq!(240.0, legs.legs[0].drive_minutes);
+    assert!(legs.legs[0].too_long);
+    // Ten hours spread over three days is fine
+    assert_eq!(3, legs.legs[1].days_between);
+    assert!(!legs.legs[1].too_long);
+    assert_eq!(1300.0, legs.total_distance_km);
+    assert!(!legs.estimated);
+    assert_eq!(vec![unlocated], legs.unlocated_dates);
+}
+
+#[tokio::test]
+async fn legs_are_estimated_when_the_routing_server_fails() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    book_contact(&app, tour.tour_id, "first", "2024-06-03", Some((35.5, -82.5))).await;
+    book_contact(&app, tour.tour_id, "second", "2024-06-04", Some((35.75, -78.75))).await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&app.routing_server)
+        .await;
+
+    let tour_id  = tour.tour_id.to_string();
+    let response = app.get_tour_legs(&[("tourId", &tour_id)]).await;
+    assert_eq!(200, response.status().as_u16());
+    let legs     = response.json::<TourLegs>().await.unwrap();
+
+    assert_eq!(1, legs.legs.len());
+    assert!(legs.legs[0].estimated);
+    assert!(legs.estimated);
+    assert!(legs.total_distance_km > 0.0);
+}
+
+#[tokio::test]
+async fn tour_with_one_stop_has_no_legs() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    book_contact(&app, tour.tour_id, "only", "2024-06-03", Some((35.5, -82.5))).await;
+
+    let tour_id = tour.tour_id.to_string();
+    let legs    = app.get_tour_legs(&[("tourId", &tour_id)])
+        .await
+        .json::<TourLegs>()
+        .await
+        .unwrap();
+
+    assert!(legs.legs.is_empty());
+    assert_eq!(8.0, legs.max_drive_hours);
+}
+
+#[tokio::test]
+async fn unroutable_tour_is_rejected() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    book_contact(&app, tour.tour_id, "mainland", "2024-06-03", Some((35.5, -82.5))).await;
+    book_contact(&app, tour.tour_id, "island", "2024-06-05", Some((21.3, -157.8))).await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "code":    "NoRoute",
+            "message": "Impossible route between points"
+        })))
+        .mount(&app.routing_server)
+        .await;
+
+    let tour_id  = tour.tour_id.to_string();
+    let response = app.get_tour_legs(&[("tourId", &tour_id)]).await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn invalid_drive_hours_are_rejected() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    let tour_id  = tour.tour_id.to_string();
+    let response = app.get_tour_legs(&[("tourId", &tour_id), ("maxDriveHours", "30")]).await;
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn other_users_cannot_see_tour_legs() {
+    let app      = spawn_app().await;
+    let response = app.test_user_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let tour     = app.create_tour().await;
+
+    let response = app.admin_login().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let tour_id  = tour.tour_id.to_string();
+    let response = app.get_tour_legs(&[("tourId", &tour_id)]).await;
+
+    assert_eq!(401, response.status().as_u16());
+}